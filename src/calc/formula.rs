@@ -0,0 +1,201 @@
+// Molecular formulas: computed from a molecule, written in Hill order and
+// parsed back from strings such as "C6H12O6", "Ca(OH)2" or "C5[13C]H12+".
+use std::{collections::BTreeMap, fmt, str::FromStr};
+
+use crate::{
+    core::{mendeleev::symbol, molecule::Molecule},
+    parsers::{
+        elements::read_symbol,
+        error::{Error, ErrorKind},
+        scanner::{missing_character, Scanner},
+    },
+};
+
+/// Element counts and net charge.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Formula {
+    /// Counts keyed by (atomic number, mass number), with mass number 0 for
+    /// the natural isotopic mix.
+    pub counts: BTreeMap<(usize, usize), usize>,
+    pub charge: i32,
+}
+
+impl Formula {
+    /// Atoms of an element, all isotopes together.
+    pub fn count(&self, atomic_number: usize) -> usize {
+        self.counts
+            .range((atomic_number, 0)..=(atomic_number, usize::MAX))
+            .map(|(_, &n)| n)
+            .sum()
+    }
+
+    /// Counts per atomic number, isotopes merged.
+    pub fn element_counts(&self) -> BTreeMap<usize, usize> {
+        let mut counts = BTreeMap::new();
+        for (&(z, _), &n) in &self.counts {
+            *counts.entry(z).or_insert(0) += n;
+        }
+        counts
+    }
+
+    // None when the count no longer fits
+    fn add(&mut self, atomic_number: usize, mass_number: usize, count: usize) -> Option<()> {
+        if count > 0 {
+            let total = self.counts.entry((atomic_number, mass_number)).or_insert(0);
+            *total = total.checked_add(count)?;
+        }
+        Some(())
+    }
+}
+
+/// Hill order: carbon, then hydrogen, then the rest alphabetically. Without
+/// carbon everything is alphabetical. Labelled isotopes follow the natural
+/// element as `[13C]`, the charge goes last as `+`, `-2` and so on.
+impl fmt::Display for Formula {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut elements: Vec<usize> = self.element_counts().into_keys().collect();
+        let has_carbon = elements.contains(&6);
+        elements.sort_by_key(|&z| match (has_carbon, z) {
+            (true, 6) => (0, ""),
+            (true, 1) => (1, ""),
+            _ => (2, symbol(z)),
+        });
+
+        for z in elements {
+            for (&(_, mass_number), &n) in self.counts.range((z, 0)..=(z, usize::MAX)) {
+                if mass_number == 0 {
+                    write!(f, "{}", symbol(z))?;
+                } else {
+                    write!(f, "[{}{}]", mass_number, symbol(z))?;
+                }
+                if n > 1 {
+                    write!(f, "{}", n)?;
+                }
+            }
+        }
+
+        match self.charge {
+            0 => Ok(()),
+            1 => write!(f, "+"),
+            -1 => write!(f, "-"),
+            c if c > 0 => write!(f, "+{}", c),
+            c => write!(f, "{}", c),
+        }
+    }
+}
+
+impl FromStr for Formula {
+    type Err = Error;
+
+    fn from_str(formula: &str) -> Result<Formula, Error> {
+        parse_formula(formula)
+    }
+}
+
+/// Parses a formula with optional parenthesised groups, `[13C]` style
+/// isotopes and a trailing charge such as `+`, `-` or `+2`.
+pub fn parse_formula(formula: &str) -> Result<Formula, Error> {
+    let mut scanner = Scanner::new(formula);
+    let mut result = read_group(&mut scanner, false)?;
+
+    if let Some(&sign) = scanner.peek() {
+        let sign = match sign {
+            '+' => 1,
+            '-' => -1,
+            _ => return Err(missing_character(&mut scanner)),
+        };
+        scanner.pop();
+        let start = scanner.cursor();
+        let charge = i32::try_from(read_count(&mut scanner)?)
+            .map_err(|_| scanner.error_at(start, ErrorKind::InvalidNumber))?;
+        result.charge = sign * charge;
+    }
+    if !scanner.is_done() {
+        return Err(missing_character(&mut scanner));
+    }
+    Ok(result)
+}
+
+fn read_group(scanner: &mut Scanner, nested: bool) -> Result<Formula, Error> {
+    let mut group = Formula::default();
+    loop {
+        match scanner.peek() {
+            Some('(') => {
+                scanner.pop();
+                let inner = read_group(scanner, true)?;
+                if scanner.pop() != Some(&')') {
+                    return Err(missing_character(scanner));
+                }
+                let start = scanner.cursor();
+                let repeat = read_count(scanner)?;
+                for (&(z, mass_number), &n) in &inner.counts {
+                    n.checked_mul(repeat)
+                        .and_then(|count| group.add(z, mass_number, count))
+                        .ok_or_else(|| scanner.error_at(start, ErrorKind::InvalidNumber))?;
+                }
+            }
+            Some('[') => {
+                let start = scanner.cursor();
+                scanner.pop();
+                let mass_number = read_count(scanner)?;
+                let z = read_symbol(scanner)?;
+                if scanner.pop() != Some(&']') {
+                    return Err(missing_character(scanner));
+                }
+                let count = read_count(scanner)?;
+                group
+                    .add(z, mass_number, count)
+                    .ok_or_else(|| scanner.error_at(start, ErrorKind::InvalidNumber))?;
+            }
+            Some(c) if c.is_ascii_uppercase() => {
+                let start = scanner.cursor();
+                let z = read_symbol(scanner)?;
+                let count = read_count(scanner)?;
+                group
+                    .add(z, 0, count)
+                    .ok_or_else(|| scanner.error_at(start, ErrorKind::InvalidNumber))?;
+            }
+            Some(')') if nested => return Ok(group),
+            Some(')') => return Err(missing_character(scanner)),
+            _ if nested => return Err(missing_character(scanner)),
+            _ => return Ok(group),
+        }
+    }
+}
+
+// Digits as a number, 1 when there are none
+fn read_count(scanner: &mut Scanner) -> Result<usize, Error> {
+    let start = scanner.cursor();
+    let mut digits = String::new();
+    while let Some(&c) = scanner.peek() {
+        if !c.is_ascii_digit() {
+            break;
+        }
+        digits.push(c);
+        scanner.pop();
+    }
+    if digits.is_empty() {
+        return Ok(1);
+    }
+    digits
+        .parse()
+        .map_err(|_| scanner.error_at(start, ErrorKind::InvalidNumber))
+}
+
+impl Molecule {
+    /// Formula including implicit hydrogens, isotopes and net charge.
+    pub fn formula(&self) -> Formula {
+        let mut formula = Formula::default();
+        for atom in &self.atoms {
+            formula.add(atom.element, atom.isotope, 1);
+            formula.add(1, 0, atom.hydrogens);
+            formula.charge += atom.f_charge as i32;
+        }
+        formula
+    }
+
+    /// Hill ordered molecular formula, e.g. "C7H5O2-" for benzoate.
+    pub fn molecular_formula(&self) -> String {
+        self.formula().to_string()
+    }
+}
@@ -0,0 +1,42 @@
+// Molecular masses. Atoms with `isotope` set use that isotope's mass, from
+// the natural abundance tables or the common tracers (3H, 14C, 18F...); only
+// for isotopes in neither is the mass number used as an approximation.
+use crate::core::{
+    mendeleev::{element, Element},
+    molecule::Molecule,
+};
+
+// CODATA 2018, in daltons
+const ELECTRON_MASS: f64 = 0.000_548_579_909_065;
+
+impl Molecule {
+    /// Average molecular weight from standard atomic weights, implicit
+    /// hydrogens included.
+    pub fn molecular_weight(&self) -> f64 {
+        self.mass_with(|e| e.average_mass)
+    }
+
+    /// Monoisotopic mass: every atom as its most abundant isotope, less the
+    /// electrons gained or lost by charged atoms.
+    pub fn exact_mass(&self) -> f64 {
+        let charge: i32 = self.atoms.iter().map(|a| a.f_charge as i32).sum();
+        self.mass_with(|e| e.monoisotopic_mass) - charge as f64 * ELECTRON_MASS
+    }
+
+    fn mass_with(&self, unlabelled: impl Fn(&Element) -> f64) -> f64 {
+        let hydrogen = element(1).map_or(0.0, &unlabelled);
+        self.atoms
+            .iter()
+            .map(|atom| {
+                let heavy = match (element(atom.element), atom.isotope) {
+                    (None, _) => 0.0,
+                    (Some(e), 0) => unlabelled(e),
+                    (Some(e), mass_number) => {
+                        e.isotope_mass(mass_number).unwrap_or(mass_number as f64)
+                    }
+                };
+                heavy + atom.hydrogens as f64 * hydrogen
+            })
+            .sum()
+    }
+}
@@ -1 +1,3 @@
+pub mod formula;
+pub mod mass;
 pub mod tanimoto;
@@ -0,0 +1,187 @@
+// Aromaticity perception on a Kekulé graph. Candidate rings are the SSSR
+// rings and the envelopes of fused rings, each tested with Hückel's 4n+2 rule
+// using per-atom pi electron counts that depend on the model.
+use super::{molecule::Molecule, rings::RingInfo};
+
+// Fused systems up to this many rings have every connected subset of rings
+// tested, larger ones only single rings and pairs
+const MAX_EXHAUSTIVE_RINGS: usize = 10;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AromaticityModel {
+    /// Lone pairs of N, O, S count and exocyclic double bonds to
+    /// electronegative atoms are allowed, as in pyridone.
+    Daylight,
+    /// Only rings of alternating single and double bonds, no lone pairs.
+    Mdl,
+    /// Daylight electron counting, but only for 5 and 6 membered rings
+    /// and with no exocyclic double bonds.
+    Simple,
+}
+
+impl Molecule {
+    /// Sets `Atom.aromatic` and `Bond.arom` from the bond orders, replacing
+    /// whatever flags were there. Aromatic bonds keep their Kekulé orders.
+    pub fn perceive_aromaticity(&mut self, model: AromaticityModel) {
+        let computed;
+        let rings = match self.ring_info() {
+            Some(rings) => rings,
+            None => {
+                computed = RingInfo::new(self);
+                &computed
+            }
+        };
+
+        let mut aromatic_atoms = vec![false; self.atoms.len()];
+        let mut aromatic_bonds = vec![false; self.bonds.len()];
+        for system in rings.ring_systems() {
+            let system: Vec<usize> = system
+                .into_iter()
+                .filter(|&r| {
+                    model != AromaticityModel::Simple || matches!(rings.sssr()[r].len(), 5 | 6)
+                })
+                .collect();
+            for subset in ring_subsets(rings, &system) {
+                let (atoms, bonds) = envelope(rings, &subset, self.atoms.len(), self.bonds.len());
+                if self.is_huckel(&atoms, &bonds, model) {
+                    for (atom_idx, _) in atoms.iter().enumerate().filter(|(_, &a)| a) {
+                        aromatic_atoms[atom_idx] = true;
+                    }
+                    for (bond_idx, _) in bonds.iter().enumerate().filter(|(_, &b)| b) {
+                        aromatic_bonds[bond_idx] = true;
+                    }
+                }
+            }
+        }
+
+        for (atom, aromatic) in self.atoms.iter_mut().zip(aromatic_atoms) {
+            atom.aromatic = aromatic;
+        }
+        for (bond, aromatic) in self.bonds.iter_mut().zip(aromatic_bonds) {
+            bond.arom = aromatic;
+        }
+    }
+
+    fn is_huckel(&self, atoms: &[bool], bonds: &[bool], model: AromaticityModel) -> bool {
+        let mut electrons = 0;
+        for atom_idx in (0..self.atoms.len()).filter(|&a| atoms[a]) {
+            match self.pi_electrons(atom_idx, bonds, model) {
+                Some(count) => electrons += count,
+                None => return false,
+            }
+        }
+        electrons % 4 == 2
+    }
+
+    // Pi electrons the atom gives to a ring made of `ring_bonds`, None if
+    // the atom can't be part of an aromatic ring under the model
+    fn pi_electrons(
+        &self,
+        atom_idx: usize,
+        ring_bonds: &[bool],
+        model: AromaticityModel,
+    ) -> Option<usize> {
+        let atom = &self.atoms[atom_idx];
+        let mut in_ring_double = 0;
+        let mut exocyclic_double = None;
+        for &bond_idx in self.bonds_of(atom_idx) {
+            let bond = &self.bonds[bond_idx];
+            match bond.bond_order {
+                1 => {}
+                2 if ring_bonds[bond_idx] => in_ring_double += 1,
+                2 => exocyclic_double = Some(bond.other(atom_idx)),
+                // Triple bonds and anything stranger
+                _ => return None,
+            }
+        }
+
+        match (in_ring_double, exocyclic_double) {
+            (1, None) => return Some(1),
+            (0, None) => {}
+            (0, Some(partner)) if model == AromaticityModel::Daylight => {
+                // C=O, C=N and C=S pull the electrons out of the ring
+                let partner = self.atoms[partner].element;
+                return matches!(partner, 7 | 8 | 16).then_some(0);
+            }
+            _ => return None,
+        }
+        if model == AromaticityModel::Mdl {
+            return None;
+        }
+
+        // No double bond: a lone pair, an empty p orbital, or sp3
+        match (atom.element, atom.f_charge) {
+            // Boron has an empty p orbital, as does a carbocation
+            (5, 0) | (6, 1) => Some(0),
+            // Carbanion, pyrrole type N and P, furan and thiophene type atoms
+            (6, -1) | (7, 0) | (7, -1) | (15, 0) | (33, 0) => Some(2),
+            (8, 0) | (16, 0) | (34, 0) | (52, 0) => Some(2),
+            _ => None,
+        }
+    }
+}
+
+// Connected subsets of the rings of one fused system
+fn ring_subsets(rings: &RingInfo, system: &[usize]) -> Vec<Vec<usize>> {
+    let fused = |a: usize, b: usize| {
+        rings.sssr_bonds()[a]
+            .iter()
+            .any(|bond| rings.sssr_bonds()[b].contains(bond))
+    };
+
+    if system.len() > MAX_EXHAUSTIVE_RINGS {
+        let mut subsets: Vec<Vec<usize>> = system.iter().map(|&r| vec![r]).collect();
+        for (i, &a) in system.iter().enumerate() {
+            for &b in &system[i + 1..] {
+                if fused(a, b) {
+                    subsets.push(vec![a, b]);
+                }
+            }
+        }
+        return subsets;
+    }
+
+    let mut subsets = Vec::new();
+    for mask in 1u32..(1 << system.len()) {
+        let subset: Vec<usize> = (0..system.len())
+            .filter(|&i| mask >> i & 1 == 1)
+            .map(|i| system[i])
+            .collect();
+
+        // Grow from the first ring through shared bonds
+        let mut reached = vec![subset[0]];
+        let mut i = 0;
+        while i < reached.len() {
+            for &ring in &subset {
+                if !reached.contains(&ring) && fused(reached[i], ring) {
+                    reached.push(ring);
+                }
+            }
+            i += 1;
+        }
+        if reached.len() == subset.len() {
+            subsets.push(subset);
+        }
+    }
+    subsets
+}
+
+// Atoms and bonds of a set of rings, as masks
+fn envelope(
+    rings: &RingInfo,
+    subset: &[usize],
+    n_atoms: usize,
+    n_bonds: usize,
+) -> (Vec<bool>, Vec<bool>) {
+    let mut atoms = vec![false; n_atoms];
+    let mut bonds = vec![false; n_bonds];
+    for &ring in subset {
+        for &atom_idx in &rings.sssr()[ring] {
+            atoms[atom_idx] = true;
+        }
+        for &bond_idx in &rings.sssr_bonds()[ring] {
+            bonds[bond_idx] = true;
+        }
+    }
+    (atoms, bonds)
+}
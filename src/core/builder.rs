@@ -0,0 +1,143 @@
+// Building molecules in code. The builder only adds atoms and bonds through
+// `Molecule::add_atom`/`add_bond`, then finishes the molecule the way the
+// SMILES parser does: implicit hydrogens, Kekulé form, rings, aromaticity
+// and symmetry classes.
+use super::{
+    aromaticity::AromaticityModel,
+    defs::{Atom, Bond},
+    kekulize::KekulizeError,
+    mendeleev::element_by_symbol,
+    molecule::Molecule,
+    valence::{aromatic_implicit_h_count, implicit_h_count},
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BondOrder {
+    Single,
+    Double,
+    Triple,
+    Quadruple,
+    /// Kekulized when the molecule is built.
+    Aromatic,
+}
+
+impl BondOrder {
+    /// `Bond.bond_order`, aromatic bonds count as single until kekulized.
+    pub fn order(self) -> i8 {
+        match self {
+            BondOrder::Single | BondOrder::Aromatic => 1,
+            BondOrder::Double => 2,
+            BondOrder::Triple => 3,
+            BondOrder::Quadruple => 4,
+        }
+    }
+}
+
+/// Adds atoms and bonds one call at a time, e.g. acetaldehyde as
+/// `MoleculeBuilder::new().atom("C").atom("C").bond_to(0, BondOrder::Single)
+/// .atom("O").bond_to(1, BondOrder::Double).build()`.
+#[derive(Default)]
+pub struct MoleculeBuilder {
+    molecule: Molecule,
+    // Atoms added with a hydrogen count of their own, like SMILES bracket atoms
+    fixed_hydrogens: Vec<bool>,
+}
+
+impl MoleculeBuilder {
+    pub fn new() -> MoleculeBuilder {
+        MoleculeBuilder::default()
+    }
+
+    /// Adds an atom by element symbol, lowercase ("c", "n") for aromatic
+    /// atoms. Its hydrogens are computed on `build`. Panics on an unknown
+    /// symbol.
+    pub fn atom(self, symbol: &str) -> MoleculeBuilder {
+        let aromatic = symbol.starts_with(|c: char| c.is_ascii_lowercase());
+        let mut chars = symbol.chars();
+        let symbol: String = chars
+            .next()
+            .map(|c| c.to_ascii_uppercase())
+            .into_iter()
+            .chain(chars)
+            .collect();
+        let element = match symbol.as_str() {
+            "*" => 0,
+            _ => {
+                element_by_symbol(&symbol)
+                    .unwrap_or_else(|| panic!("unknown element symbol {:?}", symbol))
+                    .atomic_number
+            }
+        };
+        self.push(Atom::new(element).with_aromatic(aromatic), false)
+    }
+
+    /// Adds `atom` as given, keeping its hydrogen count.
+    pub fn add_atom(self, atom: Atom) -> MoleculeBuilder {
+        self.push(atom, true)
+    }
+
+    /// Bonds the last added atom to `atom_idx`. Panics without atoms or for
+    /// an index out of range.
+    pub fn bond_to(self, atom_idx: usize, order: BondOrder) -> MoleculeBuilder {
+        let last = self
+            .molecule
+            .atoms
+            .len()
+            .checked_sub(1)
+            .expect("bond_to before any atom");
+        self.bond(atom_idx, last, order)
+    }
+
+    /// Bonds two atoms already added. Panics for an index out of range.
+    pub fn bond(mut self, source: usize, dest: usize, order: BondOrder) -> MoleculeBuilder {
+        let atom_count = self.molecule.atoms.len();
+        assert!(
+            source < atom_count && dest < atom_count && source != dest,
+            "cannot bond atoms {} and {} of {}",
+            source,
+            dest,
+            atom_count
+        );
+        let mut bond = Bond::new(source, dest, order.order());
+        bond.arom = order == BondOrder::Aromatic;
+        self.molecule.add_bond(bond);
+        self
+    }
+
+    /// Index the next added atom will get.
+    pub fn next_index(&self) -> usize {
+        self.molecule.atoms.len()
+    }
+
+    /// The finished molecule, or the error from kekulizing its aromatic
+    /// atoms and bonds.
+    pub fn build(self) -> Result<Molecule, KekulizeError> {
+        let mut molecule = self.molecule;
+        for (atom_idx, &fixed) in self.fixed_hydrogens.iter().enumerate() {
+            if fixed {
+                continue;
+            }
+            // Aromatic bonds are all still single here
+            let valence = molecule.explicit_valence(atom_idx);
+            let atom = &molecule.atoms[atom_idx];
+            let h_count = if atom.aromatic {
+                aromatic_implicit_h_count(atom.element, atom.f_charge, valence)
+            } else {
+                implicit_h_count(atom.element, atom.f_charge, valence)
+            };
+            molecule.h_count_update(atom_idx, h_count);
+        }
+
+        molecule.kekulize()?;
+        molecule.perceive_rings();
+        molecule.perceive_aromaticity(AromaticityModel::Daylight);
+        molecule.perceive_symmetry_classes();
+        Ok(molecule)
+    }
+
+    fn push(mut self, atom: Atom, fixed_hydrogens: bool) -> MoleculeBuilder {
+        self.molecule.add_atom(atom);
+        self.fixed_hydrogens.push(fixed_hydrogens);
+        self
+    }
+}
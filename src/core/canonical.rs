@@ -0,0 +1,525 @@
+// Canonical atom ranking by iterative refinement, in the spirit of Morgan's
+// algorithm and CANON (Weininger 1989).
+//
+// Atoms start out ranked on local invariants. Each pass re-ranks them on
+// their own rank plus the sorted ranks of their neighbours, until the number
+// of classes stops growing; the result is the symmetry classes. Stereo
+// enters once the neighbours of a centre have distinct ranks: its parity
+// relative to those ranks becomes one more invariant.
+//
+// Canonical ranks break the remaining ties by individualization-refinement
+// (McKay's scheme, as in nauty): an atom of the first tied class is given a
+// rank of its own and the partition refined again, once for every atom of
+// the class, recursively. Each complete ranking relabels the molecule, and
+// the smallest relabelled molecule wins. Two rankings giving the same one
+// differ by an automorphism: the search jumps back to where their paths
+// part, and the automorphism joins the orbits used to skip atoms that could
+// only repeat a branch already searched. A node whose partition differs
+// from the first path's only in singletons is tried as such a pair before
+// being searched. Connected components are ranked separately and ordered
+// by their relabelled forms.
+use super::{
+    configuration::Configuration,
+    defs::{Axialness, Bond},
+    molecule::Molecule,
+};
+
+// Stand-in for the implicit hydrogen in a stereocentre's neighbour list
+const IMPLICIT_H: usize = usize::MAX;
+
+impl Molecule {
+    /// Symmetry class of every atom. Atoms share a class when refinement
+    /// cannot tell them apart; classes are numbered from 0 in canonical order.
+    pub fn symmetry_classes(&self) -> Vec<usize> {
+        let keys: Vec<_> = (0..self.atoms.len())
+            .map(|atom_idx| self.atom_key(atom_idx))
+            .collect();
+        let mut ranks = dense_ranks(&keys);
+
+        let mut stereo = vec![0; self.atoms.len()];
+        loop {
+            ranks = self.refine(ranks);
+            let next = self.stereo_invariants(&ranks);
+            if next == stereo {
+                return ranks;
+            }
+            stereo = next;
+            let keys: Vec<_> = ranks.iter().zip(&stereo).collect();
+            ranks = dense_ranks(&keys);
+        }
+    }
+
+    /// Canonical rank of every atom: a permutation of `0..n` that does not
+    /// depend on the input atom order.
+    pub fn canonical_ranks(&self) -> Vec<usize> {
+        let fragments = self.split_fragments();
+        if fragments.len() < 2 {
+            return self.component_ranks().0;
+        }
+        // Components are ranked on their own, so identical ones do not
+        // multiply the search, then ordered by what they ranked to
+        let mut components: Vec<_> = fragments
+            .into_iter()
+            .map(|fragment| {
+                let (ranks, form) = fragment.molecule.component_ranks();
+                (form, ranks, fragment.atoms)
+            })
+            .collect();
+        components.sort_by(|a, b| a.0.cmp(&b.0));
+        let mut ranks = vec![0; self.atoms.len()];
+        let mut offset = 0;
+        for (_, local_ranks, atoms) in &components {
+            for (&atom_idx, &rank) in atoms.iter().zip(local_ranks) {
+                ranks[atom_idx] = offset + rank;
+            }
+            offset += atoms.len();
+        }
+        ranks
+    }
+
+    /// Stores the symmetry classes in `Atom.symmetry_class`.
+    pub fn perceive_symmetry_classes(&mut self) {
+        for (atom_idx, class) in self.symmetry_classes().into_iter().enumerate() {
+            self.atoms[atom_idx].sym_class_update(class);
+        }
+    }
+
+    // Canonical ranks of a connected molecule, with its form under them
+    fn component_ranks(&self) -> (Vec<usize>, ComponentForm) {
+        let mut search = CanonicalSearch {
+            molecule: self,
+            first: None,
+            best: None,
+            first_partitions: Vec::new(),
+            automorphisms: Vec::new(),
+            orbits: Vec::new(),
+        };
+        search.visit(self.symmetry_classes(), &mut Vec::new());
+        let Some(best) = search.best else {
+            return (Vec::new(), Default::default());
+        };
+        let mut atom_keys = vec![Default::default(); self.atoms.len()];
+        for (atom_idx, &rank) in best.ranks.iter().enumerate() {
+            atom_keys[rank] = self.atom_key(atom_idx);
+        }
+        (best.ranks, (atom_keys, best.form))
+    }
+
+    fn atom_key(&self, atom_idx: usize) -> AtomKey {
+        let atom = &self.atoms[atom_idx];
+        (
+            self.degree(atom_idx),
+            atom.element,
+            atom.isotope,
+            atom.f_charge,
+            atom.hydrogens,
+            atom.aromatic,
+            atom.ring,
+            atom.atom_map,
+        )
+    }
+
+    // The molecule relabelled by a complete ranking
+    fn relabelled(&self, ranks: &[usize]) -> Form {
+        let mut bonds: Vec<_> = self
+            .bonds
+            .iter()
+            .map(|bond| {
+                let (a, b) = (ranks[bond.source], ranks[bond.dest]);
+                let order = if bond.arom { i8::MAX } else { bond.bond_order };
+                (a.min(b), a.max(b), order)
+            })
+            .collect();
+        bonds.sort_unstable();
+        let mut stereo = vec![0; ranks.len()];
+        for (atom_idx, invariant) in self.stereo_invariants(ranks).into_iter().enumerate() {
+            stereo[ranks[atom_idx]] = invariant;
+        }
+        (bonds, stereo)
+    }
+
+    // Re-ranks on neighbour ranks and bond orders until the partition is stable
+    fn refine(&self, ranks: Vec<usize>) -> Vec<usize> {
+        // Aromatic bonds compare equal whatever the Kekulé form
+        self.refine_on(ranks, &|bond| {
+            if bond.arom {
+                i8::MAX
+            } else {
+                bond.bond_order
+            }
+        })
+    }
+
+    // Re-ranks on neighbour ranks and `bond_key` until the partition is stable
+    pub(super) fn refine_on(
+        &self,
+        mut ranks: Vec<usize>,
+        bond_key: &dyn Fn(&Bond) -> i8,
+    ) -> Vec<usize> {
+        loop {
+            let keys: Vec<_> = (0..self.atoms.len())
+                .map(|atom_idx| {
+                    let mut neighbors: Vec<(usize, i8)> = self
+                        .bonds_of(atom_idx)
+                        .iter()
+                        .map(|&b| {
+                            let bond = &self.bonds[b];
+                            (ranks[bond.other(atom_idx)], bond_key(bond))
+                        })
+                        .collect();
+                    neighbors.sort_unstable();
+                    (ranks[atom_idx], neighbors)
+                })
+                .collect();
+            let refined = dense_ranks(&keys);
+            if class_count(&refined) == class_count(&ranks) {
+                return refined;
+            }
+            ranks = refined;
+        }
+    }
+
+    // 1 or 2 for stereo the current ranks can describe, 0 otherwise
+    fn stereo_invariants(&self, ranks: &[usize]) -> Vec<usize> {
+        let mut stereo = vec![0; self.atoms.len()];
+        for (atom_idx, atom) in self.atoms.iter().enumerate() {
+            if let Some(configuration) = &atom.configuration {
+                stereo[atom_idx] = self.tetrahedral_parity(atom_idx, configuration, ranks);
+            }
+        }
+        for bond in &self.bonds {
+            if bond.bond_order != 2 || bond.arom {
+                continue;
+            }
+            let parity = self.double_bond_parity(bond.source, bond.dest, ranks);
+            if parity != 0 {
+                stereo[bond.source] = parity;
+                stereo[bond.dest] = parity;
+            }
+        }
+        stereo
+    }
+
+    // Parity of a tetrahedral centre with its neighbours listed by rank
+    fn tetrahedral_parity(
+        &self,
+        atom_idx: usize,
+        configuration: &Configuration,
+        ranks: &[usize],
+    ) -> usize {
+        let clockwise = match configuration {
+            Configuration::TH1 => false,
+            Configuration::TH2 => true,
+            _ => return 0,
+        };
+        let mut order: Vec<usize> = self.neighbors(atom_idx).map(|n| ranks[n]).collect();
+        if self.atoms[atom_idx].hydrogens > 0 {
+            order.insert(order.len().min(1), IMPLICIT_H);
+        }
+        let mut sorted = order.clone();
+        sorted.sort_unstable();
+        sorted.dedup();
+        if sorted.len() != order.len() || order.len() < 3 {
+            return 0;
+        }
+        let inversions = (0..order.len())
+            .flat_map(|i| (i + 1..order.len()).map(move |j| (i, j)))
+            .filter(|&(i, j)| order[i] > order[j])
+            .count();
+        if clockwise == (inversions % 2 == 1) {
+            1
+        } else {
+            2
+        }
+    }
+
+    // 1 for cis, 2 for trans between the highest ranked neighbours on each
+    // side, from the `/` and `\` marks on the adjacent single bonds
+    fn double_bond_parity(&self, left: usize, right: usize, ranks: &[usize]) -> usize {
+        let (Some(left_side), Some(right_side)) = (
+            self.double_bond_side(left, right, ranks),
+            self.double_bond_side(right, left, ranks),
+        ) else {
+            return 0;
+        };
+        if left_side == right_side {
+            1
+        } else {
+            2
+        }
+    }
+
+    // Whether the highest ranked neighbour of `atom_idx` (other than
+    // `across`) sits "up", or None if the side carries no usable stereo
+    fn double_bond_side(&self, atom_idx: usize, across: usize, ranks: &[usize]) -> Option<bool> {
+        let substituents: Vec<usize> = self
+            .bonds_of(atom_idx)
+            .iter()
+            .copied()
+            .filter(|&b| self.bonds[b].other(atom_idx) != across)
+            .collect();
+        if substituents.is_empty() || substituents.len() > 2 {
+            return None;
+        }
+        let rank_of = |b: usize| ranks[self.bonds[b].other(atom_idx)];
+        if substituents.len() == 2 && rank_of(substituents[0]) == rank_of(substituents[1]) {
+            return None;
+        }
+
+        let &marked = substituents
+            .iter()
+            .find(|&&b| self.bonds[b].axialness != Axialness::UNKNOWN)?;
+        let bond = &self.bonds[marked];
+        // Direction of the substituent as seen from `atom_idx`
+        let up = (bond.axialness == Axialness::UP) == (bond.source == atom_idx);
+        let highest = substituents.iter().copied().max_by_key(|&b| rank_of(b))?;
+        Some(if marked == highest { up } else { !up })
+    }
+}
+
+// The molecule relabelled by a complete ranking: its bonds as sorted
+// (rank, rank, order) and the stereo invariant of the atom at each rank
+type Form = (Vec<(usize, usize, i8)>, Vec<usize>);
+
+// Local invariants an atom is first ranked on
+type AtomKey = (usize, usize, usize, i8, usize, bool, bool, usize);
+
+// A component's atom invariants in canonical order and its relabelled form,
+// by which identical components compare equal
+type ComponentForm = (Vec<AtomKey>, Form);
+
+// A complete ranking, the atoms individualized to reach it and its form
+#[derive(Clone)]
+struct Leaf {
+    path: Vec<usize>,
+    ranks: Vec<usize>,
+    form: Form,
+}
+
+// The individualization-refinement search behind `canonical_ranks`
+struct CanonicalSearch<'a> {
+    molecule: &'a Molecule,
+    // The first complete ranking reached and the smallest one so far
+    first: Option<Leaf>,
+    best: Option<Leaf>,
+    // The partition at each node on the way to `first`
+    first_partitions: Vec<Vec<usize>>,
+    // Found as pairs of branches with equal relabelled molecules
+    automorphisms: Vec<Vec<usize>>,
+    // Union-find of the orbits at each node on the current path, under the
+    // automorphisms found so far that fix the atoms individualized above it
+    orbits: Vec<Vec<usize>>,
+}
+
+impl CanonicalSearch<'_> {
+    // `path` holds the atoms individualized on the way to `ranks`. Returns
+    // the depth to resume at when the rest of a branch is known to repeat
+    // one already searched
+    fn visit(&mut self, ranks: Vec<usize>, path: &mut Vec<usize>) -> Option<usize> {
+        let depth = path.len();
+        if self.first.is_none() {
+            self.first_partitions.push(ranks.clone());
+        } else if let Some(gamma) = self.first_path_automorphism(&ranks, depth) {
+            let first_path = self.first.as_ref().unwrap().path.clone();
+            return self.automorphism_found(gamma, &first_path, path);
+        }
+        let Some(rank) = first_tied_class(&ranks) else {
+            return self.leaf(ranks, path);
+        };
+        let orbits = self.orbits_fixing(path);
+        self.orbits.push(orbits);
+        let mut searched: Vec<usize> = Vec::new();
+        let mut resume = None;
+        for pick in 0..ranks.len() {
+            if ranks[pick] != rank {
+                continue;
+            }
+            // An automorphism fixing the path that takes a searched atom to
+            // `pick` maps that branch onto this one
+            let orbits = &self.orbits[depth];
+            if searched
+                .iter()
+                .any(|&atom_idx| root(orbits, atom_idx) == root(orbits, pick))
+            {
+                continue;
+            }
+            let keys: Vec<_> = ranks
+                .iter()
+                .enumerate()
+                .map(|(atom_idx, &r)| (r, atom_idx != pick))
+                .collect();
+            path.push(pick);
+            let target = self.visit(self.molecule.refine(dense_ranks(&keys)), path);
+            path.pop();
+            searched.push(pick);
+            if let Some(target) = target.filter(|&target| target < depth) {
+                resume = Some(target);
+                break;
+            }
+        }
+        self.orbits.pop();
+        resume
+    }
+
+    fn leaf(&mut self, ranks: Vec<usize>, path: &[usize]) -> Option<usize> {
+        let form = self.molecule.relabelled(&ranks);
+        let equal = [&self.first, &self.best]
+            .into_iter()
+            .flatten()
+            .find(|other| other.form == form);
+        if let Some(other) = equal {
+            // Atom to atom through the shared relabelling
+            let mut atom_at = vec![0; ranks.len()];
+            for (atom_idx, &rank) in ranks.iter().enumerate() {
+                atom_at[rank] = atom_idx;
+            }
+            let gamma = other.ranks.iter().map(|&rank| atom_at[rank]).collect();
+            let other_path = other.path.clone();
+            return self.automorphism_found(gamma, &other_path, path);
+        }
+        let leaf = Leaf {
+            path: path.to_vec(),
+            ranks,
+            form,
+        };
+        if self.best.as_ref().is_none_or(|best| leaf.form < best.form) {
+            self.best = Some(leaf.clone());
+        }
+        if self.first.is_none() {
+            self.first = Some(leaf);
+        }
+        None
+    }
+
+    // An automorphism taking the node at `depth` on the way to `first` to
+    // the one with partition `ranks`. Refinement cannot tell two partitions
+    // apart that differ only in their singletons, so mapping those onto each
+    // other is tried and checked against the first leaf
+    fn first_path_automorphism(&self, ranks: &[usize], depth: usize) -> Option<Vec<usize>> {
+        let first_ranks = self.first_partitions.get(depth)?;
+        let first = self.first.as_ref()?;
+        let mut sizes = vec![0; ranks.len()];
+        let mut first_sizes = vec![0; ranks.len()];
+        for (&rank, &first_rank) in ranks.iter().zip(first_ranks) {
+            sizes[rank] += 1;
+            first_sizes[first_rank] += 1;
+        }
+        if sizes != first_sizes {
+            return None;
+        }
+        let mut singleton_at = vec![0; ranks.len()];
+        for (atom_idx, (&rank, &first_rank)) in ranks.iter().zip(first_ranks).enumerate() {
+            if sizes[rank] == 1 {
+                singleton_at[rank] = atom_idx;
+            } else if rank != first_rank {
+                return None;
+            }
+        }
+        let gamma: Vec<usize> = first_ranks
+            .iter()
+            .enumerate()
+            .map(|(atom_idx, &rank)| {
+                if sizes[rank] == 1 {
+                    singleton_at[rank]
+                } else {
+                    atom_idx
+                }
+            })
+            .collect();
+        let mut relabelled = vec![0; ranks.len()];
+        for (atom_idx, &rank) in first.ranks.iter().enumerate() {
+            relabelled[gamma[atom_idx]] = rank;
+        }
+        (self.molecule.relabelled(&relabelled) == first.form).then_some(gamma)
+    }
+
+    // Records `gamma`, which takes the branch along `other_path` onto the
+    // current one. When it also takes the atoms individualized on the way
+    // there onto those on `path`, everything below the point where the two
+    // paths part repeats a finished branch, and the search resumes there
+    fn automorphism_found(
+        &mut self,
+        gamma: Vec<usize>,
+        other_path: &[usize],
+        path: &[usize],
+    ) -> Option<usize> {
+        let moved: Vec<usize> = (0..gamma.len())
+            .filter(|&atom_idx| gamma[atom_idx] != atom_idx)
+            .collect();
+        for (depth, orbits) in self.orbits.iter_mut().enumerate() {
+            if depth > 0 && gamma[path[depth - 1]] != path[depth - 1] {
+                break;
+            }
+            for &atom_idx in &moved {
+                union(orbits, atom_idx, gamma[atom_idx]);
+            }
+        }
+        let common = path
+            .iter()
+            .zip(other_path)
+            .take_while(|(a, b)| a == b)
+            .count();
+        let maps_path = (0..=common).all(|depth| {
+            depth < path.len().min(other_path.len()) && gamma[other_path[depth]] == path[depth]
+        });
+        self.automorphisms.push(gamma);
+        maps_path.then_some(common)
+    }
+
+    // Orbits of the automorphisms found so far that fix every atom of `path`
+    fn orbits_fixing(&self, path: &[usize]) -> Vec<usize> {
+        let mut orbits: Vec<usize> = (0..self.molecule.atoms.len()).collect();
+        for automorphism in &self.automorphisms {
+            if path
+                .iter()
+                .all(|&atom_idx| automorphism[atom_idx] == atom_idx)
+            {
+                for (atom_idx, &image) in automorphism.iter().enumerate() {
+                    union(&mut orbits, atom_idx, image);
+                }
+            }
+        }
+        orbits
+    }
+}
+
+// Union-find over `parent`, roots being the lowest atom of their set
+pub(super) fn root(parent: &[usize], mut atom_idx: usize) -> usize {
+    while parent[atom_idx] != atom_idx {
+        atom_idx = parent[atom_idx];
+    }
+    atom_idx
+}
+
+pub(super) fn union(parent: &mut [usize], a: usize, b: usize) {
+    let (a, b) = (root(parent, a), root(parent, b));
+    parent[a.max(b)] = a.min(b);
+}
+
+// The lowest rank shared by more than one atom
+fn first_tied_class(ranks: &[usize]) -> Option<usize> {
+    let mut class_sizes = vec![0; ranks.len()];
+    for &rank in ranks {
+        class_sizes[rank] += 1;
+    }
+    class_sizes.iter().position(|&size| size > 1)
+}
+
+// Ranks 0..k by sorted key, equal keys sharing a rank
+pub(super) fn dense_ranks<T: Ord>(keys: &[T]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..keys.len()).collect();
+    order.sort_by(|&a, &b| keys[a].cmp(&keys[b]));
+    let mut ranks = vec![0; keys.len()];
+    let mut rank = 0;
+    for (position, &atom_idx) in order.iter().enumerate() {
+        if position > 0 && keys[atom_idx] != keys[order[position - 1]] {
+            rank += 1;
+        }
+        ranks[atom_idx] = rank;
+    }
+    ranks
+}
+
+fn class_count(ranks: &[usize]) -> usize {
+    ranks.iter().max().map_or(0, |&r| r + 1)
+}
@@ -0,0 +1,440 @@
+// CIP stereodescriptors: R/S for tetrahedral centres and E/Z for double
+// bonds, ranking ligands with sequence rules 1 (atomic number, duplicates
+// included) and 2 (atomic mass).
+//
+// Ligands are compared by walking the hierarchical digraph rooted at the
+// stereocentre one sphere at a time. A sphere is compared set by set, the sets
+// taken in the order of the branches they hang from. Ring closures and
+// multiple bonds add duplicate atoms, which carry the atomic number and mass
+// of the atom they copy but only phantom substituents of atomic number 0.
+use std::{cmp::Ordering, fmt};
+
+use super::{
+    configuration::Configuration,
+    defs::{Axialness, BondIndex},
+    mendeleev::element,
+    molecule::Molecule,
+    rings::RingInfo,
+};
+
+// Bounds on the digraph walk; branches still tied by then are equal
+const MAX_SPHERES: usize = 64;
+const MAX_SPHERE_SIZE: usize = 10_000;
+
+// Double bonds in smaller rings cannot be trans
+const MIN_STEREO_RING_SIZE: usize = 8;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CipLabel {
+    R,
+    S,
+    E,
+    Z,
+}
+
+impl fmt::Display for CipLabel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// Stereodescriptors of a molecule, see `Molecule::cip_labels`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CipLabels {
+    /// R or S for each specified stereocentre, indexed by atom.
+    pub atoms: Vec<Option<CipLabel>>,
+    /// E or Z for each specified stereogenic double bond, indexed by bond.
+    pub bonds: Vec<Option<CipLabel>>,
+    /// Stereocentres with no configuration given.
+    pub unspecified_atoms: Vec<usize>,
+    /// Stereogenic double bonds with no `/` or `\` marks.
+    pub unspecified_bonds: Vec<BondIndex>,
+}
+
+// One node of the hierarchical digraph
+#[derive(Clone)]
+struct Node {
+    // None for implicit hydrogens and phantom atoms
+    atom: Option<usize>,
+    atomic_number: usize,
+    mass: f64,
+    // Arena index of the node this one hangs from
+    parent: Option<usize>,
+    // Duplicates, hydrogens and phantoms have no substituents to explore
+    expandable: bool,
+}
+
+#[derive(Clone, Copy)]
+enum Rule {
+    AtomicNumber,
+    Mass,
+}
+
+impl Node {
+    fn key(&self, rule: Rule) -> f64 {
+        match rule {
+            Rule::AtomicNumber => self.atomic_number as f64,
+            Rule::Mass => self.mass,
+        }
+    }
+}
+
+impl Molecule {
+    /// CIP descriptors for every tetrahedral centre and double bond, along
+    /// with the stereogenic ones that carry no configuration.
+    pub fn cip_labels(&self) -> CipLabels {
+        let mut labels = CipLabels {
+            atoms: vec![None; self.atoms.len()],
+            bonds: vec![None; self.bonds.len()],
+            ..CipLabels::default()
+        };
+
+        for atom_idx in 0..self.atoms.len() {
+            match &self.atoms[atom_idx].configuration {
+                Some(configuration @ (Configuration::TH1 | Configuration::TH2)) => {
+                    labels.atoms[atom_idx] = self.tetrahedral_label(atom_idx, configuration);
+                }
+                _ => {
+                    if self.is_tetrahedral_candidate(atom_idx)
+                        && self.ligand_ranks(&self.centre_digraph(atom_idx)).is_some()
+                    {
+                        labels.unspecified_atoms.push(atom_idx);
+                    }
+                }
+            }
+        }
+
+        let computed;
+        let rings = match self.ring_info() {
+            Some(rings) => rings,
+            None => {
+                computed = RingInfo::new(self);
+                &computed
+            }
+        };
+        for (bond_idx, bond) in self.bonds.iter().enumerate() {
+            if bond.bond_order != 2
+                || bond.arom
+                || rings
+                    .smallest_bond_ring_size(bond_idx)
+                    .is_some_and(|size| size < MIN_STEREO_RING_SIZE)
+            {
+                continue;
+            }
+            let (Some(left), Some(right)) = (
+                self.double_bond_end(bond.source, bond.dest),
+                self.double_bond_end(bond.dest, bond.source),
+            ) else {
+                continue;
+            };
+            match (left, right) {
+                (Some(left_up), Some(right_up)) => {
+                    labels.bonds[bond_idx] = Some(if left_up == right_up {
+                        CipLabel::Z
+                    } else {
+                        CipLabel::E
+                    });
+                }
+                _ => labels.unspecified_bonds.push(bond_idx),
+            }
+        }
+        labels
+    }
+
+    /// The CIP ranks of the ligands of `atom_idx`, 0 for the highest: its
+    /// neighbours in `outgoing_bond` order, then one entry per implicit
+    /// hydrogen. `None` when two ligands cannot be told apart.
+    pub fn cip_ranks(&self, atom_idx: usize) -> Option<Vec<usize>> {
+        let mut arena = vec![self.root_node(atom_idx)];
+        for neighbor in self.neighbors(atom_idx) {
+            arena.push(self.atom_node(neighbor, Some(0)));
+        }
+        for _ in 0..self.atoms[atom_idx].hydrogens {
+            arena.push(hydrogen_node(Some(0)));
+        }
+        self.ligand_ranks(&arena)
+    }
+
+    // R or S from a tetrahedral parity, None if the centre is not stereogenic
+    fn tetrahedral_label(
+        &self,
+        atom_idx: usize,
+        configuration: &Configuration,
+    ) -> Option<CipLabel> {
+        let arena = self.centre_digraph(atom_idx);
+        if arena.len() != 5 {
+            return None;
+        }
+        let ranks = self.ligand_ranks(&arena)?;
+
+        // Looking from the first ligand, '@' (TH1) lists the others
+        // anticlockwise. With the lowest ranked ligand first and the rest
+        // from highest to lowest that is R.
+        let inversions = (0..ranks.len())
+            .flat_map(|i| (i + 1..ranks.len()).map(move |j| (i, j)))
+            .filter(|&(i, j)| ranks[i] > ranks[j])
+            .count();
+        // [3, 0, 1, 2] is itself an odd permutation
+        let odd = inversions % 2 == 0;
+        let anticlockwise = (*configuration == Configuration::TH1) != odd;
+        Some(if anticlockwise {
+            CipLabel::R
+        } else {
+            CipLabel::S
+        })
+    }
+
+    // Sp3 atoms with four ligands that could be stereocentres
+    fn is_tetrahedral_candidate(&self, atom_idx: usize) -> bool {
+        let atom = &self.atoms[atom_idx];
+        let element_fits = match atom.element {
+            6 | 14 | 32 | 50 => atom.f_charge == 0,
+            // Neutral amines invert too fast to count
+            7 | 15 => atom.f_charge == 1,
+            _ => false,
+        };
+        element_fits
+            && !atom.aromatic
+            && atom.hydrogens <= 1
+            && self.degree(atom_idx) + atom.hydrogens == 4
+            && self
+                .bonds_of(atom_idx)
+                .iter()
+                .all(|&b| self.bonds[b].bond_order == 1)
+    }
+
+    // Root node and the ligands of a stereocentre in stored parity order:
+    // neighbours with an implicit hydrogen or lone pair after the first one
+    fn centre_digraph(&self, atom_idx: usize) -> Vec<Node> {
+        let mut ligands: Vec<Node> = self
+            .neighbors(atom_idx)
+            .map(|n| self.atom_node(n, Some(0)))
+            .collect();
+        let position = ligands.len().min(1);
+        match self.atoms[atom_idx].hydrogens {
+            0 if ligands.len() == 3 => ligands.insert(position, phantom_node(Some(0))),
+            0 => {}
+            h => {
+                for _ in 0..h {
+                    ligands.insert(position, hydrogen_node(Some(0)));
+                }
+            }
+        }
+        let mut arena = vec![self.root_node(atom_idx)];
+        arena.extend(ligands);
+        arena
+    }
+
+    // For one end of a double bond: None if it is not stereogenic, Some(None)
+    // if it is but has no marks, else whether its top ranked ligand is "up"
+    fn double_bond_end(&self, atom_idx: usize, across: usize) -> Option<Option<bool>> {
+        let atom = &self.atoms[atom_idx];
+        if !matches!(atom.element, 6 | 7) {
+            return None;
+        }
+        // The far end of the double bond is the root, this end its only child
+        let mut arena = vec![self.root_node(across), self.atom_node(atom_idx, Some(0))];
+        let ligand_bonds: Vec<BondIndex> = self
+            .bonds_of(atom_idx)
+            .iter()
+            .copied()
+            .filter(|&b| self.bonds[b].other(atom_idx) != across)
+            .collect();
+        for &b in &ligand_bonds {
+            arena.push(self.atom_node(self.bonds[b].other(atom_idx), Some(1)));
+        }
+        for _ in 0..atom.hydrogens {
+            arena.push(hydrogen_node(Some(1)));
+        }
+        let ligand_count = arena.len() - 2;
+        if ligand_count == 0 || ligand_count > 2 {
+            return None;
+        }
+
+        let top = if ligand_count == 2 {
+            let order = self.compare_ligands(&arena, 2, 3);
+            match order {
+                Ordering::Equal => return None,
+                Ordering::Greater => 0,
+                Ordering::Less => 1,
+            }
+        } else {
+            // A lone pair ranks below anything
+            0
+        };
+
+        let Some((marked, &bond_idx)) = ligand_bonds
+            .iter()
+            .enumerate()
+            .find(|(_, &b)| self.bonds[b].axialness != Axialness::UNKNOWN)
+        else {
+            return Some(None);
+        };
+        let bond = &self.bonds[bond_idx];
+        // Direction of the marked ligand as seen from this end
+        let up = (bond.axialness == Axialness::UP) == (bond.source == atom_idx);
+        Some(Some(if marked == top { up } else { !up }))
+    }
+
+    // Ranks of the ligands in arena[1..], all hanging from arena[0]
+    fn ligand_ranks(&self, arena: &[Node]) -> Option<Vec<usize>> {
+        let ligands = 1..arena.len();
+        let mut ranks = Vec::with_capacity(ligands.len());
+        for i in ligands.clone() {
+            let mut rank = 0;
+            for j in ligands.clone() {
+                match self.compare_ligands(arena, i, j) {
+                    Ordering::Less => rank += 1,
+                    Ordering::Equal if i != j => return None,
+                    _ => {}
+                }
+            }
+            ranks.push(rank);
+        }
+        Some(ranks)
+    }
+
+    // Rule 1 over the whole digraph, then rule 2
+    fn compare_ligands(&self, arena: &[Node], a: usize, b: usize) -> Ordering {
+        let mut arena = arena.to_vec();
+        self.compare_branches(&mut arena, a, b, Rule::AtomicNumber)
+            .then_with(|| self.compare_branches(&mut arena, a, b, Rule::Mass))
+    }
+
+    fn compare_branches(&self, arena: &mut Vec<Node>, a: usize, b: usize, rule: Rule) -> Ordering {
+        let order = arena[a].key(rule).total_cmp(&arena[b].key(rule));
+        if order != Ordering::Equal {
+            return order;
+        }
+        let mut sphere_a = vec![a];
+        let mut sphere_b = vec![b];
+        for _ in 0..MAX_SPHERES {
+            let sets_a = self.next_sphere(arena, &sphere_a, rule);
+            let sets_b = self.next_sphere(arena, &sphere_b, rule);
+            for i in 0..sets_a.len().max(sets_b.len()) {
+                let set_a = sets_a.get(i).map_or(&[][..], |s| &s[..]);
+                let set_b = sets_b.get(i).map_or(&[][..], |s| &s[..]);
+                for j in 0..set_a.len().max(set_b.len()) {
+                    let key_a = set_a.get(j).map_or(0.0, |&n| arena[n].key(rule));
+                    let key_b = set_b.get(j).map_or(0.0, |&n| arena[n].key(rule));
+                    let order = key_a.total_cmp(&key_b);
+                    if order != Ordering::Equal {
+                        return order;
+                    }
+                }
+            }
+            sphere_a = sets_a.concat();
+            sphere_b = sets_b.concat();
+            if (sphere_a.is_empty() && sphere_b.is_empty())
+                || sphere_a.len().max(sphere_b.len()) > MAX_SPHERE_SIZE
+            {
+                break;
+            }
+        }
+        Ordering::Equal
+    }
+
+    // Substituent sets of each node in the sphere, highest key first
+    fn next_sphere(&self, arena: &mut Vec<Node>, sphere: &[usize], rule: Rule) -> Vec<Vec<usize>> {
+        sphere
+            .iter()
+            .map(|&node_idx| {
+                let mut set: Vec<usize> = self
+                    .substituents(arena, node_idx)
+                    .into_iter()
+                    .map(|node| {
+                        arena.push(node);
+                        arena.len() - 1
+                    })
+                    .collect();
+                set.sort_by(|&x, &y| arena[y].key(rule).total_cmp(&arena[x].key(rule)));
+                set
+            })
+            .collect()
+    }
+
+    fn substituents(&self, arena: &[Node], node_idx: usize) -> Vec<Node> {
+        let node = &arena[node_idx];
+        let (true, Some(atom_idx)) = (node.expandable, node.atom) else {
+            return Vec::new();
+        };
+        let parent_atom = node.parent.and_then(|p| arena[p].atom);
+        let on_path = |atom: usize| {
+            let mut current = node.parent;
+            while let Some(p) = current {
+                if arena[p].atom == Some(atom) {
+                    return true;
+                }
+                current = arena[p].parent;
+            }
+            false
+        };
+
+        let mut substituents = Vec::new();
+        for &bond_idx in self.bonds_of(atom_idx) {
+            let bond = &self.bonds[bond_idx];
+            let neighbor = bond.other(atom_idx);
+            let order = bond.bond_order.max(1) as usize;
+            let duplicates = if Some(neighbor) == parent_atom {
+                order - 1
+            } else if on_path(neighbor) {
+                // Ring closure
+                order
+            } else {
+                substituents.push(self.atom_node(neighbor, Some(node_idx)));
+                order - 1
+            };
+            for _ in 0..duplicates {
+                let mut duplicate = self.atom_node(neighbor, Some(node_idx));
+                duplicate.expandable = false;
+                substituents.push(duplicate);
+            }
+        }
+        for _ in 0..self.atoms[atom_idx].hydrogens {
+            substituents.push(hydrogen_node(Some(node_idx)));
+        }
+        substituents
+    }
+
+    fn root_node(&self, atom_idx: usize) -> Node {
+        let mut root = self.atom_node(atom_idx, None);
+        // Never explored, ligands are compared below it
+        root.expandable = false;
+        root
+    }
+
+    fn atom_node(&self, atom_idx: usize, parent: Option<usize>) -> Node {
+        let atom = &self.atoms[atom_idx];
+        let mass = match atom.isotope {
+            0 => element(atom.element).map_or(0.0, |e| e.average_mass),
+            mass_number => mass_number as f64,
+        };
+        Node {
+            atom: Some(atom_idx),
+            atomic_number: atom.element,
+            mass,
+            parent,
+            expandable: true,
+        }
+    }
+}
+
+fn hydrogen_node(parent: Option<usize>) -> Node {
+    Node {
+        atom: None,
+        atomic_number: 1,
+        mass: element(1).map_or(1.0, |e| e.average_mass),
+        parent,
+        expandable: false,
+    }
+}
+
+fn phantom_node(parent: Option<usize>) -> Node {
+    Node {
+        atom: None,
+        atomic_number: 0,
+        mass: 0.0,
+        parent,
+        expandable: false,
+    }
+}
@@ -1,4 +1,5 @@
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Configuration {
     // Allene-like
     AL1,
@@ -68,3 +69,69 @@ pub enum Configuration {
     //
     Unknown,
 }
+
+impl Configuration {
+    /// Every variant in declaration order, so `ALL[c as usize] == c`.
+    pub const ALL: [Configuration; 60] = [
+        Configuration::AL1,
+        Configuration::AL2,
+        Configuration::OH1,
+        Configuration::OH2,
+        Configuration::OH3,
+        Configuration::OH4,
+        Configuration::OH5,
+        Configuration::OH6,
+        Configuration::OH7,
+        Configuration::OH8,
+        Configuration::OH9,
+        Configuration::OH10,
+        Configuration::OH11,
+        Configuration::OH12,
+        Configuration::OH13,
+        Configuration::OH14,
+        Configuration::OH15,
+        Configuration::OH16,
+        Configuration::OH17,
+        Configuration::OH18,
+        Configuration::OH19,
+        Configuration::OH20,
+        Configuration::OH21,
+        Configuration::OH22,
+        Configuration::OH23,
+        Configuration::OH24,
+        Configuration::OH25,
+        Configuration::OH26,
+        Configuration::OH27,
+        Configuration::OH28,
+        Configuration::OH29,
+        Configuration::OH30,
+        Configuration::SP1,
+        Configuration::SP2,
+        Configuration::SP3,
+        Configuration::TB1,
+        Configuration::TB2,
+        Configuration::TB3,
+        Configuration::TB4,
+        Configuration::TB5,
+        Configuration::TB6,
+        Configuration::TB7,
+        Configuration::TB8,
+        Configuration::TB9,
+        Configuration::TB10,
+        Configuration::TB11,
+        Configuration::TB12,
+        Configuration::TB13,
+        Configuration::TB14,
+        Configuration::TB15,
+        Configuration::TB16,
+        Configuration::TB17,
+        Configuration::TB18,
+        Configuration::TB19,
+        Configuration::TB20,
+        Configuration::TH1,
+        Configuration::TH2,
+        Configuration::DB1,
+        Configuration::DB2,
+        Configuration::Unknown,
+    ];
+}
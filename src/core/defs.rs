@@ -1,7 +1,11 @@
-use super::configuration::Configuration;
+use super::{
+    configuration::Configuration,
+    properties::{Property, PropertyMap},
+};
 
 // Defines properties of a atom
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Atom {
     // List of BondIndexes
     pub outgoing_bond: Vec<BondIndex>,
@@ -15,7 +19,9 @@ pub struct Atom {
     pub aromatic: bool,
     /// The charge of the atom.
     pub f_charge: i8,
-    /// The chirality/configuration of the atom.
+    /// The chirality/configuration of the atom. Tetrahedral parities are
+    /// relative to the `outgoing_bond` order, with an implicit hydrogen
+    /// counted straight after the first neighbour.
     pub configuration: Option<Configuration>,
     /// part of ring?
     pub ring: bool,
@@ -23,9 +29,73 @@ pub struct Atom {
     pub symmetry_class: usize,
     // 3D Coords
     pub coords_3d: Option<(f64, f64, f64)>,
+    /// Atom-map or atom class number, 0 for none. Written `[CH3:1]` in SMILES.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub atom_map: usize,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub properties: PropertyMap,
 }
 
 impl Atom {
+    /// A neutral, aliphatic atom of `element` with no hydrogens, stereo or
+    /// coordinates; the `with_` setters fill in the rest.
+    pub fn new(element: usize) -> Atom {
+        Atom {
+            outgoing_bond: Vec::new(),
+            element,
+            isotope: 0,
+            hydrogens: 0,
+            aromatic: false,
+            f_charge: 0,
+            configuration: None,
+            ring: false,
+            symmetry_class: 0,
+            coords_3d: None,
+            atom_map: 0,
+            properties: PropertyMap::new(),
+        }
+    }
+
+    pub fn with_isotope(mut self, isotope: usize) -> Atom {
+        self.isotope = isotope;
+        self
+    }
+
+    pub fn with_hydrogens(mut self, hydrogens: usize) -> Atom {
+        self.hydrogens = hydrogens;
+        self
+    }
+
+    pub fn with_aromatic(mut self, aromatic: bool) -> Atom {
+        self.aromatic = aromatic;
+        self
+    }
+
+    pub fn with_charge(mut self, f_charge: i8) -> Atom {
+        self.f_charge = f_charge;
+        self
+    }
+
+    pub fn with_configuration(mut self, configuration: Configuration) -> Atom {
+        self.configuration = Some(configuration);
+        self
+    }
+
+    pub fn with_coords(mut self, coords: (f64, f64, f64)) -> Atom {
+        self.coords_3d = Some(coords);
+        self
+    }
+
+    pub fn with_atom_map(mut self, atom_map: usize) -> Atom {
+        self.atom_map = atom_map;
+        self
+    }
+
+    pub fn with_property(mut self, name: &str, value: impl Into<Property>) -> Atom {
+        self.properties.set(name, value);
+        self
+    }
+
     pub fn add_to_bond_list(&mut self, bond: BondIndex) {
         self.outgoing_bond.push(bond)
     }
@@ -46,6 +116,7 @@ impl Atom {
 pub type BondIndex = usize;
 
 #[derive(PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Bond {
     pub source: usize,
     pub dest: usize,
@@ -53,9 +124,36 @@ pub struct Bond {
     pub ring: bool,
     pub bond_order: i8,
     pub axialness: Axialness,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub properties: PropertyMap,
+}
+
+impl Bond {
+    /// A bond of `bond_order` without aromaticity, ring or direction flags.
+    pub fn new(source: usize, dest: usize, bond_order: i8) -> Bond {
+        Bond {
+            source,
+            dest,
+            arom: false,
+            ring: false,
+            bond_order,
+            axialness: Axialness::UNKNOWN,
+            properties: PropertyMap::new(),
+        }
+    }
+
+    /// The atom at the other end of the bond from `atom_idx`.
+    pub fn other(&self, atom_idx: usize) -> usize {
+        if self.source == atom_idx {
+            self.dest
+        } else {
+            self.source
+        }
+    }
 }
 
 #[derive(PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Axialness {
     UP,
     DOWN,
@@ -0,0 +1,190 @@
+// Editing that keeps `outgoing_bond` and bond `source`/`dest` consistent.
+// Removals are queued on a `MoleculeEdit` and applied together on commit,
+// so indices stay stable while a batch is being built.
+use super::{
+    defs::{Atom, Bond, BondIndex},
+    molecule::Molecule,
+};
+
+/// Old to new indices after a batch edit, `None` for removed entries.
+/// Atoms and bonds added during the batch are included after the old ones.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct IndexMap {
+    pub atoms: Vec<Option<usize>>,
+    pub bonds: Vec<Option<BondIndex>>,
+}
+
+impl IndexMap {
+    pub fn atom(&self, old: usize) -> Option<usize> {
+        self.atoms.get(old).copied().flatten()
+    }
+
+    pub fn bond(&self, old: BondIndex) -> Option<BondIndex> {
+        self.bonds.get(old).copied().flatten()
+    }
+}
+
+/// A batch of edits on a molecule, applied by `commit`.
+pub struct MoleculeEdit<'a> {
+    molecule: &'a mut Molecule,
+    removed_atoms: Vec<bool>,
+    removed_bonds: Vec<bool>,
+    // Whether rings were perceived before the batch, additions clear the cache
+    had_rings: bool,
+}
+
+impl<'a> MoleculeEdit<'a> {
+    /// Removes the atom together with every bond touching it, including bonds
+    /// added to it later in the batch.
+    pub fn remove_atom(&mut self, atom_idx: usize) -> &mut Self {
+        self.removed_atoms[atom_idx] = true;
+        for &bond_idx in &self.molecule.atoms[atom_idx].outgoing_bond {
+            self.removed_bonds[bond_idx] = true;
+        }
+        self
+    }
+
+    pub fn remove_bond(&mut self, bond_idx: BondIndex) -> &mut Self {
+        self.removed_bonds[bond_idx] = true;
+        self
+    }
+
+    /// Adds an atom, its index is valid until `commit`.
+    pub fn add_atom(&mut self, atom: Atom) -> usize {
+        self.removed_atoms.push(false);
+        self.molecule.add_atom(atom)
+    }
+
+    /// Adds a bond, its index is valid until `commit`.
+    pub fn add_bond(&mut self, bond: Bond) -> BondIndex {
+        self.removed_bonds.push(false);
+        self.molecule.add_bond(bond)
+    }
+
+    pub fn set_bond_order(&mut self, bond_idx: BondIndex, bond_order: i8) -> &mut Self {
+        self.molecule.set_bond_order(bond_idx, bond_order);
+        self
+    }
+
+    pub fn set_element(&mut self, atom_idx: usize, element: usize) -> &mut Self {
+        self.molecule.set_element(atom_idx, element);
+        self
+    }
+
+    pub fn set_charge(&mut self, atom_idx: usize, charge: i8) -> &mut Self {
+        self.molecule.set_charge(atom_idx, charge);
+        self
+    }
+
+    pub fn set_isotope(&mut self, atom_idx: usize, isotope: usize) -> &mut Self {
+        self.molecule.set_isotope(atom_idx, isotope);
+        self
+    }
+
+    /// Applies the removals and compacts atom and bond indices.
+    pub fn commit(self) -> IndexMap {
+        let molecule = self.molecule;
+        let mut removed_bonds = self.removed_bonds;
+        // Bonds added after one of their atoms was removed
+        for (bond_idx, bond) in molecule.bonds.iter().enumerate() {
+            if self.removed_atoms[bond.source] || self.removed_atoms[bond.dest] {
+                removed_bonds[bond_idx] = true;
+            }
+        }
+
+        let mut map = IndexMap::default();
+        let mut next = 0;
+        for &removed in &self.removed_atoms {
+            map.atoms.push((!removed).then_some(next));
+            next += usize::from(!removed);
+        }
+        next = 0;
+        for &removed in &removed_bonds {
+            map.bonds.push((!removed).then_some(next));
+            next += usize::from(!removed);
+        }
+
+        let old_atoms = std::mem::take(&mut molecule.atoms);
+        for (old_idx, mut atom) in old_atoms.into_iter().enumerate() {
+            if map.atoms[old_idx].is_none() {
+                continue;
+            }
+            let bond_count = atom.outgoing_bond.len();
+            atom.outgoing_bond = atom
+                .outgoing_bond
+                .iter()
+                .filter_map(|&b| map.bonds[b])
+                .collect();
+            // Parities are relative to the neighbours, which have changed
+            if atom.outgoing_bond.len() != bond_count {
+                atom.configuration = None;
+            }
+            molecule.atoms.push(atom);
+        }
+
+        let old_bonds = std::mem::take(&mut molecule.bonds);
+        for (old_idx, mut bond) in old_bonds.into_iter().enumerate() {
+            if map.bonds[old_idx].is_none() {
+                continue;
+            }
+            bond.source = map.atoms[bond.source].unwrap();
+            bond.dest = map.atoms[bond.dest].unwrap();
+            molecule.bonds.push(bond);
+        }
+
+        // Keep the ring cache in step with the new graph
+        if self.had_rings {
+            molecule.perceive_rings();
+        }
+        map
+    }
+}
+
+impl Molecule {
+    /// Starts a batch of edits, see `MoleculeEdit`.
+    pub fn edit(&mut self) -> MoleculeEdit<'_> {
+        MoleculeEdit {
+            removed_atoms: vec![false; self.atoms.len()],
+            removed_bonds: vec![false; self.bonds.len()],
+            had_rings: self.ring_info().is_some(),
+            molecule: self,
+        }
+    }
+
+    /// Removes the atom and its bonds. Later indices shift down.
+    pub fn remove_atom(&mut self, atom_idx: usize) -> IndexMap {
+        let mut edit = self.edit();
+        edit.remove_atom(atom_idx);
+        edit.commit()
+    }
+
+    /// Removes the bond. Later bond indices shift down.
+    pub fn remove_bond(&mut self, bond_idx: BondIndex) -> IndexMap {
+        let mut edit = self.edit();
+        edit.remove_bond(bond_idx);
+        edit.commit()
+    }
+
+    /// Sets the order of the bond. Its aromatic flag is left alone.
+    pub fn set_bond_order(&mut self, bond_idx: BondIndex, bond_order: i8) {
+        self.bonds[bond_idx].bond_order = bond_order;
+    }
+
+    pub fn set_element(&mut self, atom_idx: usize, element: usize) {
+        self.atoms[atom_idx].element = element;
+    }
+
+    pub fn set_charge(&mut self, atom_idx: usize, charge: i8) {
+        self.atoms[atom_idx].f_charge = charge;
+    }
+
+    pub fn set_isotope(&mut self, atom_idx: usize, isotope: usize) {
+        self.atoms[atom_idx].isotope = isotope;
+    }
+
+    /// Replaces the atom's data, keeping its bonds.
+    pub fn replace_atom(&mut self, atom_idx: usize, mut atom: Atom) {
+        atom.outgoing_bond = std::mem::take(&mut self.atoms[atom_idx].outgoing_bond);
+        self.atoms[atom_idx] = atom;
+    }
+}
@@ -0,0 +1,112 @@
+// Splitting a molecule into its connected components and picking one,
+// e.g. to strip counter-ions and solvents.
+use super::{defs::BondIndex, molecule::Molecule};
+
+/// One connected component, with the parent index of each of its atoms and
+/// bonds. Both lists are in increasing order, as in the parent.
+#[derive(Clone)]
+pub struct Fragment {
+    pub molecule: Molecule,
+    pub atoms: Vec<usize>,
+    pub bonds: Vec<BondIndex>,
+}
+
+impl Fragment {
+    /// Index in the fragment of atom `parent_idx` of the parent.
+    pub fn atom(&self, parent_idx: usize) -> Option<usize> {
+        self.atoms.binary_search(&parent_idx).ok()
+    }
+
+    /// Index in the fragment of bond `parent_idx` of the parent.
+    pub fn bond(&self, parent_idx: BondIndex) -> Option<BondIndex> {
+        self.bonds.binary_search(&parent_idx).ok()
+    }
+}
+
+/// How `largest_fragment` ranks fragments.
+pub enum FragmentCriterion<'a> {
+    /// Atoms other than hydrogen.
+    HeavyAtomCount,
+    /// Average molecular weight, implicit hydrogens included.
+    MolecularWeight,
+    /// Any score, the highest wins.
+    Custom(&'a dyn Fn(&Molecule) -> f64),
+}
+
+impl Molecule {
+    /// The connected components as separate molecules, in the order of their
+    /// lowest atom index.
+    pub fn split_fragments(&self) -> Vec<Fragment> {
+        let component = self.connected_components();
+        let count = component.iter().max().map_or(0, |&c| c + 1);
+        let mut fragments: Vec<Fragment> = (0..count)
+            .map(|_| {
+                let mut molecule = Molecule::new();
+                molecule.properties = self.properties.clone();
+                Fragment {
+                    molecule,
+                    atoms: Vec::new(),
+                    bonds: Vec::new(),
+                }
+            })
+            .collect();
+
+        // Index of each atom and bond within its fragment
+        let mut local_atom = vec![0; self.atoms.len()];
+        for (atom_idx, &c) in component.iter().enumerate() {
+            local_atom[atom_idx] = fragments[c].atoms.len();
+            fragments[c].atoms.push(atom_idx);
+        }
+        let mut local_bond = vec![0; self.bonds.len()];
+        for (bond_idx, bond) in self.bonds.iter().enumerate() {
+            let fragment = &mut fragments[component[bond.source]];
+            local_bond[bond_idx] = fragment.bonds.len();
+            fragment.bonds.push(bond_idx);
+        }
+
+        // Every bond of an atom is in its fragment, so parities still hold
+        let had_rings = self.ring_info().is_some();
+        for fragment in fragments.iter_mut() {
+            for &atom_idx in &fragment.atoms {
+                let mut atom = self.atoms[atom_idx].clone();
+                for bond_idx in atom.outgoing_bond.iter_mut() {
+                    *bond_idx = local_bond[*bond_idx];
+                }
+                fragment.molecule.atoms.push(atom);
+            }
+            for &bond_idx in &fragment.bonds {
+                let mut bond = self.bonds[bond_idx].clone();
+                bond.source = local_atom[bond.source];
+                bond.dest = local_atom[bond.dest];
+                fragment.molecule.bonds.push(bond);
+            }
+            if had_rings {
+                fragment.molecule.perceive_rings();
+            }
+        }
+        fragments
+    }
+
+    /// The best fragment under `criterion`, the first one on ties. `None`
+    /// for a molecule without atoms.
+    pub fn largest_fragment(&self, criterion: FragmentCriterion) -> Option<Fragment> {
+        let score = |molecule: &Molecule| match &criterion {
+            FragmentCriterion::HeavyAtomCount => molecule.heavy_atom_count() as f64,
+            FragmentCriterion::MolecularWeight => molecule.molecular_weight(),
+            FragmentCriterion::Custom(score) => score(molecule),
+        };
+        let mut best: Option<(f64, Fragment)> = None;
+        for fragment in self.split_fragments() {
+            let value = score(&fragment.molecule);
+            if best.as_ref().is_none_or(|(top, _)| value > *top) {
+                best = Some((value, fragment));
+            }
+        }
+        best.map(|(_, fragment)| fragment)
+    }
+
+    /// Atoms other than hydrogen.
+    pub fn heavy_atom_count(&self) -> usize {
+        self.atoms.iter().filter(|a| a.element != 1).count()
+    }
+}
@@ -33,3 +33,37 @@ impl Molecule {
         }
     }
 }
+
+impl Molecule {
+    /// Component number of every atom, numbered from 0 in order of their
+    /// lowest atom index.
+    pub fn connected_components(&self) -> Vec<usize> {
+        let mut component = vec![usize::MAX; self.atoms.len()];
+        let mut count = 0;
+        let mut stack = Vec::new();
+        for start in 0..self.atoms.len() {
+            if component[start] != usize::MAX {
+                continue;
+            }
+            component[start] = count;
+            stack.push(start);
+            while let Some(atom_idx) = stack.pop() {
+                for neighbor in self.neighbors(atom_idx) {
+                    if component[neighbor] == usize::MAX {
+                        component[neighbor] = count;
+                        stack.push(neighbor);
+                    }
+                }
+            }
+            count += 1;
+        }
+        component
+    }
+
+    pub fn num_components(&self) -> usize {
+        self.connected_components()
+            .iter()
+            .max()
+            .map_or(0, |&c| c + 1)
+    }
+}
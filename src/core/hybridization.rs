@@ -0,0 +1,95 @@
+// Hybridization from the steric number: neighbours and hydrogens plus lone
+// pairs, the lone pairs counted from the valence electrons left after
+// bonding and charge. A lone pair next to a π system is taken to be
+// delocalised into it, making amide and pyrrole nitrogens or furan oxygen sp2.
+use super::{mendeleev::valence_electrons, molecule::Molecule};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Hybridization {
+    /// No neighbours, or not a main group element.
+    Unspecified,
+    S,
+    SP,
+    SP2,
+    SP3,
+    SP3D,
+    SP3D2,
+}
+
+impl Hybridization {
+    /// The number used by the SMARTS `^n` primitive: 0 for s up to 5 for sp3d2.
+    pub fn smarts_number(self) -> Option<u8> {
+        match self {
+            Hybridization::Unspecified => None,
+            Hybridization::S => Some(0),
+            Hybridization::SP => Some(1),
+            Hybridization::SP2 => Some(2),
+            Hybridization::SP3 => Some(3),
+            Hybridization::SP3D => Some(4),
+            Hybridization::SP3D2 => Some(5),
+        }
+    }
+}
+
+impl Molecule {
+    /// Hybridization of the atom from its bonds, hydrogens and charge.
+    pub fn hybridization(&self, atom_idx: usize) -> Hybridization {
+        let atom = &self.atoms[atom_idx];
+        if !is_main_group(atom.element) {
+            return Hybridization::Unspecified;
+        }
+        let sigma = self.degree(atom_idx) + atom.hydrogens;
+        let bonding = self.explicit_valence(atom_idx) + atom.hydrogens as i32;
+        let free = valence_electrons(atom.element) as i32 - atom.f_charge as i32 - bonding;
+        let lone_pairs = free.max(0) as usize / 2;
+        if sigma == 0 {
+            return Hybridization::Unspecified;
+        }
+
+        match sigma + lone_pairs {
+            1 => Hybridization::S,
+            2 => Hybridization::SP,
+            3 => Hybridization::SP2,
+            4 if lone_pairs > 0
+                && !self.has_multiple_bond(atom_idx)
+                && self.next_to_pi(atom_idx) =>
+            {
+                Hybridization::SP2
+            }
+            4 => Hybridization::SP3,
+            5 => Hybridization::SP3D,
+            6 => Hybridization::SP3D2,
+            _ => Hybridization::Unspecified,
+        }
+    }
+
+    /// `hybridization` for every atom.
+    pub fn hybridizations(&self) -> Vec<Hybridization> {
+        (0..self.atoms.len())
+            .map(|atom_idx| self.hybridization(atom_idx))
+            .collect()
+    }
+
+    fn has_multiple_bond(&self, atom_idx: usize) -> bool {
+        self.bonds_of(atom_idx)
+            .iter()
+            .any(|&b| self.bonds[b].bond_order > 1)
+    }
+
+    fn has_pi_bond(&self, atom_idx: usize) -> bool {
+        self.bonds_of(atom_idx).iter().any(|&b| {
+            let bond = &self.bonds[b];
+            bond.arom || bond.bond_order > 1
+        })
+    }
+
+    // Aromatic atoms count, the lone pair of a pyrrole nitrogen is part of the ring
+    fn next_to_pi(&self, atom_idx: usize) -> bool {
+        self.atoms[atom_idx].aromatic || self.neighbors(atom_idx).any(|n| self.has_pi_bond(n))
+    }
+}
+
+// Groups 1, 2 and 13 to 18, where valence electrons give the lone pairs
+fn is_main_group(z: usize) -> bool {
+    matches!(z, 1..=20 | 31..=38 | 49..=56 | 81..=88 | 113..=118)
+}
@@ -0,0 +1,538 @@
+// Graph isomorphism in the style of VF2 (Cordella et al. 2004): the query
+// atoms are taken in a connected order and each one is tried against the
+// target atoms bonded to the image of an already mapped neighbour. A pair is
+// kept when the atoms match and the bonds to mapped neighbours agree both
+// ways. Atoms must also share a refinement class, and molecules whose
+// classes differ in size are not searched at all. A stereocentre is checked
+// as soon as it and its neighbours are mapped. Components are matched one
+// after the other, and since matching is an equivalence, one that cannot be
+// completed fails the whole search rather than remapping the earlier ones.
+use super::{
+    canonical::{dense_ranks, root, union},
+    configuration::Configuration,
+    defs::{Axialness, Bond, BondIndex},
+    molecule::Molecule,
+};
+
+// Stand-in for the implicit hydrogen in a stereocentre's neighbour list
+const IMPLICIT_H: usize = usize::MAX;
+
+/// What has to agree between matched atoms and bonds, besides the element
+/// and the bond order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MatchOptions {
+    pub charge: bool,
+    pub isotope: bool,
+    /// Implicit hydrogen counts.
+    pub hydrogens: bool,
+    /// Tetrahedral parities and double bond geometry. A centre specified on
+    /// one side only does not match.
+    pub stereo: bool,
+    /// Aromatic flags must agree. When off, an aromatic bond matches a
+    /// single or double bond.
+    pub aromaticity: bool,
+}
+
+impl Default for MatchOptions {
+    fn default() -> Self {
+        MatchOptions {
+            charge: true,
+            isotope: true,
+            hydrogens: true,
+            stereo: true,
+            aromaticity: true,
+        }
+    }
+}
+
+impl MatchOptions {
+    pub fn atoms_match(&self, a: &Molecule, a_idx: usize, b: &Molecule, b_idx: usize) -> bool {
+        let (x, y) = (&a.atoms[a_idx], &b.atoms[b_idx]);
+        x.element == y.element
+            && (!self.charge || x.f_charge == y.f_charge)
+            && (!self.isotope || x.isotope == y.isotope)
+            && (!self.hydrogens || x.hydrogens == y.hydrogens)
+            && (!self.aromaticity || x.aromatic == y.aromatic)
+    }
+
+    pub fn bonds_match(
+        &self,
+        a: &Molecule,
+        a_idx: BondIndex,
+        b: &Molecule,
+        b_idx: BondIndex,
+    ) -> bool {
+        let (x, y) = (&a.bonds[a_idx], &b.bonds[b_idx]);
+        match (x.arom, y.arom) {
+            // Aromatic bonds are equal whatever their Kekulé orders
+            (true, true) => true,
+            (false, false) => x.bond_order == y.bond_order,
+            _ if self.aromaticity => false,
+            (true, false) => matches!(y.bond_order, 1 | 2),
+            (false, true) => matches!(x.bond_order, 1 | 2),
+        }
+    }
+
+    // Refinement classes atoms must share to match under these options,
+    // numbered alike in both molecules, or None when their sizes show the
+    // molecules cannot match
+    fn classes(&self, a: &Molecule, b: &Molecule) -> Option<(Vec<usize>, Vec<usize>)> {
+        let key = |molecule: &Molecule, atom_idx: usize| {
+            let atom = &molecule.atoms[atom_idx];
+            (
+                atom.element,
+                molecule.degree(atom_idx),
+                self.charge.then_some(atom.f_charge),
+                self.isotope.then_some(atom.isotope),
+                self.hydrogens.then_some(atom.hydrogens),
+                self.aromaticity.then_some(atom.aromatic),
+            )
+        };
+        let keys: Vec<_> = (0..a.atoms.len())
+            .map(|atom_idx| key(a, atom_idx))
+            .chain((0..b.atoms.len()).map(|atom_idx| key(b, atom_idx)))
+            .collect();
+        let mut a_ranks = dense_ranks(&keys);
+        let b_ranks = a_ranks.split_off(a.atoms.len());
+        if sorted(&a_ranks) != sorted(&b_ranks) {
+            return None;
+        }
+        // Bond orders only count when aromatic and Kekulé bonds cannot match
+        let bond_key = |bond: &Bond| match self.aromaticity {
+            true if bond.arom => i8::MAX,
+            true => bond.bond_order,
+            false => 0,
+        };
+        let a_classes = a.refine_on(a_ranks, &bond_key);
+        let b_classes = b.refine_on(b_ranks, &bond_key);
+        (sorted(&a_classes) == sorted(&b_classes)).then_some((a_classes, b_classes))
+    }
+}
+
+/// Isomorphisms from `query` onto `target` under caller supplied atom and
+/// bond equivalences, which get query and target indices in that order.
+pub struct Vf2<'a> {
+    query: &'a Molecule,
+    target: &'a Molecule,
+    atom_match: &'a dyn Fn(usize, usize) -> bool,
+    bond_match: &'a dyn Fn(BondIndex, BondIndex) -> bool,
+    stereo: bool,
+    classes: Option<(Vec<usize>, Vec<usize>)>,
+}
+
+// Search state: the partial mapping in both directions
+struct Vf2State {
+    // Query atoms in matching order, each with an earlier neighbour if any
+    order: Vec<(usize, Option<usize>)>,
+    // Required image of the first query atom
+    first_image: Option<usize>,
+    query_to_target: Vec<Option<usize>>,
+    target_to_query: Vec<Option<usize>>,
+    // Complete mappings reached so far
+    mappings: usize,
+}
+
+impl<'a> Vf2<'a> {
+    pub fn new(
+        query: &'a Molecule,
+        target: &'a Molecule,
+        atom_match: &'a dyn Fn(usize, usize) -> bool,
+        bond_match: &'a dyn Fn(BondIndex, BondIndex) -> bool,
+    ) -> Self {
+        Vf2 {
+            query,
+            target,
+            atom_match,
+            bond_match,
+            stereo: false,
+            classes: None,
+        }
+    }
+
+    /// Also require tetrahedral parities and double bond geometry to agree.
+    pub fn stereo(mut self, stereo: bool) -> Self {
+        self.stereo = stereo;
+        self
+    }
+
+    /// Only match atoms of equal class, as given for the query and the
+    /// target atoms. Classes must be numbered alike in both molecules, as
+    /// refinement classes of isomorphic molecules are.
+    pub fn classes(mut self, query: Vec<usize>, target: Vec<usize>) -> Self {
+        self.classes = Some((query, target));
+        self
+    }
+
+    /// The first isomorphism found, as the target atom for each query atom.
+    pub fn first(&self) -> Option<Vec<usize>> {
+        let mut result = None;
+        self.for_each(|mapping| {
+            result = Some(mapping.to_vec());
+            false
+        });
+        result
+    }
+
+    /// Every isomorphism.
+    pub fn all(&self) -> Vec<Vec<usize>> {
+        let mut result = Vec::new();
+        self.for_each(|mapping| {
+            result.push(mapping.to_vec());
+            true
+        });
+        result
+    }
+
+    /// The first isomorphism taking `query_atom` to `target_atom`. The search
+    /// starts from that pair, so it does not revisit the rest of the mapping
+    /// for every wrong choice made before reaching it.
+    pub fn first_from(&self, query_atom: usize, target_atom: usize) -> Option<Vec<usize>> {
+        let mut result = None;
+        self.search(Some((query_atom, target_atom)), &mut |mapping| {
+            result = Some(mapping.to_vec());
+            false
+        });
+        result
+    }
+
+    /// Calls `found` with each isomorphism until it returns false.
+    pub fn for_each(&self, mut found: impl FnMut(&[usize]) -> bool) {
+        self.search(None, &mut found);
+    }
+
+    fn search(&self, seed: Option<(usize, usize)>, found: &mut impl FnMut(&[usize]) -> bool) {
+        if self.query.atoms.len() != self.target.atoms.len()
+            || self.query.bonds.len() != self.target.bonds.len()
+        {
+            return;
+        }
+        let mut state = Vf2State {
+            order: self.matching_order(seed.map_or(0, |(query_atom, _)| query_atom)),
+            first_image: seed.map(|(_, target_atom)| target_atom),
+            query_to_target: vec![None; self.query.atoms.len()],
+            target_to_query: vec![None; self.target.atoms.len()],
+            mappings: 0,
+        };
+        self.extend(0, &mut state, found);
+    }
+
+    // Breadth first through each component from `first`, so later atoms have
+    // a mapped neighbour
+    fn matching_order(&self, first: usize) -> Vec<(usize, Option<usize>)> {
+        let mut order = Vec::with_capacity(self.query.atoms.len());
+        let mut seen = vec![false; self.query.atoms.len()];
+        let n = self.query.atoms.len();
+        for start in std::iter::once(first)
+            .chain(0..n)
+            .filter(|&start| start < n)
+        {
+            if seen[start] {
+                continue;
+            }
+            seen[start] = true;
+            let first = order.len();
+            order.push((start, None));
+            let mut next = first;
+            while next < order.len() {
+                let atom_idx = order[next].0;
+                next += 1;
+                for neighbor in self.query.neighbors(atom_idx) {
+                    if !seen[neighbor] {
+                        seen[neighbor] = true;
+                        order.push((neighbor, Some(atom_idx)));
+                    }
+                }
+            }
+        }
+        order
+    }
+
+    // Returns false once `found` asks to stop
+    fn extend(
+        &self,
+        depth: usize,
+        state: &mut Vf2State,
+        found: &mut impl FnMut(&[usize]) -> bool,
+    ) -> bool {
+        if depth == state.order.len() {
+            let mapping: Vec<usize> = state.query_to_target.iter().map(|t| t.unwrap()).collect();
+            state.mappings += 1;
+            return found(&mapping);
+        }
+
+        let (query_atom, anchor) = state.order[depth];
+        let mappings_before = state.mappings;
+        let candidates: Vec<usize> = match anchor {
+            Some(anchor) => {
+                let image = state.query_to_target[anchor].unwrap();
+                self.target.neighbors(image).collect()
+            }
+            None if depth == 0 && state.first_image.is_some() => {
+                state.first_image.into_iter().collect()
+            }
+            None => (0..self.target.atoms.len()).collect(),
+        };
+        for target_atom in candidates {
+            if !self.feasible(query_atom, target_atom, state) {
+                continue;
+            }
+            state.query_to_target[query_atom] = Some(target_atom);
+            state.target_to_query[target_atom] = Some(query_atom);
+            let keep_going =
+                if self.stereo && !self.stereo_preserved(query_atom, &state.query_to_target) {
+                    true
+                } else {
+                    self.extend(depth + 1, state, found)
+                };
+            state.query_to_target[query_atom] = None;
+            state.target_to_query[target_atom] = None;
+            if !keep_going {
+                return false;
+            }
+        }
+        // The components before this one map alike onto any equivalent
+        // targets, so no other mapping of them lets it complete
+        !(depth > 0 && anchor.is_none() && state.mappings == mappings_before)
+    }
+
+    fn feasible(&self, query_atom: usize, target_atom: usize, state: &Vf2State) -> bool {
+        if state.target_to_query[target_atom].is_some()
+            || self
+                .classes
+                .as_ref()
+                .is_some_and(|(query, target)| query[query_atom] != target[target_atom])
+            || self.query.degree(query_atom) != self.target.degree(target_atom)
+            || !(self.atom_match)(query_atom, target_atom)
+        {
+            return false;
+        }
+        let mut mapped_neighbors = 0;
+        for &query_bond in self.query.bonds_of(query_atom) {
+            let neighbor = self.query.bonds[query_bond].other(query_atom);
+            let Some(image) = state.query_to_target[neighbor] else {
+                continue;
+            };
+            match self.target.bond_between(target_atom, image) {
+                Some(target_bond) if (self.bond_match)(query_bond, target_bond) => {
+                    mapped_neighbors += 1
+                }
+                _ => return false,
+            }
+        }
+        // No extra bonds to mapped atoms on the target side
+        self.target
+            .neighbors(target_atom)
+            .filter(|&n| state.target_to_query[n].is_some())
+            .count()
+            == mapped_neighbors
+    }
+
+    // Stereo at every centre `query_atom` completes, itself or a neighbour,
+    // and at their double bonds once the other end is complete too
+    fn stereo_preserved(&self, query_atom: usize, mapping: &[Option<usize>]) -> bool {
+        let complete = |atom_idx: usize| {
+            mapping[atom_idx].is_some()
+                && self.query.neighbors(atom_idx).all(|n| mapping[n].is_some())
+        };
+        std::iter::once(query_atom)
+            .chain(self.query.neighbors(query_atom))
+            .filter(|&centre| complete(centre))
+            .all(|centre| {
+                tetrahedral_preserved(self.query, self.target, mapping, centre)
+                    && self.query.bonds_of(centre).iter().all(|&b| {
+                        let bond = &self.query.bonds[b];
+                        bond.bond_order != 2
+                            || bond.arom
+                            || !complete(bond.other(centre))
+                            || double_bond_preserved(self.query, self.target, mapping, b)
+                    })
+            })
+    }
+}
+
+impl Molecule {
+    /// An atom mapping from `self` onto `other` under `options`, as the atom
+    /// of `other` for each atom of `self`.
+    pub fn isomorphism(&self, other: &Molecule, options: &MatchOptions) -> Option<Vec<usize>> {
+        let (classes, other_classes) = options.classes(self, other)?;
+        let atom_match = |a, b| options.atoms_match(self, a, other, b);
+        let bond_match = |a, b| options.bonds_match(self, a, other, b);
+        Vf2::new(self, other, &atom_match, &bond_match)
+            .stereo(options.stereo)
+            .classes(classes, other_classes)
+            .first()
+    }
+
+    /// Whether both molecules are the same structure, charges, isotopes,
+    /// hydrogens, stereo and aromaticity included.
+    pub fn is_isomorphic(&self, other: &Molecule) -> bool {
+        self.isomorphism(other, &MatchOptions::default()).is_some()
+    }
+
+    /// Every mapping of the molecule onto itself, the identity included.
+    pub fn automorphisms(&self, options: &MatchOptions) -> Vec<Vec<usize>> {
+        let (classes, _) = options.classes(self, self).unwrap();
+        let atom_match = |a, b| options.atoms_match(self, a, self, b);
+        let bond_match = |a, b| options.bonds_match(self, a, self, b);
+        Vf2::new(self, self, &atom_match, &bond_match)
+            .stereo(options.stereo)
+            .classes(classes.clone(), classes)
+            .all()
+    }
+
+    /// Automorphism orbit of every atom: atoms share an orbit when some
+    /// automorphism swaps them. Orbits are numbered from 0 in order of their
+    /// lowest atom index.
+    ///
+    /// The automorphisms are not enumerated, there can be exponentially many
+    /// (perfluoroalkanes). Each atom is instead tried against one atom of
+    /// every orbit found so far, and a single automorphism linking the two
+    /// merges all the orbits it links.
+    pub fn atom_orbits(&self, options: &MatchOptions) -> Vec<usize> {
+        // Atoms in one orbit stay together under refinement
+        let (classes, _) = options.classes(self, self).unwrap();
+        let atom_match = |a, b| options.atoms_match(self, a, self, b);
+        let bond_match = |a, b| options.bonds_match(self, a, self, b);
+        let vf2 = Vf2::new(self, self, &atom_match, &bond_match)
+            .stereo(options.stereo)
+            .classes(classes.clone(), classes.clone());
+
+        let mut orbit: Vec<usize> = (0..self.atoms.len()).collect();
+        for atom_idx in 0..self.atoms.len() {
+            for lowest in 0..atom_idx {
+                if orbit[lowest] != lowest
+                    || classes[lowest] != classes[atom_idx]
+                    || root(&orbit, atom_idx) == lowest
+                    || !atom_match(lowest, atom_idx)
+                {
+                    continue;
+                }
+                if let Some(mapping) = vf2.first_from(lowest, atom_idx) {
+                    for (atom_idx, &image) in mapping.iter().enumerate() {
+                        union(&mut orbit, atom_idx, image);
+                    }
+                }
+            }
+        }
+        // Renumber densely
+        let mut ids = vec![usize::MAX; self.atoms.len()];
+        let mut next = 0;
+        (0..self.atoms.len())
+            .map(|atom_idx| {
+                let lowest = root(&orbit, atom_idx);
+                if ids[lowest] == usize::MAX {
+                    ids[lowest] = next;
+                    next += 1;
+                }
+                ids[lowest]
+            })
+            .collect()
+    }
+}
+
+// The parity of `atom_idx` carried across `mapping`, which covers it and its
+// neighbours
+fn tetrahedral_preserved(
+    query: &Molecule,
+    target: &Molecule,
+    mapping: &[Option<usize>],
+    atom_idx: usize,
+) -> bool {
+    let image = mapping[atom_idx].unwrap();
+    let (query_parity, target_parity) = match (
+        tetrahedral_parity(query, atom_idx),
+        tetrahedral_parity(target, image),
+    ) {
+        (None, None) => return true,
+        (Some(q), Some(t)) => (q, t),
+        _ => return false,
+    };
+    let query_order: Vec<usize> = parity_order(query, atom_idx)
+        .into_iter()
+        .map(|n| {
+            if n == IMPLICIT_H {
+                n
+            } else {
+                mapping[n].unwrap()
+            }
+        })
+        .collect();
+    let target_order = parity_order(target, image);
+    let Some(permutation) = query_order
+        .iter()
+        .map(|n| target_order.iter().position(|m| m == n))
+        .collect::<Option<Vec<usize>>>()
+    else {
+        return false;
+    };
+    let inversions = (0..permutation.len())
+        .flat_map(|i| (i + 1..permutation.len()).map(move |j| (i, j)))
+        .filter(|&(i, j)| permutation[i] > permutation[j])
+        .count();
+    (query_parity != (inversions % 2 == 1)) == target_parity
+}
+
+// The geometry of double bond `bond_idx` carried across `mapping`, which
+// covers both ends and their neighbours
+fn double_bond_preserved(
+    query: &Molecule,
+    target: &Molecule,
+    mapping: &[Option<usize>],
+    bond_idx: BondIndex,
+) -> bool {
+    let bond = &query.bonds[bond_idx];
+    let (left, right) = (bond.source, bond.dest);
+    let (Some(x), Some(y)) = (
+        query.neighbors(left).find(|&n| n != right),
+        query.neighbors(right).find(|&n| n != left),
+    ) else {
+        return true;
+    };
+    let image = |atom_idx: usize| mapping[atom_idx].unwrap();
+    cis(query, left, right, x, y) == cis(target, image(left), image(right), image(x), image(y))
+}
+
+// False for TH1, true for TH2
+fn tetrahedral_parity(molecule: &Molecule, atom_idx: usize) -> Option<bool> {
+    match molecule.atoms[atom_idx].configuration {
+        Some(Configuration::TH1) => Some(false),
+        Some(Configuration::TH2) => Some(true),
+        _ => None,
+    }
+}
+
+// Neighbours in the order tetrahedral parities refer to
+fn parity_order(molecule: &Molecule, atom_idx: usize) -> Vec<usize> {
+    let mut order: Vec<usize> = molecule.neighbors(atom_idx).collect();
+    if molecule.atoms[atom_idx].hydrogens > 0 {
+        order.insert(order.len().min(1), IMPLICIT_H);
+    }
+    order
+}
+
+// Whether `x` on `left` and `y` on `right` are cis, if the marks say
+fn cis(molecule: &Molecule, left: usize, right: usize, x: usize, y: usize) -> Option<bool> {
+    Some(side_up(molecule, left, right, x)? == side_up(molecule, right, left, y)?)
+}
+
+// Whether `neighbor` of `end` is "up", from its own mark or its partner's
+fn side_up(molecule: &Molecule, end: usize, across: usize, neighbor: usize) -> Option<bool> {
+    let substituents: Vec<BondIndex> = molecule
+        .bonds_of(end)
+        .iter()
+        .copied()
+        .filter(|&b| molecule.bonds[b].other(end) != across)
+        .collect();
+    let &marked = substituents
+        .iter()
+        .find(|&&b| molecule.bonds[b].axialness != Axialness::UNKNOWN)?;
+    let bond = &molecule.bonds[marked];
+    let up = (bond.axialness == Axialness::UP) == (bond.source == end);
+    Some(if bond.other(end) == neighbor { up } else { !up })
+}
+
+fn sorted(ranks: &[usize]) -> Vec<usize> {
+    let mut sorted = ranks.to_vec();
+    sorted.sort_unstable();
+    sorted
+}
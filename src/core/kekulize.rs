@@ -0,0 +1,207 @@
+// Kekulization: aromatic bonds get explicit single/double orders from a
+// maximum matching (Edmonds' blossom algorithm) over the atoms that still
+// need a double bond. Aromatic flags are left alone.
+use std::{collections::VecDeque, fmt};
+
+use super::{molecule::Molecule, valence::allowed_valences};
+
+const NONE: usize = usize::MAX;
+
+/// No Kekulé structure exists, the listed atoms were left without a double bond.
+#[derive(Clone, Debug, PartialEq)]
+pub struct KekulizeError {
+    pub unmatched: Vec<usize>,
+}
+
+impl fmt::Display for KekulizeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "can't kekulize, no double bond for aromatic atoms {:?}",
+            self.unmatched
+        )
+    }
+}
+
+impl std::error::Error for KekulizeError {}
+
+impl Molecule {
+    /// Assigns alternating single and double bonds to every aromatic bond.
+    /// Running it again gives the same orders, the input is always read with
+    /// aromatic bonds counted as single bonds.
+    pub fn kekulize(&mut self) -> Result<(), KekulizeError> {
+        // Atoms that need a double bond, with a compact index for the matching
+        let atoms: Vec<usize> = (0..self.atoms.len())
+            .filter(|&atom_idx| self.needs_double_bond(atom_idx))
+            .collect();
+        let mut node_of = vec![NONE; self.atoms.len()];
+        for (node, &atom_idx) in atoms.iter().enumerate() {
+            node_of[atom_idx] = node;
+        }
+
+        let mut adjacency = vec![Vec::new(); atoms.len()];
+        for bond in self.bonds.iter().filter(|b| b.arom) {
+            let (a, b) = (node_of[bond.source], node_of[bond.dest]);
+            if a != NONE && b != NONE {
+                adjacency[a].push(b);
+                adjacency[b].push(a);
+            }
+        }
+
+        let mate = maximum_matching(&adjacency);
+        let unmatched: Vec<usize> = (0..atoms.len())
+            .filter(|&v| mate[v] == NONE)
+            .map(|v| atoms[v])
+            .collect();
+        if !unmatched.is_empty() {
+            return Err(KekulizeError { unmatched });
+        }
+
+        for bond in self.bonds.iter_mut().filter(|b| b.arom) {
+            let (a, b) = (node_of[bond.source], node_of[bond.dest]);
+            bond.bond_order = if a != NONE && mate[a] == b { 2 } else { 1 };
+        }
+        Ok(())
+    }
+
+    /// Aromatic atom one bond short of a normal valence. Pyrrole type `[nH]`,
+    /// furan `o` and atoms with an exocyclic double bond don't qualify.
+    fn needs_double_bond(&self, atom_idx: usize) -> bool {
+        let atom = &self.atoms[atom_idx];
+        if !atom.aromatic {
+            return false;
+        }
+        let mut valence = atom.hydrogens as i32;
+        for &bond_idx in self.bonds_of(atom_idx) {
+            let bond = &self.bonds[bond_idx];
+            valence += if bond.arom { 1 } else { bond.bond_order as i32 };
+        }
+
+        let targets = allowed_valences(atom.element, atom.f_charge);
+        !targets.contains(&(valence as i8)) && targets.contains(&(valence as i8 + 1))
+    }
+}
+
+/// Maximum cardinality matching in a general graph, `mate[v]` is `NONE`
+/// for unmatched vertices.
+fn maximum_matching(adjacency: &[Vec<usize>]) -> Vec<usize> {
+    let n = adjacency.len();
+    let mut mate = vec![NONE; n];
+
+    // A greedy start leaves few augmenting paths to find
+    for v in 0..n {
+        if mate[v] == NONE {
+            if let Some(&w) = adjacency[v].iter().find(|&&w| mate[w] == NONE) {
+                mate[v] = w;
+                mate[w] = v;
+            }
+        }
+    }
+
+    for root in 0..n {
+        if mate[root] != NONE {
+            continue;
+        }
+        let mut search = Blossom::new(n);
+        if let Some(end) = search.augmenting_path(adjacency, &mate, root) {
+            let mut v = end;
+            while v != NONE {
+                let parent = search.parent[v];
+                let next = mate[parent];
+                mate[v] = parent;
+                mate[parent] = v;
+                v = next;
+            }
+        }
+    }
+    mate
+}
+
+// State of one search for an augmenting path from an unmatched root
+struct Blossom {
+    parent: Vec<usize>,
+    base: Vec<usize>,
+    used: Vec<bool>,
+    in_blossom: Vec<bool>,
+}
+
+impl Blossom {
+    fn new(n: usize) -> Blossom {
+        Blossom {
+            parent: vec![NONE; n],
+            base: (0..n).collect(),
+            used: vec![false; n],
+            in_blossom: vec![false; n],
+        }
+    }
+
+    fn augmenting_path(
+        &mut self,
+        adjacency: &[Vec<usize>],
+        mate: &[usize],
+        root: usize,
+    ) -> Option<usize> {
+        self.used[root] = true;
+        let mut queue = VecDeque::from([root]);
+        while let Some(v) = queue.pop_front() {
+            for &to in &adjacency[v] {
+                if self.base[v] == self.base[to] || mate[v] == to {
+                    continue;
+                }
+                if to == root || (mate[to] != NONE && self.parent[mate[to]] != NONE) {
+                    // Odd cycle, contract it onto its base
+                    let base = self.common_base(mate, v, to);
+                    self.in_blossom.iter_mut().for_each(|b| *b = false);
+                    self.mark_path(mate, v, base, to);
+                    self.mark_path(mate, to, base, v);
+                    for i in 0..adjacency.len() {
+                        if self.in_blossom[self.base[i]] {
+                            self.base[i] = base;
+                            if !self.used[i] {
+                                self.used[i] = true;
+                                queue.push_back(i);
+                            }
+                        }
+                    }
+                } else if self.parent[to] == NONE {
+                    self.parent[to] = v;
+                    if mate[to] == NONE {
+                        return Some(to);
+                    }
+                    self.used[mate[to]] = true;
+                    queue.push_back(mate[to]);
+                }
+            }
+        }
+        None
+    }
+
+    fn common_base(&self, mate: &[usize], mut a: usize, mut b: usize) -> usize {
+        let mut seen = vec![false; mate.len()];
+        loop {
+            a = self.base[a];
+            seen[a] = true;
+            if mate[a] == NONE {
+                break;
+            }
+            a = self.parent[mate[a]];
+        }
+        loop {
+            b = self.base[b];
+            if seen[b] {
+                return b;
+            }
+            b = self.parent[mate[b]];
+        }
+    }
+
+    fn mark_path(&mut self, mate: &[usize], mut v: usize, base: usize, mut child: usize) {
+        while self.base[v] != base {
+            self.in_blossom[self.base[v]] = true;
+            self.in_blossom[self.base[mate[v]]] = true;
+            self.parent[v] = child;
+            child = mate[v];
+            v = self.parent[mate[v]];
+        }
+    }
+}
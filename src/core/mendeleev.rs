@@ -1,3 +1,97 @@
+/// A naturally occurring isotope of an element.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Isotope {
+    /// Number of nucleons.
+    pub mass_number: usize,
+    /// Atomic mass in daltons.
+    pub mass: f64,
+    /// Natural abundance as a fraction (0.0 to 1.0).
+    pub abundance: f64,
+}
+
+/// Periodic table entry.
+///
+/// Masses and abundances follow the IUPAC/NIST tables. Elements without a
+/// stable isotope have no natural isotopes listed; their average and
+/// monoisotopic masses are those of the longest-lived isotope. Covalent
+/// radii are from Cordero et al. (2008), falling back to Pyykkö & Atsumi
+/// (2009) past curium. Van der Waals radii are Bondi's, with Mantina et al.
+/// (2009) filling in the main group gaps.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Element {
+    pub atomic_number: usize,
+    pub symbol: &'static str,
+    pub name: &'static str,
+    /// Standard atomic weight in daltons.
+    pub average_mass: f64,
+    /// Mass of the most abundant isotope in daltons.
+    pub monoisotopic_mass: f64,
+    pub isotopes: &'static [Isotope],
+    /// Default valences in increasing order; empty when there is no sensible
+    /// default (most metals).
+    pub valences: &'static [i8],
+    /// Pauling electronegativity.
+    pub electronegativity: Option<f64>,
+    /// Single bond covalent radius in angstroms.
+    pub covalent_radius: f64,
+    /// Van der Waals radius in angstroms.
+    pub vdw_radius: Option<f64>,
+}
+
+impl Element {
+    /// Looks up a natural isotope by mass number.
+    pub fn isotope(&self, mass_number: usize) -> Option<&'static Isotope> {
+        self.isotopes.iter().find(|i| i.mass_number == mass_number)
+    }
+
+    /// Mass of an isotope in daltons: a natural one, one of the tracers in
+    /// `TRACER_ISOTOPES`, or for an element without stable isotopes the
+    /// longest-lived one its masses are taken from.
+    pub fn isotope_mass(&self, mass_number: usize) -> Option<f64> {
+        if let Some(isotope) = self.isotope(mass_number) {
+            return Some(isotope.mass);
+        }
+        let tracer = TRACER_ISOTOPES
+            .iter()
+            .find(|(z, i)| *z == self.atomic_number && i.mass_number == mass_number);
+        match tracer {
+            Some((_, isotope)) => Some(isotope.mass),
+            None if self.isotopes.is_empty()
+                && self.monoisotopic_mass.round() as usize == mass_number =>
+            {
+                Some(self.monoisotopic_mass)
+            }
+            None => None,
+        }
+    }
+
+    /// The most abundant natural isotope, if the element has any.
+    pub fn most_abundant_isotope(&self) -> Option<&'static Isotope> {
+        self.isotopes
+            .iter()
+            .max_by(|a, b| a.abundance.total_cmp(&b.abundance))
+    }
+}
+
+/// Looks up an element by atomic number (1 to 118).
+pub fn element(atomic_number: usize) -> Option<&'static Element> {
+    match atomic_number {
+        1..=118 => Some(&ELEMENTS[atomic_number - 1]),
+        _ => None,
+    }
+}
+
+/// Looks up an element by its case-sensitive symbol, e.g. "Cl".
+pub fn element_by_symbol(symbol: &str) -> Option<&'static Element> {
+    ELEMENTS.iter().find(|e| e.symbol == symbol)
+}
+
+/// Element symbol for an atomic number, "*" for the wildcard atom (0) or
+/// anything out of range.
+pub fn symbol(atomic_number: usize) -> &'static str {
+    element(atomic_number).map_or("*", |e| e.symbol)
+}
+
 pub fn valence_electrons(atomic_number: usize) -> usize {
     match atomic_number {
         1 => 1,                               // Hydrogen
@@ -12,34 +106,1758 @@ pub fn valence_electrons(atomic_number: usize) -> usize {
     }
 }
 
-pub fn target_valences_for_smiles(z: usize) -> &'static [i8] {
-    // Same as above, but keep it in your chem module
-    match z {
-        5 => &[3],                // B
-        6 => &[4],                // C
-        7 => &[3, 5],             // N
-        8 => &[2],                // O
-        15 => &[3, 5],            // P
-        16 => &[2, 4, 6],         // S
-        9 | 17 | 35 | 53 => &[1], // F, Cl, Br, I
-        _ => &[0],
+const fn iso(mass_number: usize, mass: f64, abundance: f64) -> Isotope {
+    Isotope {
+        mass_number,
+        mass,
+        abundance,
     }
 }
 
-pub fn compute_implicit_h_from_valences(z: usize, bond_order_sum: i32) -> usize {
-    let targets = target_valences_for_smiles(z);
-    let valence = bond_order_sum.max(0);
-
-    let target = targets
-        .iter()
-        .filter(|&&t| t >= valence as i8)
-        .min()
-        .copied()
-        .unwrap_or(0);
+pub static ELEMENTS: [Element; 118] = [
+    Element {
+        atomic_number: 1,
+        symbol: "H",
+        name: "Hydrogen",
+        average_mass: 1.008,
+        monoisotopic_mass: 1.00782503223,
+        isotopes: &[
+            iso(1, 1.00782503223, 0.999885),
+            iso(2, 2.01410177812, 0.000115),
+        ],
+        valences: &[1],
+        electronegativity: Some(2.20),
+        covalent_radius: 0.31,
+        vdw_radius: Some(1.20),
+    },
+    Element {
+        atomic_number: 2,
+        symbol: "He",
+        name: "Helium",
+        average_mass: 4.002602,
+        monoisotopic_mass: 4.00260325413,
+        isotopes: &[
+            iso(3, 3.0160293201, 0.00000134),
+            iso(4, 4.00260325413, 0.99999866),
+        ],
+        valences: &[0],
+        electronegativity: None,
+        covalent_radius: 0.28,
+        vdw_radius: Some(1.40),
+    },
+    Element {
+        atomic_number: 3,
+        symbol: "Li",
+        name: "Lithium",
+        average_mass: 6.94,
+        monoisotopic_mass: 7.0160034366,
+        isotopes: &[iso(6, 6.0151228874, 0.0759), iso(7, 7.0160034366, 0.9241)],
+        valences: &[1],
+        electronegativity: Some(0.98),
+        covalent_radius: 1.28,
+        vdw_radius: Some(1.82),
+    },
+    Element {
+        atomic_number: 4,
+        symbol: "Be",
+        name: "Beryllium",
+        average_mass: 9.0121831,
+        monoisotopic_mass: 9.012183065,
+        isotopes: &[iso(9, 9.012183065, 1.0)],
+        valences: &[2],
+        electronegativity: Some(1.57),
+        covalent_radius: 0.96,
+        vdw_radius: Some(1.53),
+    },
+    Element {
+        atomic_number: 5,
+        symbol: "B",
+        name: "Boron",
+        average_mass: 10.81,
+        monoisotopic_mass: 11.00930536,
+        isotopes: &[iso(10, 10.01293695, 0.199), iso(11, 11.00930536, 0.801)],
+        valences: &[3],
+        electronegativity: Some(2.04),
+        covalent_radius: 0.84,
+        vdw_radius: Some(1.92),
+    },
+    Element {
+        atomic_number: 6,
+        symbol: "C",
+        name: "Carbon",
+        average_mass: 12.011,
+        monoisotopic_mass: 12.0,
+        isotopes: &[iso(12, 12.0, 0.9893), iso(13, 13.00335483507, 0.0107)],
+        valences: &[4],
+        electronegativity: Some(2.55),
+        covalent_radius: 0.76,
+        vdw_radius: Some(1.70),
+    },
+    Element {
+        atomic_number: 7,
+        symbol: "N",
+        name: "Nitrogen",
+        average_mass: 14.007,
+        monoisotopic_mass: 14.00307400443,
+        isotopes: &[
+            iso(14, 14.00307400443, 0.99636),
+            iso(15, 15.00010889888, 0.00364),
+        ],
+        valences: &[3, 5],
+        electronegativity: Some(3.04),
+        covalent_radius: 0.71,
+        vdw_radius: Some(1.55),
+    },
+    Element {
+        atomic_number: 8,
+        symbol: "O",
+        name: "Oxygen",
+        average_mass: 15.999,
+        monoisotopic_mass: 15.99491461957,
+        isotopes: &[
+            iso(16, 15.99491461957, 0.99757),
+            iso(17, 16.99913175650, 0.00038),
+            iso(18, 17.99915961286, 0.00205),
+        ],
+        valences: &[2],
+        electronegativity: Some(3.44),
+        covalent_radius: 0.66,
+        vdw_radius: Some(1.52),
+    },
+    Element {
+        atomic_number: 9,
+        symbol: "F",
+        name: "Fluorine",
+        average_mass: 18.998403163,
+        monoisotopic_mass: 18.99840316273,
+        isotopes: &[iso(19, 18.99840316273, 1.0)],
+        valences: &[1],
+        electronegativity: Some(3.98),
+        covalent_radius: 0.57,
+        vdw_radius: Some(1.47),
+    },
+    Element {
+        atomic_number: 10,
+        symbol: "Ne",
+        name: "Neon",
+        average_mass: 20.1797,
+        monoisotopic_mass: 19.9924401762,
+        isotopes: &[
+            iso(20, 19.9924401762, 0.9048),
+            iso(21, 20.993846685, 0.0027),
+            iso(22, 21.991385114, 0.0925),
+        ],
+        valences: &[0],
+        electronegativity: None,
+        covalent_radius: 0.58,
+        vdw_radius: Some(1.54),
+    },
+    Element {
+        atomic_number: 11,
+        symbol: "Na",
+        name: "Sodium",
+        average_mass: 22.98976928,
+        monoisotopic_mass: 22.9897692820,
+        isotopes: &[iso(23, 22.9897692820, 1.0)],
+        valences: &[1],
+        electronegativity: Some(0.93),
+        covalent_radius: 1.66,
+        vdw_radius: Some(2.27),
+    },
+    Element {
+        atomic_number: 12,
+        symbol: "Mg",
+        name: "Magnesium",
+        average_mass: 24.305,
+        monoisotopic_mass: 23.985041697,
+        isotopes: &[
+            iso(24, 23.985041697, 0.7899),
+            iso(25, 24.985836976, 0.1000),
+            iso(26, 25.982592968, 0.1101),
+        ],
+        valences: &[2],
+        electronegativity: Some(1.31),
+        covalent_radius: 1.41,
+        vdw_radius: Some(1.73),
+    },
+    Element {
+        atomic_number: 13,
+        symbol: "Al",
+        name: "Aluminium",
+        average_mass: 26.9815385,
+        monoisotopic_mass: 26.98153853,
+        isotopes: &[iso(27, 26.98153853, 1.0)],
+        valences: &[3],
+        electronegativity: Some(1.61),
+        covalent_radius: 1.21,
+        vdw_radius: Some(1.84),
+    },
+    Element {
+        atomic_number: 14,
+        symbol: "Si",
+        name: "Silicon",
+        average_mass: 28.085,
+        monoisotopic_mass: 27.97692653465,
+        isotopes: &[
+            iso(28, 27.97692653465, 0.92223),
+            iso(29, 28.97649466490, 0.04685),
+            iso(30, 29.973770136, 0.03092),
+        ],
+        valences: &[4],
+        electronegativity: Some(1.90),
+        covalent_radius: 1.11,
+        vdw_radius: Some(2.10),
+    },
+    Element {
+        atomic_number: 15,
+        symbol: "P",
+        name: "Phosphorus",
+        average_mass: 30.973761998,
+        monoisotopic_mass: 30.97376199842,
+        isotopes: &[iso(31, 30.97376199842, 1.0)],
+        valences: &[3, 5],
+        electronegativity: Some(2.19),
+        covalent_radius: 1.07,
+        vdw_radius: Some(1.80),
+    },
+    Element {
+        atomic_number: 16,
+        symbol: "S",
+        name: "Sulfur",
+        average_mass: 32.06,
+        monoisotopic_mass: 31.9720711744,
+        isotopes: &[
+            iso(32, 31.9720711744, 0.9499),
+            iso(33, 32.9714589098, 0.0075),
+            iso(34, 33.967867004, 0.0425),
+            iso(36, 35.96708071, 0.0001),
+        ],
+        valences: &[2, 4, 6],
+        electronegativity: Some(2.58),
+        covalent_radius: 1.05,
+        vdw_radius: Some(1.80),
+    },
+    Element {
+        atomic_number: 17,
+        symbol: "Cl",
+        name: "Chlorine",
+        average_mass: 35.45,
+        monoisotopic_mass: 34.968852682,
+        isotopes: &[iso(35, 34.968852682, 0.7576), iso(37, 36.965902602, 0.2424)],
+        valences: &[1],
+        electronegativity: Some(3.16),
+        covalent_radius: 1.02,
+        vdw_radius: Some(1.75),
+    },
+    Element {
+        atomic_number: 18,
+        symbol: "Ar",
+        name: "Argon",
+        average_mass: 39.948,
+        monoisotopic_mass: 39.9623831237,
+        isotopes: &[
+            iso(36, 35.967545105, 0.003336),
+            iso(38, 37.96273211, 0.000629),
+            iso(40, 39.9623831237, 0.996035),
+        ],
+        valences: &[0],
+        electronegativity: None,
+        covalent_radius: 1.06,
+        vdw_radius: Some(1.88),
+    },
+    Element {
+        atomic_number: 19,
+        symbol: "K",
+        name: "Potassium",
+        average_mass: 39.0983,
+        monoisotopic_mass: 38.9637064864,
+        isotopes: &[
+            iso(39, 38.9637064864, 0.932581),
+            iso(40, 39.963998166, 0.000117),
+            iso(41, 40.9618252579, 0.067302),
+        ],
+        valences: &[1],
+        electronegativity: Some(0.82),
+        covalent_radius: 2.03,
+        vdw_radius: Some(2.75),
+    },
+    Element {
+        atomic_number: 20,
+        symbol: "Ca",
+        name: "Calcium",
+        average_mass: 40.078,
+        monoisotopic_mass: 39.962590863,
+        isotopes: &[
+            iso(40, 39.962590863, 0.96941),
+            iso(42, 41.95861783, 0.00647),
+            iso(43, 42.95876644, 0.00135),
+            iso(44, 43.95548156, 0.02086),
+            iso(46, 45.9536890, 0.00004),
+            iso(48, 47.95252276, 0.00187),
+        ],
+        valences: &[2],
+        electronegativity: Some(1.00),
+        covalent_radius: 1.76,
+        vdw_radius: Some(2.31),
+    },
+    Element {
+        atomic_number: 21,
+        symbol: "Sc",
+        name: "Scandium",
+        average_mass: 44.955908,
+        monoisotopic_mass: 44.95590828,
+        isotopes: &[iso(45, 44.95590828, 1.0)],
+        valences: &[],
+        electronegativity: Some(1.36),
+        covalent_radius: 1.70,
+        vdw_radius: None,
+    },
+    Element {
+        atomic_number: 22,
+        symbol: "Ti",
+        name: "Titanium",
+        average_mass: 47.867,
+        monoisotopic_mass: 47.94794198,
+        isotopes: &[
+            iso(46, 45.95262772, 0.0825),
+            iso(47, 46.95175879, 0.0744),
+            iso(48, 47.94794198, 0.7372),
+            iso(49, 48.94786568, 0.0541),
+            iso(50, 49.94478689, 0.0518),
+        ],
+        valences: &[],
+        electronegativity: Some(1.54),
+        covalent_radius: 1.60,
+        vdw_radius: None,
+    },
+    Element {
+        atomic_number: 23,
+        symbol: "V",
+        name: "Vanadium",
+        average_mass: 50.9415,
+        monoisotopic_mass: 50.94395704,
+        isotopes: &[iso(50, 49.94715601, 0.00250), iso(51, 50.94395704, 0.99750)],
+        valences: &[],
+        electronegativity: Some(1.63),
+        covalent_radius: 1.53,
+        vdw_radius: None,
+    },
+    Element {
+        atomic_number: 24,
+        symbol: "Cr",
+        name: "Chromium",
+        average_mass: 51.9961,
+        monoisotopic_mass: 51.94050623,
+        isotopes: &[
+            iso(50, 49.94604183, 0.04345),
+            iso(52, 51.94050623, 0.83789),
+            iso(53, 52.94064815, 0.09501),
+            iso(54, 53.93887916, 0.02365),
+        ],
+        valences: &[],
+        electronegativity: Some(1.66),
+        covalent_radius: 1.39,
+        vdw_radius: None,
+    },
+    Element {
+        atomic_number: 25,
+        symbol: "Mn",
+        name: "Manganese",
+        average_mass: 54.938044,
+        monoisotopic_mass: 54.93804391,
+        isotopes: &[iso(55, 54.93804391, 1.0)],
+        valences: &[],
+        electronegativity: Some(1.55),
+        covalent_radius: 1.39,
+        vdw_radius: None,
+    },
+    Element {
+        atomic_number: 26,
+        symbol: "Fe",
+        name: "Iron",
+        average_mass: 55.845,
+        monoisotopic_mass: 55.93493633,
+        isotopes: &[
+            iso(54, 53.93960899, 0.05845),
+            iso(56, 55.93493633, 0.91754),
+            iso(57, 56.93539284, 0.02119),
+            iso(58, 57.93327443, 0.00282),
+        ],
+        valences: &[],
+        electronegativity: Some(1.83),
+        covalent_radius: 1.32,
+        vdw_radius: None,
+    },
+    Element {
+        atomic_number: 27,
+        symbol: "Co",
+        name: "Cobalt",
+        average_mass: 58.933194,
+        monoisotopic_mass: 58.93319429,
+        isotopes: &[iso(59, 58.93319429, 1.0)],
+        valences: &[],
+        electronegativity: Some(1.88),
+        covalent_radius: 1.26,
+        vdw_radius: None,
+    },
+    Element {
+        atomic_number: 28,
+        symbol: "Ni",
+        name: "Nickel",
+        average_mass: 58.6934,
+        monoisotopic_mass: 57.93534241,
+        isotopes: &[
+            iso(58, 57.93534241, 0.68077),
+            iso(60, 59.93078588, 0.26223),
+            iso(61, 60.93105557, 0.011399),
+            iso(62, 61.92834537, 0.036346),
+            iso(64, 63.92796682, 0.009255),
+        ],
+        valences: &[],
+        electronegativity: Some(1.91),
+        covalent_radius: 1.24,
+        vdw_radius: Some(1.63),
+    },
+    Element {
+        atomic_number: 29,
+        symbol: "Cu",
+        name: "Copper",
+        average_mass: 63.546,
+        monoisotopic_mass: 62.92959772,
+        isotopes: &[iso(63, 62.92959772, 0.6915), iso(65, 64.92778970, 0.3085)],
+        valences: &[],
+        electronegativity: Some(1.90),
+        covalent_radius: 1.32,
+        vdw_radius: Some(1.40),
+    },
+    Element {
+        atomic_number: 30,
+        symbol: "Zn",
+        name: "Zinc",
+        average_mass: 65.38,
+        monoisotopic_mass: 63.92914201,
+        isotopes: &[
+            iso(64, 63.92914201, 0.4917),
+            iso(66, 65.92603381, 0.2773),
+            iso(67, 66.92712775, 0.0404),
+            iso(68, 67.92484455, 0.1845),
+            iso(70, 69.9253192, 0.0061),
+        ],
+        valences: &[],
+        electronegativity: Some(1.65),
+        covalent_radius: 1.22,
+        vdw_radius: Some(1.39),
+    },
+    Element {
+        atomic_number: 31,
+        symbol: "Ga",
+        name: "Gallium",
+        average_mass: 69.723,
+        monoisotopic_mass: 68.9255735,
+        isotopes: &[iso(69, 68.9255735, 0.60108), iso(71, 70.92470258, 0.39892)],
+        valences: &[3],
+        electronegativity: Some(1.81),
+        covalent_radius: 1.22,
+        vdw_radius: Some(1.87),
+    },
+    Element {
+        atomic_number: 32,
+        symbol: "Ge",
+        name: "Germanium",
+        average_mass: 72.630,
+        monoisotopic_mass: 73.921177761,
+        isotopes: &[
+            iso(70, 69.92424875, 0.2057),
+            iso(72, 71.922075826, 0.2745),
+            iso(73, 72.923458956, 0.0775),
+            iso(74, 73.921177761, 0.3650),
+            iso(76, 75.921402726, 0.0773),
+        ],
+        valences: &[4],
+        electronegativity: Some(2.01),
+        covalent_radius: 1.20,
+        vdw_radius: Some(2.11),
+    },
+    Element {
+        atomic_number: 33,
+        symbol: "As",
+        name: "Arsenic",
+        average_mass: 74.921595,
+        monoisotopic_mass: 74.92159457,
+        isotopes: &[iso(75, 74.92159457, 1.0)],
+        valences: &[3, 5],
+        electronegativity: Some(2.18),
+        covalent_radius: 1.19,
+        vdw_radius: Some(1.85),
+    },
+    Element {
+        atomic_number: 34,
+        symbol: "Se",
+        name: "Selenium",
+        average_mass: 78.971,
+        monoisotopic_mass: 79.9165218,
+        isotopes: &[
+            iso(74, 73.922475934, 0.0089),
+            iso(76, 75.919213704, 0.0937),
+            iso(77, 76.919914154, 0.0763),
+            iso(78, 77.91730928, 0.2377),
+            iso(80, 79.9165218, 0.4961),
+            iso(82, 81.9166995, 0.0873),
+        ],
+        valences: &[2, 4, 6],
+        electronegativity: Some(2.55),
+        covalent_radius: 1.20,
+        vdw_radius: Some(1.90),
+    },
+    Element {
+        atomic_number: 35,
+        symbol: "Br",
+        name: "Bromine",
+        average_mass: 79.904,
+        monoisotopic_mass: 78.9183376,
+        isotopes: &[iso(79, 78.9183376, 0.5069), iso(81, 80.9162897, 0.4931)],
+        valences: &[1],
+        electronegativity: Some(2.96),
+        covalent_radius: 1.20,
+        vdw_radius: Some(1.85),
+    },
+    Element {
+        atomic_number: 36,
+        symbol: "Kr",
+        name: "Krypton",
+        average_mass: 83.798,
+        monoisotopic_mass: 83.9114977282,
+        isotopes: &[
+            iso(78, 77.92036494, 0.00355),
+            iso(80, 79.91637808, 0.02286),
+            iso(82, 81.91348273, 0.11593),
+            iso(83, 82.91412716, 0.11500),
+            iso(84, 83.9114977282, 0.56987),
+            iso(86, 85.9106106269, 0.17279),
+        ],
+        valences: &[0],
+        electronegativity: Some(3.00),
+        covalent_radius: 1.16,
+        vdw_radius: Some(2.02),
+    },
+    Element {
+        atomic_number: 37,
+        symbol: "Rb",
+        name: "Rubidium",
+        average_mass: 85.4678,
+        monoisotopic_mass: 84.9117897379,
+        isotopes: &[
+            iso(85, 84.9117897379, 0.7217),
+            iso(87, 86.9091805310, 0.2783),
+        ],
+        valences: &[1],
+        electronegativity: Some(0.82),
+        covalent_radius: 2.20,
+        vdw_radius: Some(3.03),
+    },
+    Element {
+        atomic_number: 38,
+        symbol: "Sr",
+        name: "Strontium",
+        average_mass: 87.62,
+        monoisotopic_mass: 87.9056125,
+        isotopes: &[
+            iso(84, 83.9134191, 0.0056),
+            iso(86, 85.9092606, 0.0986),
+            iso(87, 86.9088775, 0.0700),
+            iso(88, 87.9056125, 0.8258),
+        ],
+        valences: &[2],
+        electronegativity: Some(0.95),
+        covalent_radius: 1.95,
+        vdw_radius: Some(2.49),
+    },
+    Element {
+        atomic_number: 39,
+        symbol: "Y",
+        name: "Yttrium",
+        average_mass: 88.90584,
+        monoisotopic_mass: 88.9058403,
+        isotopes: &[iso(89, 88.9058403, 1.0)],
+        valences: &[],
+        electronegativity: Some(1.22),
+        covalent_radius: 1.90,
+        vdw_radius: None,
+    },
+    Element {
+        atomic_number: 40,
+        symbol: "Zr",
+        name: "Zirconium",
+        average_mass: 91.224,
+        monoisotopic_mass: 89.9046977,
+        isotopes: &[
+            iso(90, 89.9046977, 0.5145),
+            iso(91, 90.9056396, 0.1122),
+            iso(92, 91.9050347, 0.1715),
+            iso(94, 93.9063108, 0.1738),
+            iso(96, 95.9082714, 0.0280),
+        ],
+        valences: &[],
+        electronegativity: Some(1.33),
+        covalent_radius: 1.75,
+        vdw_radius: None,
+    },
+    Element {
+        atomic_number: 41,
+        symbol: "Nb",
+        name: "Niobium",
+        average_mass: 92.90637,
+        monoisotopic_mass: 92.9063730,
+        isotopes: &[iso(93, 92.9063730, 1.0)],
+        valences: &[],
+        electronegativity: Some(1.6),
+        covalent_radius: 1.64,
+        vdw_radius: None,
+    },
+    Element {
+        atomic_number: 42,
+        symbol: "Mo",
+        name: "Molybdenum",
+        average_mass: 95.95,
+        monoisotopic_mass: 97.90540482,
+        isotopes: &[
+            iso(92, 91.90680796, 0.1453),
+            iso(94, 93.90508490, 0.0915),
+            iso(95, 94.90583877, 0.1584),
+            iso(96, 95.90467612, 0.1667),
+            iso(97, 96.90601812, 0.0960),
+            iso(98, 97.90540482, 0.2439),
+            iso(100, 99.9074718, 0.0982),
+        ],
+        valences: &[],
+        electronegativity: Some(2.16),
+        covalent_radius: 1.54,
+        vdw_radius: None,
+    },
+    Element {
+        atomic_number: 43,
+        symbol: "Tc",
+        name: "Technetium",
+        average_mass: 98.0,
+        monoisotopic_mass: 97.9072124,
+        isotopes: &[],
+        valences: &[],
+        electronegativity: Some(1.9),
+        covalent_radius: 1.47,
+        vdw_radius: None,
+    },
+    Element {
+        atomic_number: 44,
+        symbol: "Ru",
+        name: "Ruthenium",
+        average_mass: 101.07,
+        monoisotopic_mass: 101.9043441,
+        isotopes: &[
+            iso(96, 95.90759025, 0.0554),
+            iso(98, 97.9052868, 0.0187),
+            iso(99, 98.9059341, 0.1276),
+            iso(100, 99.9042143, 0.1260),
+            iso(101, 100.9055769, 0.1706),
+            iso(102, 101.9043441, 0.3155),
+            iso(104, 103.9054275, 0.1862),
+        ],
+        valences: &[],
+        electronegativity: Some(2.2),
+        covalent_radius: 1.46,
+        vdw_radius: None,
+    },
+    Element {
+        atomic_number: 45,
+        symbol: "Rh",
+        name: "Rhodium",
+        average_mass: 102.90550,
+        monoisotopic_mass: 102.9054980,
+        isotopes: &[iso(103, 102.9054980, 1.0)],
+        valences: &[],
+        electronegativity: Some(2.28),
+        covalent_radius: 1.42,
+        vdw_radius: None,
+    },
+    Element {
+        atomic_number: 46,
+        symbol: "Pd",
+        name: "Palladium",
+        average_mass: 106.42,
+        monoisotopic_mass: 105.9034804,
+        isotopes: &[
+            iso(102, 101.9056022, 0.0102),
+            iso(104, 103.9040305, 0.1114),
+            iso(105, 104.9050796, 0.2233),
+            iso(106, 105.9034804, 0.2733),
+            iso(108, 107.9038916, 0.2646),
+            iso(110, 109.9051722, 0.1172),
+        ],
+        valences: &[],
+        electronegativity: Some(2.20),
+        covalent_radius: 1.39,
+        vdw_radius: Some(1.63),
+    },
+    Element {
+        atomic_number: 47,
+        symbol: "Ag",
+        name: "Silver",
+        average_mass: 107.8682,
+        monoisotopic_mass: 106.9050916,
+        isotopes: &[
+            iso(107, 106.9050916, 0.51839),
+            iso(109, 108.9047553, 0.48161),
+        ],
+        valences: &[],
+        electronegativity: Some(1.93),
+        covalent_radius: 1.45,
+        vdw_radius: Some(1.72),
+    },
+    Element {
+        atomic_number: 48,
+        symbol: "Cd",
+        name: "Cadmium",
+        average_mass: 112.414,
+        monoisotopic_mass: 113.90336509,
+        isotopes: &[
+            iso(106, 105.9064599, 0.0125),
+            iso(108, 107.9041834, 0.0089),
+            iso(110, 109.90300661, 0.1249),
+            iso(111, 110.90418287, 0.1280),
+            iso(112, 111.90276287, 0.2413),
+            iso(113, 112.90440813, 0.1222),
+            iso(114, 113.90336509, 0.2873),
+            iso(116, 115.90476315, 0.0749),
+        ],
+        valences: &[],
+        electronegativity: Some(1.69),
+        covalent_radius: 1.44,
+        vdw_radius: Some(1.58),
+    },
+    Element {
+        atomic_number: 49,
+        symbol: "In",
+        name: "Indium",
+        average_mass: 114.818,
+        monoisotopic_mass: 114.903878776,
+        isotopes: &[
+            iso(113, 112.90406184, 0.0429),
+            iso(115, 114.903878776, 0.9571),
+        ],
+        valences: &[3],
+        electronegativity: Some(1.78),
+        covalent_radius: 1.42,
+        vdw_radius: Some(1.93),
+    },
+    Element {
+        atomic_number: 50,
+        symbol: "Sn",
+        name: "Tin",
+        average_mass: 118.710,
+        monoisotopic_mass: 119.90220163,
+        isotopes: &[
+            iso(112, 111.90482387, 0.0097),
+            iso(114, 113.9027827, 0.0066),
+            iso(115, 114.903344699, 0.0034),
+            iso(116, 115.90174280, 0.1454),
+            iso(117, 116.90295398, 0.0768),
+            iso(118, 117.90160657, 0.2422),
+            iso(119, 118.90331117, 0.0859),
+            iso(120, 119.90220163, 0.3258),
+            iso(122, 121.9034438, 0.0463),
+            iso(124, 123.9052766, 0.0579),
+        ],
+        valences: &[2, 4],
+        electronegativity: Some(1.96),
+        covalent_radius: 1.39,
+        vdw_radius: Some(2.17),
+    },
+    Element {
+        atomic_number: 51,
+        symbol: "Sb",
+        name: "Antimony",
+        average_mass: 121.760,
+        monoisotopic_mass: 120.9038120,
+        isotopes: &[iso(121, 120.9038120, 0.5721), iso(123, 122.9042132, 0.4279)],
+        valences: &[3, 5],
+        electronegativity: Some(2.05),
+        covalent_radius: 1.39,
+        vdw_radius: Some(2.06),
+    },
+    Element {
+        atomic_number: 52,
+        symbol: "Te",
+        name: "Tellurium",
+        average_mass: 127.60,
+        monoisotopic_mass: 129.906222748,
+        isotopes: &[
+            iso(120, 119.9040593, 0.0009),
+            iso(122, 121.9030435, 0.0255),
+            iso(123, 122.9042698, 0.0089),
+            iso(124, 123.9028171, 0.0474),
+            iso(125, 124.9044299, 0.0707),
+            iso(126, 125.9033109, 0.1884),
+            iso(128, 127.90446128, 0.3174),
+            iso(130, 129.906222748, 0.3408),
+        ],
+        valences: &[2, 4, 6],
+        electronegativity: Some(2.1),
+        covalent_radius: 1.38,
+        vdw_radius: Some(2.06),
+    },
+    Element {
+        atomic_number: 53,
+        symbol: "I",
+        name: "Iodine",
+        average_mass: 126.90447,
+        monoisotopic_mass: 126.9044719,
+        isotopes: &[iso(127, 126.9044719, 1.0)],
+        valences: &[1, 3, 5],
+        electronegativity: Some(2.66),
+        covalent_radius: 1.39,
+        vdw_radius: Some(1.98),
+    },
+    Element {
+        atomic_number: 54,
+        symbol: "Xe",
+        name: "Xenon",
+        average_mass: 131.293,
+        monoisotopic_mass: 131.9041550856,
+        isotopes: &[
+            iso(124, 123.9058920, 0.000952),
+            iso(126, 125.9042983, 0.000890),
+            iso(128, 127.9035310, 0.019102),
+            iso(129, 128.9047808611, 0.264006),
+            iso(130, 129.903509349, 0.040710),
+            iso(131, 130.90508406, 0.212324),
+            iso(132, 131.9041550856, 0.269086),
+            iso(134, 133.90539466, 0.104357),
+            iso(136, 135.907214484, 0.088573),
+        ],
+        valences: &[0],
+        electronegativity: Some(2.6),
+        covalent_radius: 1.40,
+        vdw_radius: Some(2.16),
+    },
+    Element {
+        atomic_number: 55,
+        symbol: "Cs",
+        name: "Caesium",
+        average_mass: 132.90545196,
+        monoisotopic_mass: 132.9054519610,
+        isotopes: &[iso(133, 132.9054519610, 1.0)],
+        valences: &[1],
+        electronegativity: Some(0.79),
+        covalent_radius: 2.44,
+        vdw_radius: Some(3.43),
+    },
+    Element {
+        atomic_number: 56,
+        symbol: "Ba",
+        name: "Barium",
+        average_mass: 137.327,
+        monoisotopic_mass: 137.90524700,
+        isotopes: &[
+            iso(130, 129.9063207, 0.00106),
+            iso(132, 131.9050611, 0.00101),
+            iso(134, 133.90450818, 0.02417),
+            iso(135, 134.90568838, 0.06592),
+            iso(136, 135.90457573, 0.07854),
+            iso(137, 136.90582714, 0.11232),
+            iso(138, 137.90524700, 0.71698),
+        ],
+        valences: &[2],
+        electronegativity: Some(0.89),
+        covalent_radius: 2.15,
+        vdw_radius: Some(2.68),
+    },
+    Element {
+        atomic_number: 57,
+        symbol: "La",
+        name: "Lanthanum",
+        average_mass: 138.90547,
+        monoisotopic_mass: 138.9063563,
+        isotopes: &[
+            iso(138, 137.9071149, 0.0008881),
+            iso(139, 138.9063563, 0.9991119),
+        ],
+        valences: &[],
+        electronegativity: Some(1.10),
+        covalent_radius: 2.07,
+        vdw_radius: None,
+    },
+    Element {
+        atomic_number: 58,
+        symbol: "Ce",
+        name: "Cerium",
+        average_mass: 140.116,
+        monoisotopic_mass: 139.9054431,
+        isotopes: &[
+            iso(136, 135.90712921, 0.00185),
+            iso(138, 137.905991, 0.00251),
+            iso(140, 139.9054431, 0.88450),
+            iso(142, 141.9092504, 0.11114),
+        ],
+        valences: &[],
+        electronegativity: Some(1.12),
+        covalent_radius: 2.04,
+        vdw_radius: None,
+    },
+    Element {
+        atomic_number: 59,
+        symbol: "Pr",
+        name: "Praseodymium",
+        average_mass: 140.90766,
+        monoisotopic_mass: 140.9076576,
+        isotopes: &[iso(141, 140.9076576, 1.0)],
+        valences: &[],
+        electronegativity: Some(1.13),
+        covalent_radius: 2.03,
+        vdw_radius: None,
+    },
+    Element {
+        atomic_number: 60,
+        symbol: "Nd",
+        name: "Neodymium",
+        average_mass: 144.242,
+        monoisotopic_mass: 141.907729,
+        isotopes: &[
+            iso(142, 141.907729, 0.27152),
+            iso(143, 142.90982, 0.12174),
+            iso(144, 143.910093, 0.23798),
+            iso(145, 144.9125793, 0.08293),
+            iso(146, 145.9131226, 0.17189),
+            iso(148, 147.9168993, 0.05756),
+            iso(150, 149.9209022, 0.05638),
+        ],
+        valences: &[],
+        electronegativity: Some(1.14),
+        covalent_radius: 2.01,
+        vdw_radius: None,
+    },
+    Element {
+        atomic_number: 61,
+        symbol: "Pm",
+        name: "Promethium",
+        average_mass: 145.0,
+        monoisotopic_mass: 144.9127559,
+        isotopes: &[],
+        valences: &[],
+        electronegativity: Some(1.13),
+        covalent_radius: 1.99,
+        vdw_radius: None,
+    },
+    Element {
+        atomic_number: 62,
+        symbol: "Sm",
+        name: "Samarium",
+        average_mass: 150.36,
+        monoisotopic_mass: 151.9197397,
+        isotopes: &[
+            iso(144, 143.9120065, 0.0307),
+            iso(147, 146.9149044, 0.1499),
+            iso(148, 147.9148292, 0.1124),
+            iso(149, 148.9171921, 0.1382),
+            iso(150, 149.9172829, 0.0738),
+            iso(152, 151.9197397, 0.2675),
+            iso(154, 153.9222169, 0.2275),
+        ],
+        valences: &[],
+        electronegativity: Some(1.17),
+        covalent_radius: 1.98,
+        vdw_radius: None,
+    },
+    Element {
+        atomic_number: 63,
+        symbol: "Eu",
+        name: "Europium",
+        average_mass: 151.964,
+        monoisotopic_mass: 152.921238,
+        isotopes: &[iso(151, 150.9198578, 0.4781), iso(153, 152.921238, 0.5219)],
+        valences: &[],
+        electronegativity: Some(1.2),
+        covalent_radius: 1.98,
+        vdw_radius: None,
+    },
+    Element {
+        atomic_number: 64,
+        symbol: "Gd",
+        name: "Gadolinium",
+        average_mass: 157.25,
+        monoisotopic_mass: 157.9241123,
+        isotopes: &[
+            iso(152, 151.9197995, 0.0020),
+            iso(154, 153.9208741, 0.0218),
+            iso(155, 154.9226305, 0.1480),
+            iso(156, 155.9221312, 0.2047),
+            iso(157, 156.9239686, 0.1565),
+            iso(158, 157.9241123, 0.2484),
+            iso(160, 159.9270624, 0.2186),
+        ],
+        valences: &[],
+        electronegativity: Some(1.20),
+        covalent_radius: 1.96,
+        vdw_radius: None,
+    },
+    Element {
+        atomic_number: 65,
+        symbol: "Tb",
+        name: "Terbium",
+        average_mass: 158.92535,
+        monoisotopic_mass: 158.9253547,
+        isotopes: &[iso(159, 158.9253547, 1.0)],
+        valences: &[],
+        electronegativity: Some(1.1),
+        covalent_radius: 1.94,
+        vdw_radius: None,
+    },
+    Element {
+        atomic_number: 66,
+        symbol: "Dy",
+        name: "Dysprosium",
+        average_mass: 162.500,
+        monoisotopic_mass: 163.9291819,
+        isotopes: &[
+            iso(156, 155.9242847, 0.00056),
+            iso(158, 157.9244159, 0.00095),
+            iso(160, 159.9252046, 0.02329),
+            iso(161, 160.9269405, 0.18889),
+            iso(162, 161.9268056, 0.25475),
+            iso(163, 162.9287383, 0.24896),
+            iso(164, 163.9291819, 0.28260),
+        ],
+        valences: &[],
+        electronegativity: Some(1.22),
+        covalent_radius: 1.92,
+        vdw_radius: None,
+    },
+    Element {
+        atomic_number: 67,
+        symbol: "Ho",
+        name: "Holmium",
+        average_mass: 164.93033,
+        monoisotopic_mass: 164.9303288,
+        isotopes: &[iso(165, 164.9303288, 1.0)],
+        valences: &[],
+        electronegativity: Some(1.23),
+        covalent_radius: 1.92,
+        vdw_radius: None,
+    },
+    Element {
+        atomic_number: 68,
+        symbol: "Er",
+        name: "Erbium",
+        average_mass: 167.259,
+        monoisotopic_mass: 165.9302995,
+        isotopes: &[
+            iso(162, 161.9287884, 0.00139),
+            iso(164, 163.9292088, 0.01601),
+            iso(166, 165.9302995, 0.33503),
+            iso(167, 166.9320546, 0.22869),
+            iso(168, 167.9323767, 0.26978),
+            iso(170, 169.9354702, 0.14910),
+        ],
+        valences: &[],
+        electronegativity: Some(1.24),
+        covalent_radius: 1.89,
+        vdw_radius: None,
+    },
+    Element {
+        atomic_number: 69,
+        symbol: "Tm",
+        name: "Thulium",
+        average_mass: 168.93422,
+        monoisotopic_mass: 168.9342179,
+        isotopes: &[iso(169, 168.9342179, 1.0)],
+        valences: &[],
+        electronegativity: Some(1.25),
+        covalent_radius: 1.90,
+        vdw_radius: None,
+    },
+    Element {
+        atomic_number: 70,
+        symbol: "Yb",
+        name: "Ytterbium",
+        average_mass: 173.045,
+        monoisotopic_mass: 173.9388664,
+        isotopes: &[
+            iso(168, 167.9338896, 0.00123),
+            iso(170, 169.9347664, 0.02982),
+            iso(171, 170.9363302, 0.1409),
+            iso(172, 171.9363859, 0.2168),
+            iso(173, 172.9382151, 0.16103),
+            iso(174, 173.9388664, 0.32026),
+            iso(176, 175.9425764, 0.12996),
+        ],
+        valences: &[],
+        electronegativity: Some(1.1),
+        covalent_radius: 1.87,
+        vdw_radius: None,
+    },
+    Element {
+        atomic_number: 71,
+        symbol: "Lu",
+        name: "Lutetium",
+        average_mass: 174.9668,
+        monoisotopic_mass: 174.9407752,
+        isotopes: &[
+            iso(175, 174.9407752, 0.97401),
+            iso(176, 175.9426897, 0.02599),
+        ],
+        valences: &[],
+        electronegativity: Some(1.27),
+        covalent_radius: 1.87,
+        vdw_radius: None,
+    },
+    Element {
+        atomic_number: 72,
+        symbol: "Hf",
+        name: "Hafnium",
+        average_mass: 178.49,
+        monoisotopic_mass: 179.946557,
+        isotopes: &[
+            iso(174, 173.9400461, 0.0016),
+            iso(176, 175.9414076, 0.0526),
+            iso(177, 176.9432277, 0.1860),
+            iso(178, 177.9437058, 0.2728),
+            iso(179, 178.9458232, 0.1362),
+            iso(180, 179.946557, 0.3508),
+        ],
+        valences: &[],
+        electronegativity: Some(1.3),
+        covalent_radius: 1.75,
+        vdw_radius: None,
+    },
+    Element {
+        atomic_number: 73,
+        symbol: "Ta",
+        name: "Tantalum",
+        average_mass: 180.94788,
+        monoisotopic_mass: 180.9479958,
+        isotopes: &[
+            iso(180, 179.9474648, 0.0001201),
+            iso(181, 180.9479958, 0.9998799),
+        ],
+        valences: &[],
+        electronegativity: Some(1.5),
+        covalent_radius: 1.70,
+        vdw_radius: None,
+    },
+    Element {
+        atomic_number: 74,
+        symbol: "W",
+        name: "Tungsten",
+        average_mass: 183.84,
+        monoisotopic_mass: 183.95093092,
+        isotopes: &[
+            iso(180, 179.9467108, 0.0012),
+            iso(182, 181.94820394, 0.2650),
+            iso(183, 182.95022275, 0.1431),
+            iso(184, 183.95093092, 0.3064),
+            iso(186, 185.9543628, 0.2843),
+        ],
+        valences: &[],
+        electronegativity: Some(2.36),
+        covalent_radius: 1.62,
+        vdw_radius: None,
+    },
+    Element {
+        atomic_number: 75,
+        symbol: "Re",
+        name: "Rhenium",
+        average_mass: 186.207,
+        monoisotopic_mass: 186.9557501,
+        isotopes: &[iso(185, 184.9529545, 0.3740), iso(187, 186.9557501, 0.6260)],
+        valences: &[],
+        electronegativity: Some(1.9),
+        covalent_radius: 1.51,
+        vdw_radius: None,
+    },
+    Element {
+        atomic_number: 76,
+        symbol: "Os",
+        name: "Osmium",
+        average_mass: 190.23,
+        monoisotopic_mass: 191.961477,
+        isotopes: &[
+            iso(184, 183.9524885, 0.0002),
+            iso(186, 185.953835, 0.0159),
+            iso(187, 186.9557474, 0.0196),
+            iso(188, 187.9558352, 0.1324),
+            iso(189, 188.9581442, 0.1615),
+            iso(190, 189.9584437, 0.2626),
+            iso(192, 191.961477, 0.4078),
+        ],
+        valences: &[],
+        electronegativity: Some(2.2),
+        covalent_radius: 1.44,
+        vdw_radius: None,
+    },
+    Element {
+        atomic_number: 77,
+        symbol: "Ir",
+        name: "Iridium",
+        average_mass: 192.217,
+        monoisotopic_mass: 192.9629216,
+        isotopes: &[iso(191, 190.9605893, 0.373), iso(193, 192.9629216, 0.627)],
+        valences: &[],
+        electronegativity: Some(2.20),
+        covalent_radius: 1.41,
+        vdw_radius: None,
+    },
+    Element {
+        atomic_number: 78,
+        symbol: "Pt",
+        name: "Platinum",
+        average_mass: 195.084,
+        monoisotopic_mass: 194.9647917,
+        isotopes: &[
+            iso(190, 189.9599297, 0.00012),
+            iso(192, 191.9610387, 0.00782),
+            iso(194, 193.9626809, 0.3286),
+            iso(195, 194.9647917, 0.3378),
+            iso(196, 195.96495209, 0.2521),
+            iso(198, 197.9678949, 0.07356),
+        ],
+        valences: &[],
+        electronegativity: Some(2.28),
+        covalent_radius: 1.36,
+        vdw_radius: Some(1.75),
+    },
+    Element {
+        atomic_number: 79,
+        symbol: "Au",
+        name: "Gold",
+        average_mass: 196.966569,
+        monoisotopic_mass: 196.96656879,
+        isotopes: &[iso(197, 196.96656879, 1.0)],
+        valences: &[],
+        electronegativity: Some(2.54),
+        covalent_radius: 1.36,
+        vdw_radius: Some(1.66),
+    },
+    Element {
+        atomic_number: 80,
+        symbol: "Hg",
+        name: "Mercury",
+        average_mass: 200.592,
+        monoisotopic_mass: 201.9706434,
+        isotopes: &[
+            iso(196, 195.9658326, 0.0015),
+            iso(198, 197.9667686, 0.0997),
+            iso(199, 198.96828064, 0.1687),
+            iso(200, 199.96832659, 0.2310),
+            iso(201, 200.97030284, 0.1318),
+            iso(202, 201.9706434, 0.2986),
+            iso(204, 203.97349398, 0.0687),
+        ],
+        valences: &[],
+        electronegativity: Some(2.00),
+        covalent_radius: 1.32,
+        vdw_radius: Some(1.55),
+    },
+    Element {
+        atomic_number: 81,
+        symbol: "Tl",
+        name: "Thallium",
+        average_mass: 204.38,
+        monoisotopic_mass: 204.9744278,
+        isotopes: &[iso(203, 202.9723446, 0.2952), iso(205, 204.9744278, 0.7048)],
+        valences: &[1, 3],
+        electronegativity: Some(1.62),
+        covalent_radius: 1.45,
+        vdw_radius: Some(1.96),
+    },
+    Element {
+        atomic_number: 82,
+        symbol: "Pb",
+        name: "Lead",
+        average_mass: 207.2,
+        monoisotopic_mass: 207.9766525,
+        isotopes: &[
+            iso(204, 203.973044, 0.014),
+            iso(206, 205.9744657, 0.241),
+            iso(207, 206.9758973, 0.221),
+            iso(208, 207.9766525, 0.524),
+        ],
+        valences: &[2, 4],
+        electronegativity: Some(2.33),
+        covalent_radius: 1.46,
+        vdw_radius: Some(2.02),
+    },
+    Element {
+        atomic_number: 83,
+        symbol: "Bi",
+        name: "Bismuth",
+        average_mass: 208.98040,
+        monoisotopic_mass: 208.9803991,
+        isotopes: &[iso(209, 208.9803991, 1.0)],
+        valences: &[3, 5],
+        electronegativity: Some(2.02),
+        covalent_radius: 1.48,
+        vdw_radius: Some(2.07),
+    },
+    Element {
+        atomic_number: 84,
+        symbol: "Po",
+        name: "Polonium",
+        average_mass: 209.0,
+        monoisotopic_mass: 208.9824308,
+        isotopes: &[],
+        valences: &[2, 4, 6],
+        electronegativity: Some(2.0),
+        covalent_radius: 1.40,
+        vdw_radius: Some(1.97),
+    },
+    Element {
+        atomic_number: 85,
+        symbol: "At",
+        name: "Astatine",
+        average_mass: 210.0,
+        monoisotopic_mass: 209.9871479,
+        isotopes: &[],
+        valences: &[1],
+        electronegativity: Some(2.2),
+        covalent_radius: 1.50,
+        vdw_radius: Some(2.02),
+    },
+    Element {
+        atomic_number: 86,
+        symbol: "Rn",
+        name: "Radon",
+        average_mass: 222.0,
+        monoisotopic_mass: 222.0175782,
+        isotopes: &[],
+        valences: &[0],
+        electronegativity: Some(2.2),
+        covalent_radius: 1.50,
+        vdw_radius: Some(2.20),
+    },
+    Element {
+        atomic_number: 87,
+        symbol: "Fr",
+        name: "Francium",
+        average_mass: 223.0,
+        monoisotopic_mass: 223.019736,
+        isotopes: &[],
+        valences: &[1],
+        electronegativity: Some(0.7),
+        covalent_radius: 2.60,
+        vdw_radius: Some(3.48),
+    },
+    Element {
+        atomic_number: 88,
+        symbol: "Ra",
+        name: "Radium",
+        average_mass: 226.0,
+        monoisotopic_mass: 226.0254103,
+        isotopes: &[],
+        valences: &[2],
+        electronegativity: Some(0.9),
+        covalent_radius: 2.21,
+        vdw_radius: Some(2.83),
+    },
+    Element {
+        atomic_number: 89,
+        symbol: "Ac",
+        name: "Actinium",
+        average_mass: 227.0,
+        monoisotopic_mass: 227.0277523,
+        isotopes: &[],
+        valences: &[],
+        electronegativity: Some(1.1),
+        covalent_radius: 2.15,
+        vdw_radius: None,
+    },
+    Element {
+        atomic_number: 90,
+        symbol: "Th",
+        name: "Thorium",
+        average_mass: 232.0377,
+        monoisotopic_mass: 232.0380558,
+        isotopes: &[iso(232, 232.0380558, 1.0)],
+        valences: &[],
+        electronegativity: Some(1.3),
+        covalent_radius: 2.06,
+        vdw_radius: None,
+    },
+    Element {
+        atomic_number: 91,
+        symbol: "Pa",
+        name: "Protactinium",
+        average_mass: 231.03588,
+        monoisotopic_mass: 231.0358842,
+        isotopes: &[iso(231, 231.0358842, 1.0)],
+        valences: &[],
+        electronegativity: Some(1.5),
+        covalent_radius: 2.00,
+        vdw_radius: None,
+    },
+    Element {
+        atomic_number: 92,
+        symbol: "U",
+        name: "Uranium",
+        average_mass: 238.02891,
+        monoisotopic_mass: 238.0507884,
+        isotopes: &[
+            iso(234, 234.0409523, 0.000054),
+            iso(235, 235.0439301, 0.007204),
+            iso(238, 238.0507884, 0.992742),
+        ],
+        valences: &[],
+        electronegativity: Some(1.38),
+        covalent_radius: 1.96,
+        vdw_radius: Some(1.86),
+    },
+    Element {
+        atomic_number: 93,
+        symbol: "Np",
+        name: "Neptunium",
+        average_mass: 237.0,
+        monoisotopic_mass: 237.0481736,
+        isotopes: &[],
+        valences: &[],
+        electronegativity: Some(1.36),
+        covalent_radius: 1.90,
+        vdw_radius: None,
+    },
+    Element {
+        atomic_number: 94,
+        symbol: "Pu",
+        name: "Plutonium",
+        average_mass: 244.0,
+        monoisotopic_mass: 244.0642053,
+        isotopes: &[],
+        valences: &[],
+        electronegativity: Some(1.28),
+        covalent_radius: 1.87,
+        vdw_radius: None,
+    },
+    Element {
+        atomic_number: 95,
+        symbol: "Am",
+        name: "Americium",
+        average_mass: 243.0,
+        monoisotopic_mass: 243.0613813,
+        isotopes: &[],
+        valences: &[],
+        electronegativity: Some(1.13),
+        covalent_radius: 1.80,
+        vdw_radius: None,
+    },
+    Element {
+        atomic_number: 96,
+        symbol: "Cm",
+        name: "Curium",
+        average_mass: 247.0,
+        monoisotopic_mass: 247.0703541,
+        isotopes: &[],
+        valences: &[],
+        electronegativity: Some(1.28),
+        covalent_radius: 1.69,
+        vdw_radius: None,
+    },
+    Element {
+        atomic_number: 97,
+        symbol: "Bk",
+        name: "Berkelium",
+        average_mass: 247.0,
+        monoisotopic_mass: 247.0703073,
+        isotopes: &[],
+        valences: &[],
+        electronegativity: Some(1.3),
+        covalent_radius: 1.68,
+        vdw_radius: None,
+    },
+    Element {
+        atomic_number: 98,
+        symbol: "Cf",
+        name: "Californium",
+        average_mass: 251.0,
+        monoisotopic_mass: 251.0795886,
+        isotopes: &[],
+        valences: &[],
+        electronegativity: Some(1.3),
+        covalent_radius: 1.68,
+        vdw_radius: None,
+    },
+    Element {
+        atomic_number: 99,
+        symbol: "Es",
+        name: "Einsteinium",
+        average_mass: 252.0,
+        monoisotopic_mass: 252.08298,
+        isotopes: &[],
+        valences: &[],
+        electronegativity: Some(1.3),
+        covalent_radius: 1.65,
+        vdw_radius: None,
+    },
+    Element {
+        atomic_number: 100,
+        symbol: "Fm",
+        name: "Fermium",
+        average_mass: 257.0,
+        monoisotopic_mass: 257.0951061,
+        isotopes: &[],
+        valences: &[],
+        electronegativity: Some(1.3),
+        covalent_radius: 1.67,
+        vdw_radius: None,
+    },
+    Element {
+        atomic_number: 101,
+        symbol: "Md",
+        name: "Mendelevium",
+        average_mass: 258.0,
+        monoisotopic_mass: 258.0984315,
+        isotopes: &[],
+        valences: &[],
+        electronegativity: Some(1.3),
+        covalent_radius: 1.73,
+        vdw_radius: None,
+    },
+    Element {
+        atomic_number: 102,
+        symbol: "No",
+        name: "Nobelium",
+        average_mass: 259.0,
+        monoisotopic_mass: 259.10103,
+        isotopes: &[],
+        valences: &[],
+        electronegativity: Some(1.3),
+        covalent_radius: 1.76,
+        vdw_radius: None,
+    },
+    Element {
+        atomic_number: 103,
+        symbol: "Lr",
+        name: "Lawrencium",
+        average_mass: 262.0,
+        monoisotopic_mass: 262.10961,
+        isotopes: &[],
+        valences: &[],
+        electronegativity: Some(1.3),
+        covalent_radius: 1.61,
+        vdw_radius: None,
+    },
+    Element {
+        atomic_number: 104,
+        symbol: "Rf",
+        name: "Rutherfordium",
+        average_mass: 267.0,
+        monoisotopic_mass: 267.12179,
+        isotopes: &[],
+        valences: &[],
+        electronegativity: None,
+        covalent_radius: 1.57,
+        vdw_radius: None,
+    },
+    Element {
+        atomic_number: 105,
+        symbol: "Db",
+        name: "Dubnium",
+        average_mass: 268.0,
+        monoisotopic_mass: 268.12567,
+        isotopes: &[],
+        valences: &[],
+        electronegativity: None,
+        covalent_radius: 1.49,
+        vdw_radius: None,
+    },
+    Element {
+        atomic_number: 106,
+        symbol: "Sg",
+        name: "Seaborgium",
+        average_mass: 271.0,
+        monoisotopic_mass: 271.13393,
+        isotopes: &[],
+        valences: &[],
+        electronegativity: None,
+        covalent_radius: 1.43,
+        vdw_radius: None,
+    },
+    Element {
+        atomic_number: 107,
+        symbol: "Bh",
+        name: "Bohrium",
+        average_mass: 272.0,
+        monoisotopic_mass: 272.13826,
+        isotopes: &[],
+        valences: &[],
+        electronegativity: None,
+        covalent_radius: 1.41,
+        vdw_radius: None,
+    },
+    Element {
+        atomic_number: 108,
+        symbol: "Hs",
+        name: "Hassium",
+        average_mass: 270.0,
+        monoisotopic_mass: 270.13429,
+        isotopes: &[],
+        valences: &[],
+        electronegativity: None,
+        covalent_radius: 1.34,
+        vdw_radius: None,
+    },
+    Element {
+        atomic_number: 109,
+        symbol: "Mt",
+        name: "Meitnerium",
+        average_mass: 276.0,
+        monoisotopic_mass: 276.15159,
+        isotopes: &[],
+        valences: &[],
+        electronegativity: None,
+        covalent_radius: 1.29,
+        vdw_radius: None,
+    },
+    Element {
+        atomic_number: 110,
+        symbol: "Ds",
+        name: "Darmstadtium",
+        average_mass: 281.0,
+        monoisotopic_mass: 281.16451,
+        isotopes: &[],
+        valences: &[],
+        electronegativity: None,
+        covalent_radius: 1.28,
+        vdw_radius: None,
+    },
+    Element {
+        atomic_number: 111,
+        symbol: "Rg",
+        name: "Roentgenium",
+        average_mass: 280.0,
+        monoisotopic_mass: 280.16514,
+        isotopes: &[],
+        valences: &[],
+        electronegativity: None,
+        covalent_radius: 1.21,
+        vdw_radius: None,
+    },
+    Element {
+        atomic_number: 112,
+        symbol: "Cn",
+        name: "Copernicium",
+        average_mass: 285.0,
+        monoisotopic_mass: 285.17712,
+        isotopes: &[],
+        valences: &[],
+        electronegativity: None,
+        covalent_radius: 1.22,
+        vdw_radius: None,
+    },
+    Element {
+        atomic_number: 113,
+        symbol: "Nh",
+        name: "Nihonium",
+        average_mass: 284.0,
+        monoisotopic_mass: 284.17873,
+        isotopes: &[],
+        valences: &[],
+        electronegativity: None,
+        covalent_radius: 1.36,
+        vdw_radius: None,
+    },
+    Element {
+        atomic_number: 114,
+        symbol: "Fl",
+        name: "Flerovium",
+        average_mass: 289.0,
+        monoisotopic_mass: 289.19042,
+        isotopes: &[],
+        valences: &[],
+        electronegativity: None,
+        covalent_radius: 1.43,
+        vdw_radius: None,
+    },
+    Element {
+        atomic_number: 115,
+        symbol: "Mc",
+        name: "Moscovium",
+        average_mass: 288.0,
+        monoisotopic_mass: 288.19274,
+        isotopes: &[],
+        valences: &[],
+        electronegativity: None,
+        covalent_radius: 1.62,
+        vdw_radius: None,
+    },
+    Element {
+        atomic_number: 116,
+        symbol: "Lv",
+        name: "Livermorium",
+        average_mass: 293.0,
+        monoisotopic_mass: 293.20449,
+        isotopes: &[],
+        valences: &[],
+        electronegativity: None,
+        covalent_radius: 1.75,
+        vdw_radius: None,
+    },
+    Element {
+        atomic_number: 117,
+        symbol: "Ts",
+        name: "Tennessine",
+        average_mass: 292.0,
+        monoisotopic_mass: 292.20746,
+        isotopes: &[],
+        valences: &[],
+        electronegativity: None,
+        covalent_radius: 1.65,
+        vdw_radius: None,
+    },
+    Element {
+        atomic_number: 118,
+        symbol: "Og",
+        name: "Oganesson",
+        average_mass: 294.0,
+        monoisotopic_mass: 294.21392,
+        isotopes: &[],
+        valences: &[],
+        electronegativity: None,
+        covalent_radius: 1.57,
+        vdw_radius: None,
+    },
+];
 
-    if target == 0 {
-        0
-    } else {
-        (target - valence as i8).max(0) as usize
-    }
-}
+/// Radioisotopes common as tracers and labels in SMILES, by atomic number,
+/// with no natural abundance. Masses from AME2020.
+pub static TRACER_ISOTOPES: [(usize, Isotope); 35] = [
+    (1, iso(3, 3.01604928132, 0.0)),
+    (6, iso(11, 11.0114336, 0.0)),
+    (6, iso(14, 14.0032419884, 0.0)),
+    (7, iso(13, 13.00573861, 0.0)),
+    (8, iso(15, 15.0030656, 0.0)),
+    (9, iso(18, 18.0009373, 0.0)),
+    (11, iso(22, 21.99443742, 0.0)),
+    (15, iso(32, 31.97390764, 0.0)),
+    (15, iso(33, 32.9717257, 0.0)),
+    (16, iso(35, 34.96903231, 0.0)),
+    (17, iso(36, 35.96830682, 0.0)),
+    (20, iso(45, 44.95618635, 0.0)),
+    (24, iso(51, 50.94476502, 0.0)),
+    (26, iso(59, 58.93487434, 0.0)),
+    (27, iso(57, 56.93629057, 0.0)),
+    (27, iso(60, 59.93381554, 0.0)),
+    (29, iso(64, 63.92976368, 0.0)),
+    (31, iso(67, 66.92820252, 0.0)),
+    (31, iso(68, 67.92798008, 0.0)),
+    (35, iso(76, 75.924542, 0.0)),
+    (35, iso(77, 76.9213792, 0.0)),
+    (35, iso(82, 81.9168018, 0.0)),
+    (39, iso(90, 89.9071439, 0.0)),
+    (40, iso(89, 88.9088814, 0.0)),
+    (43, iso(99, 98.9062508, 0.0)),
+    (49, iso(111, 110.9051085, 0.0)),
+    (53, iso(123, 122.9055898, 0.0)),
+    (53, iso(124, 123.9062091, 0.0)),
+    (53, iso(125, 124.9046294, 0.0)),
+    (53, iso(131, 130.9061263, 0.0)),
+    (71, iso(177, 176.9437615, 0.0)),
+    (81, iso(201, 200.970819, 0.0)),
+    (85, iso(211, 210.9874963, 0.0)),
+    (88, iso(223, 223.0185023, 0.0)),
+    (89, iso(225, 225.0232296, 0.0)),
+];
@@ -1,5 +1,19 @@
+pub mod aromaticity;
+pub mod builder;
+pub mod canonical;
+pub mod cip;
 pub mod configuration;
 pub mod defs;
+pub mod edit;
+pub mod fragments;
 pub mod graph_algo;
+pub mod hybridization;
+pub mod isomorphism;
+pub mod kekulize;
 pub mod mendeleev;
 pub mod molecule;
+pub mod properties;
+pub mod reaction;
+pub mod rings;
+pub mod stereo_perception;
+pub mod valence;
@@ -1,11 +1,28 @@
 // Code Stolen from https://smallcultfollowing.com/babysteps/blog/2015/04/06/modeling-graphs-in-rust-using-vector-indices/
-use super::defs::{Atom, Bond};
+use super::{
+    defs::{Atom, Bond, BondIndex},
+    properties::{PropertyMap, NAME},
+    rings::RingInfo,
+};
 
 // Graph Related Functions
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Molecule {
     pub atoms: Vec<Atom>,
     pub bonds: Vec<Bond>,
+    /// Title, SD data items and other named values.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub properties: PropertyMap,
+    // Filled by `perceive_rings`, see `core::rings`
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) ring_info: Option<RingInfo>,
+}
+
+impl Default for Molecule {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Molecule {
@@ -13,87 +30,85 @@ impl Molecule {
         Molecule {
             atoms: Vec::new(),
             bonds: Vec::new(),
+            properties: PropertyMap::new(),
+            ring_info: None,
         }
     }
-    pub fn add_atom(&mut self, atom: Atom) {
-        self.atoms.push(atom)
+    pub fn add_atom(&mut self, atom: Atom) -> usize {
+        self.ring_info = None;
+        self.atoms.push(atom);
+        self.atoms.len() - 1
     }
-    pub fn add_bond(&mut self, bond: Bond) {
-        self.bonds.push(bond)
+    /// Adds the bond and registers it in the `outgoing_bond` list of both atoms.
+    pub fn add_bond(&mut self, bond: Bond) -> BondIndex {
+        let bond_index = self.bonds.len();
+        self.ring_info = None;
+        self.atoms[bond.source].add_to_bond_list(bond_index);
+        self.atoms[bond.dest].add_to_bond_list(bond_index);
+        self.bonds.push(bond);
+        bond_index
     }
-    pub fn h_count_update(&mut self, atom_index: usize, h_count: usize) {
-        self.atoms[atom_index].h_count_update(h_count);
+    /// Rebuilds every `outgoing_bond` list from `bonds`, for molecules
+    /// assembled by pushing onto the vectors directly.
+    pub fn rebuild_bond_lists(&mut self) {
+        self.ring_info = None;
+        for atom in self.atoms.iter_mut() {
+            atom.outgoing_bond.clear();
+        }
+        for (bond_index, bond) in self.bonds.iter().enumerate() {
+            self.atoms[bond.source].add_to_bond_list(bond_index);
+            self.atoms[bond.dest].add_to_bond_list(bond_index);
+        }
+    }
+    /// The `NAME` property, if it is set and a string.
+    pub fn name(&self) -> Option<&str> {
+        self.properties.get(NAME).and_then(|p| p.as_str())
     }
 
-    pub fn get_bond(&self, atom1: usize, atom2: usize) -> Option<&Bond> {
-        self.bonds.iter().find(|&bond| {
-            (bond.source == atom1 && bond.dest == atom2)
-                || (bond.source == atom2 && bond.dest == atom1)
-        })
+    pub fn set_name(&mut self, name: &str) {
+        self.properties.set(NAME, name);
     }
 
-    pub fn atom_to_smiles_str(&self, atom_idx: usize) -> String {
-        let atom = &self.atoms[atom_idx];
+    pub fn h_count_update(&mut self, atom_index: usize, h_count: usize) {
+        self.atoms[atom_index].h_count_update(h_count);
+    }
 
-        let organic = is_organic(atom.element);
-        let aromatic = atom.aromatic;
+    // Neighbour queries, all O(degree) through `outgoing_bond`
 
-        // When do we need brackets?
-        let needs_brackets =
-            atom.isotope != 0 || atom.hydrogens != 0 || atom.f_charge != 0 || atom.ring || !organic;
+    /// Indices of the bonds touching `atom_idx`.
+    pub fn bonds_of(&self, atom_idx: usize) -> &[BondIndex] {
+        &self.atoms[atom_idx].outgoing_bond
+    }
 
-        if needs_brackets {
-            let mut s = String::from("[");
-            if atom.isotope != 0 {
-                s.push_str(&atom.isotope.to_string());
-            }
+    /// Atoms bonded to `atom_idx`, in `outgoing_bond` order.
+    pub fn neighbors(&self, atom_idx: usize) -> impl Iterator<Item = usize> + '_ {
+        self.bonds_of(atom_idx)
+            .iter()
+            .map(move |&b| self.bonds[b].other(atom_idx))
+    }
+
+    /// Number of explicit bonds, not counting order or implicit hydrogens.
+    pub fn degree(&self, atom_idx: usize) -> usize {
+        self.bonds_of(atom_idx).len()
+    }
 
-            if aromatic && organic {
-                // SMILES aromatic atoms: lowercase
-                let sym = match atom.element {
-                    5 => "b",  // boron
-                    6 => "c",  // carbon
-                    7 => "n",  // nitrogen
-                    8 => "o",  // oxygen
-                    15 => "p", // phosphorus
-                    16 => "s", // sulfur
-                    _ => &atom.element.to_string(),
-                };
-                s.push_str(sym);
-            } else {
-                s.push_str(&atom.element.to_string()); // uppercase
-            }
+    /// Index of the bond joining `atom1` and `atom2`, if there is one.
+    pub fn bond_between(&self, atom1: usize, atom2: usize) -> Option<BondIndex> {
+        self.bonds_of(atom1)
+            .iter()
+            .copied()
+            .find(|&b| self.bonds[b].other(atom1) == atom2)
+    }
 
-            if atom.hydrogens > 0 {
-                s.push_str(&format!("H{}", atom.hydrogens));
-            }
-            if atom.f_charge > 0 {
-                s.push_str(&format!("+{}", atom.f_charge));
-            } else if atom.f_charge < 0 {
-                s.push_str(&format!("{}", atom.f_charge)); // already has minus sign
-            }
+    pub fn get_bond(&self, atom1: usize, atom2: usize) -> Option<&Bond> {
+        self.bond_between(atom1, atom2).map(|b| &self.bonds[b])
+    }
 
-            s.push(']');
-            return s;
-        } else {
-            // bare organic atom
-            if organic && aromatic {
-                match atom.element {
-                    5 => return "b".to_string(),
-                    6 => return "c".to_string(),
-                    7 => return "n".to_string(),
-                    8 => return "o".to_string(),
-                    15 => return "p".to_string(),
-                    16 => return "s".to_string(),
-                    _ => {}
-                }
-            }
-            atom.element.to_string() // uppercase
+    /// The first atom carrying atom-map number `atom_map`.
+    pub fn atom_with_map(&self, atom_map: usize) -> Option<usize> {
+        if atom_map == 0 {
+            return None;
         }
+        self.atoms.iter().position(|a| a.atom_map == atom_map)
     }
 }
-
-// organic subset atomic numbers: 5,6,7,8,15,16,9,17,35,53
-const fn is_organic(z: usize) -> bool {
-    matches!(z, 5 | 6 | 7 | 8 | 15 | 16 | 9 | 17 | 35 | 53)
-}
@@ -0,0 +1,181 @@
+// Named values attached to molecules, atoms, bonds and reactions: SD data
+// items, titles, record names and anything callers want to carry along.
+use std::fmt;
+
+/// Property holding the title of an SD record or the name column of a
+/// SMILES record.
+pub const NAME: &str = "_Name";
+
+/// Atom property holding the enhanced stereo group of a stereocentre, as in
+/// V3000 molfiles: "abs", or "and1", "or1" and so on for racemic and
+/// relative groups.
+pub const STEREO_GROUP: &str = "_StereoGroup";
+
+/// Molecule property holding the SGroups (superatoms, polymer units, data
+/// groups...) of a V3000 molfile: the lines of its SGroup block, with atoms
+/// and bonds numbered from 1 in index order as they are written back. The
+/// numbers are not updated when the molecule is edited.
+pub const SGROUPS: &str = "_SGroups";
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Property {
+    String(String),
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+}
+
+impl Property {
+    /// The typed value `text` reads as, when it prints back unchanged
+    /// ("42", "-1.5", "true"); a string otherwise, so "007" or "1.50" keep
+    /// their spelling.
+    pub fn infer(text: &str) -> Property {
+        if let Ok(value) = text.parse::<i64>() {
+            if value.to_string() == text {
+                return Property::Int(value);
+            }
+        }
+        if let Ok(value) = text.parse::<f64>() {
+            if value.is_finite() && value.to_string() == text {
+                return Property::Float(value);
+            }
+        }
+        match text {
+            "true" => Property::Bool(true),
+            "false" => Property::Bool(false),
+            _ => Property::String(text.to_string()),
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Property::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_int(&self) -> Option<i64> {
+        match self {
+            Property::Int(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Floats, and integers converted to one.
+    pub fn as_float(&self) -> Option<f64> {
+        match self {
+            Property::Float(value) => Some(*value),
+            Property::Int(value) => Some(*value as f64),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Property::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Property {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Property::String(value) => write!(f, "{}", value),
+            Property::Int(value) => write!(f, "{}", value),
+            Property::Float(value) => write!(f, "{}", value),
+            Property::Bool(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+impl From<&str> for Property {
+    fn from(value: &str) -> Self {
+        Property::String(value.to_string())
+    }
+}
+
+impl From<String> for Property {
+    fn from(value: String) -> Self {
+        Property::String(value)
+    }
+}
+
+impl From<i64> for Property {
+    fn from(value: i64) -> Self {
+        Property::Int(value)
+    }
+}
+
+impl From<i32> for Property {
+    fn from(value: i32) -> Self {
+        Property::Int(value.into())
+    }
+}
+
+impl From<f64> for Property {
+    fn from(value: f64) -> Self {
+        Property::Float(value)
+    }
+}
+
+impl From<bool> for Property {
+    fn from(value: bool) -> Self {
+        Property::Bool(value)
+    }
+}
+
+/// Properties by name, kept in insertion order so files are written back
+/// the way they were read.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct PropertyMap {
+    entries: Vec<(String, Property)>,
+}
+
+impl PropertyMap {
+    pub fn new() -> PropertyMap {
+        PropertyMap::default()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Property> {
+        self.entries
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value)
+    }
+
+    /// Sets the property, replacing an existing value in place.
+    pub fn set(&mut self, name: &str, value: impl Into<Property>) {
+        let value = value.into();
+        match self.entries.iter_mut().find(|(key, _)| key == name) {
+            Some(entry) => entry.1 = value,
+            None => self.entries.push((name.to_string(), value)),
+        }
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<Property> {
+        let position = self.entries.iter().position(|(key, _)| key == name)?;
+        Some(self.entries.remove(position).1)
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Property)> {
+        self.entries
+            .iter()
+            .map(|(key, value)| (key.as_str(), value))
+    }
+}
@@ -0,0 +1,28 @@
+// Reactions as in reaction SMILES `reactants>agents>products`. Atom-map
+// numbers on the atoms (`Atom.atom_map`) pair reactant and product atoms.
+use super::{molecule::Molecule, properties::PropertyMap};
+
+#[derive(Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Reaction {
+    pub reactants: Vec<Molecule>,
+    pub agents: Vec<Molecule>,
+    pub products: Vec<Molecule>,
+    /// The name column of a reaction SMILES record and other named values.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub properties: PropertyMap,
+}
+
+impl Reaction {
+    pub fn new() -> Reaction {
+        Reaction::default()
+    }
+
+    /// Reactants, agents and products, in that order.
+    pub fn molecules(&self) -> impl Iterator<Item = &Molecule> {
+        self.reactants
+            .iter()
+            .chain(&self.agents)
+            .chain(&self.products)
+    }
+}
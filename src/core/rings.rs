@@ -0,0 +1,529 @@
+// Ring perception after Vismara, "Union of all the minimum cycle bases of a
+// graph" (1997). Candidate cycles ("prototypes") are grown from shortest paths,
+// then Gaussian elimination over GF(2) picks the SSSR and the relevant cycles.
+use std::collections::{HashSet, VecDeque};
+
+use super::{defs::BondIndex, molecule::Molecule};
+
+/// Ring membership of a molecule.
+///
+/// Rings are stored as atom indices in ring order. The SSSR is one minimum
+/// cycle basis, which is not unique for systems like cubane; the relevant
+/// cycles are the union of all of them and do not depend on that choice.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RingInfo {
+    sssr: Vec<Vec<usize>>,
+    sssr_bonds: Vec<Vec<BondIndex>>,
+    relevant: Vec<Vec<usize>>,
+    // Indices into `sssr` for each atom and bond
+    atom_rings: Vec<Vec<usize>>,
+    bond_rings: Vec<Vec<usize>>,
+    // Sizes of the relevant cycles through each atom and bond
+    atom_cycle_sizes: Vec<Vec<usize>>,
+    bond_cycle_sizes: Vec<Vec<usize>>,
+    ring_bond_count: Vec<usize>,
+}
+
+impl RingInfo {
+    pub fn new(molecule: &Molecule) -> RingInfo {
+        let n_atoms = molecule.atoms.len();
+        let n_bonds = molecule.bonds.len();
+        let in_ring = ring_bonds(molecule);
+
+        // Ring bonds get a compact index so cycles can be bitsets over them
+        let mut edge_index = vec![usize::MAX; n_bonds];
+        let mut n_edges = 0;
+        for (bond_idx, &ring) in in_ring.iter().enumerate() {
+            if ring {
+                edge_index[bond_idx] = n_edges;
+                n_edges += 1;
+            }
+        }
+        let adjacency: Vec<Vec<usize>> = (0..n_atoms)
+            .map(|a| {
+                molecule
+                    .bonds_of(a)
+                    .iter()
+                    .filter(|&&b| in_ring[b])
+                    .map(|&b| molecule.bonds[b].other(a))
+                    .collect()
+            })
+            .collect();
+
+        let mut trees = Vec::new();
+        let mut families = Vec::new();
+        for root in 0..n_atoms {
+            if !adjacency[root].is_empty() {
+                let tree = PathTree::new(&adjacency, root);
+                families.extend(tree.prototypes(&adjacency, trees.len()));
+                trees.push(tree);
+            }
+        }
+        families.sort_by_key(|f| f.length);
+
+        let as_edges = |cycle: &[usize]| -> Vec<u64> {
+            let mut bits = vec![0u64; n_edges.div_ceil(64)];
+            for i in 0..cycle.len() {
+                let (a, b) = (cycle[i], cycle[(i + 1) % cycle.len()]);
+                let e = edge_index[molecule.bond_between(a, b).unwrap()];
+                bits[e / 64] ^= 1 << (e % 64);
+            }
+            bits
+        };
+
+        // Relevant: independent of all strictly shorter cycles.
+        // SSSR: independent of everything picked so far.
+        let mut shorter = Gf2Basis::default();
+        let mut picked = Gf2Basis::default();
+        let mut sssr = Vec::new();
+        let mut relevant = Vec::new();
+        let mut start = 0;
+        while start < families.len() {
+            let length = families[start].length;
+            let end = start
+                + families[start..]
+                    .iter()
+                    .take_while(|f| f.length == length)
+                    .count();
+
+            let mut this_length = Vec::new();
+            for family in &families[start..end] {
+                let edges = as_edges(&family.prototype);
+                if !shorter.is_independent(&edges) {
+                    continue;
+                }
+                if picked.insert(edges.clone()) {
+                    sssr.push(family.prototype.clone());
+                }
+                relevant.extend(family.members(&trees[family.tree]));
+                this_length.push(edges);
+            }
+            for edges in this_length {
+                shorter.insert(edges);
+            }
+            start = end;
+        }
+
+        // Families are disjoint in theory, dedupe anyway on the bond sets
+        let mut seen = HashSet::new();
+        relevant.retain(|cycle: &Vec<usize>| seen.insert(as_edges(cycle)));
+
+        let cycle_bonds = |cycle: &[usize]| -> Vec<BondIndex> {
+            (0..cycle.len())
+                .map(|i| {
+                    molecule
+                        .bond_between(cycle[i], cycle[(i + 1) % cycle.len()])
+                        .unwrap()
+                })
+                .collect()
+        };
+
+        let mut info = RingInfo {
+            sssr_bonds: sssr.iter().map(|r| cycle_bonds(r)).collect(),
+            sssr,
+            relevant,
+            atom_rings: vec![Vec::new(); n_atoms],
+            bond_rings: vec![Vec::new(); n_bonds],
+            atom_cycle_sizes: vec![Vec::new(); n_atoms],
+            bond_cycle_sizes: vec![Vec::new(); n_bonds],
+            ring_bond_count: vec![0; n_atoms],
+        };
+        for (ring_idx, ring) in info.sssr.iter().enumerate() {
+            for &atom in ring {
+                info.atom_rings[atom].push(ring_idx);
+            }
+            for &bond in &info.sssr_bonds[ring_idx] {
+                info.bond_rings[bond].push(ring_idx);
+            }
+        }
+        for cycle in &info.relevant {
+            for &atom in cycle {
+                info.atom_cycle_sizes[atom].push(cycle.len());
+            }
+            for bond in cycle_bonds(cycle) {
+                info.bond_cycle_sizes[bond].push(cycle.len());
+            }
+        }
+        for (atom, count) in info.ring_bond_count.iter_mut().enumerate() {
+            *count = molecule
+                .bonds_of(atom)
+                .iter()
+                .filter(|&&b| in_ring[b])
+                .count();
+        }
+        info
+    }
+
+    /// Smallest set of smallest rings, atoms in ring order.
+    pub fn sssr(&self) -> &[Vec<usize>] {
+        &self.sssr
+    }
+
+    /// Bonds of each SSSR ring, in the same order as `sssr`.
+    pub fn sssr_bonds(&self) -> &[Vec<BondIndex>] {
+        &self.sssr_bonds
+    }
+
+    /// Union of all minimum cycle bases, atoms in ring order.
+    pub fn relevant_cycles(&self) -> &[Vec<usize>] {
+        &self.relevant
+    }
+
+    pub fn num_rings(&self) -> usize {
+        self.sssr.len()
+    }
+
+    /// Number of SSSR rings the atom belongs to (SMARTS `R<n>`).
+    pub fn atom_ring_count(&self, atom_idx: usize) -> usize {
+        self.atom_rings[atom_idx].len()
+    }
+
+    /// Number of SSSR rings the bond belongs to.
+    pub fn bond_ring_count(&self, bond_idx: BondIndex) -> usize {
+        self.bond_rings[bond_idx].len()
+    }
+
+    /// Indices into `sssr` of the rings containing the atom.
+    pub fn atom_rings(&self, atom_idx: usize) -> &[usize] {
+        &self.atom_rings[atom_idx]
+    }
+
+    /// Indices into `sssr` of the rings containing the bond.
+    pub fn bond_rings(&self, bond_idx: BondIndex) -> &[usize] {
+        &self.bond_rings[bond_idx]
+    }
+
+    pub fn is_ring_atom(&self, atom_idx: usize) -> bool {
+        self.ring_bond_count[atom_idx] > 0
+    }
+
+    pub fn is_ring_bond(&self, bond_idx: BondIndex) -> bool {
+        !self.bond_rings[bond_idx].is_empty()
+    }
+
+    /// Number of ring bonds on the atom (SMARTS `x<n>`).
+    pub fn ring_connectivity(&self, atom_idx: usize) -> usize {
+        self.ring_bond_count[atom_idx]
+    }
+
+    /// Size of the smallest ring through the atom, if it is in one.
+    pub fn smallest_ring_size(&self, atom_idx: usize) -> Option<usize> {
+        self.atom_cycle_sizes[atom_idx].iter().copied().min()
+    }
+
+    /// Size of the smallest ring through the bond, if it is in one.
+    pub fn smallest_bond_ring_size(&self, bond_idx: BondIndex) -> Option<usize> {
+        self.bond_cycle_sizes[bond_idx].iter().copied().min()
+    }
+
+    /// Whether any relevant cycle of this size passes through the atom.
+    /// Unlike the SSSR this does not depend on which basis was picked.
+    pub fn is_atom_in_ring_of_size(&self, atom_idx: usize, size: usize) -> bool {
+        self.atom_cycle_sizes[atom_idx].contains(&size)
+    }
+
+    pub fn is_bond_in_ring_of_size(&self, bond_idx: BondIndex, size: usize) -> bool {
+        self.bond_cycle_sizes[bond_idx].contains(&size)
+    }
+
+    /// Fused ring systems: groups of SSSR ring indices connected by shared
+    /// bonds. Spiro-linked rings, which only share an atom, stay separate.
+    pub fn ring_systems(&self) -> Vec<Vec<usize>> {
+        let mut system_of = vec![usize::MAX; self.sssr.len()];
+        let mut systems = Vec::new();
+        for start in 0..self.sssr.len() {
+            if system_of[start] != usize::MAX {
+                continue;
+            }
+            let mut system = Vec::new();
+            let mut stack = vec![start];
+            system_of[start] = systems.len();
+            while let Some(ring) = stack.pop() {
+                system.push(ring);
+                for &bond in &self.sssr_bonds[ring] {
+                    for &other in &self.bond_rings[bond] {
+                        if system_of[other] == usize::MAX {
+                            system_of[other] = systems.len();
+                            stack.push(other);
+                        }
+                    }
+                }
+            }
+            system.sort_unstable();
+            systems.push(system);
+        }
+        systems
+    }
+}
+
+impl Molecule {
+    /// Computes ring membership, caches it and updates the `ring` flags on
+    /// atoms and bonds.
+    pub fn perceive_rings(&mut self) {
+        let info = RingInfo::new(self);
+        for (atom_idx, atom) in self.atoms.iter_mut().enumerate() {
+            atom.ring = info.is_ring_atom(atom_idx);
+        }
+        for (bond_idx, bond) in self.bonds.iter_mut().enumerate() {
+            bond.ring = info.is_ring_bond(bond_idx);
+        }
+        self.ring_info = Some(info);
+    }
+
+    /// Ring membership from the last call to `perceive_rings`, `None` once
+    /// atoms or bonds have been added since.
+    pub fn ring_info(&self) -> Option<&RingInfo> {
+        // Atoms and bonds pushed directly onto the vectors bypass `add_atom`
+        // and `add_bond`, so check the cache still covers the graph
+        self.ring_info.as_ref().filter(|info| {
+            info.atom_rings.len() == self.atoms.len() && info.bond_rings.len() == self.bonds.len()
+        })
+    }
+}
+
+// Bonds that are not bridges, found with an iterative Tarjan DFS
+fn ring_bonds(molecule: &Molecule) -> Vec<bool> {
+    let n = molecule.atoms.len();
+    let mut in_ring = vec![true; molecule.bonds.len()];
+    let mut order = vec![usize::MAX; n];
+    let mut low = vec![0; n];
+    let mut counter = 0;
+
+    for start in 0..n {
+        if order[start] != usize::MAX {
+            continue;
+        }
+        // (atom, bond used to reach it, next outgoing bond position)
+        let mut stack: Vec<(usize, Option<BondIndex>, usize)> = vec![(start, None, 0)];
+        order[start] = counter;
+        low[start] = counter;
+        counter += 1;
+
+        while let Some(&mut (atom, parent_bond, ref mut next)) = stack.last_mut() {
+            if let Some(&bond) = molecule.bonds_of(atom).get(*next) {
+                *next += 1;
+                if Some(bond) == parent_bond {
+                    continue;
+                }
+                let other = molecule.bonds[bond].other(atom);
+                if order[other] == usize::MAX {
+                    order[other] = counter;
+                    low[other] = counter;
+                    counter += 1;
+                    stack.push((other, Some(bond), 0));
+                } else {
+                    low[atom] = low[atom].min(order[other]);
+                }
+            } else {
+                stack.pop();
+                if let (Some(bond), Some(&(parent, _, _))) = (parent_bond, stack.last()) {
+                    low[parent] = low[parent].min(low[atom]);
+                    if low[atom] > order[parent] {
+                        in_ring[bond] = false;
+                    }
+                }
+            }
+        }
+    }
+    in_ring
+}
+
+// Shortest path DAG from a root, restricted to atoms ranked below it
+struct PathTree {
+    root: usize,
+    dist: Vec<usize>,
+    preds: Vec<Vec<usize>>,
+}
+
+impl PathTree {
+    fn new(adjacency: &[Vec<usize>], root: usize) -> PathTree {
+        // Distances in the whole ring graph...
+        let full = bfs(adjacency, root, |_| true);
+        // ...and using only atoms ranked below the root. Atoms where the two
+        // agree can be reached by a shortest path that the root is the top of.
+        let dist = bfs(adjacency, root, |a| a < root);
+
+        let in_tree = |a: usize| dist[a] != usize::MAX && dist[a] == full[a];
+        let preds = (0..adjacency.len())
+            .map(|atom| {
+                if atom == root || !in_tree(atom) {
+                    return Vec::new();
+                }
+                adjacency[atom]
+                    .iter()
+                    .copied()
+                    .filter(|&p| in_tree(p) && dist[p] + 1 == dist[atom])
+                    .collect()
+            })
+            .collect();
+        PathTree { root, dist, preds }
+    }
+
+    fn contains(&self, atom: usize) -> bool {
+        atom == self.root || !self.preds[atom].is_empty()
+    }
+
+    // One shortest path from the root to `atom`, root first
+    fn path(&self, atom: usize) -> Vec<usize> {
+        let mut path = vec![atom];
+        let mut current = atom;
+        while current != self.root {
+            current = self.preds[current][0];
+            path.push(current);
+        }
+        path.reverse();
+        path
+    }
+
+    // Every shortest path from the root to `atom`, root first
+    fn all_paths(&self, atom: usize) -> Vec<Vec<usize>> {
+        if atom == self.root {
+            return vec![vec![atom]];
+        }
+        let mut paths = Vec::new();
+        for &pred in &self.preds[atom] {
+            for mut path in self.all_paths(pred) {
+                path.push(atom);
+                paths.push(path);
+            }
+        }
+        paths
+    }
+
+    // Prototype cycles that have the root as their highest ranked atom
+    fn prototypes(&self, adjacency: &[Vec<usize>], tree: usize) -> Vec<Family> {
+        let mut families = Vec::new();
+        let mut push = |p: usize, q: usize, apex: Option<usize>| {
+            let prototype = join(&self.path(p), apex, &self.path(q));
+            families.push(Family {
+                length: prototype.len(),
+                prototype,
+                tree,
+                p,
+                q,
+                apex,
+            });
+        };
+
+        for (y, neighbors) in adjacency.iter().enumerate() {
+            if y == self.root || !self.contains(y) {
+                continue;
+            }
+            let y_path = self.path(y);
+
+            // Odd cycles: an edge between two atoms at the same distance
+            for &z in neighbors {
+                if z < y
+                    && z != self.root
+                    && self.contains(z)
+                    && self.dist[z] == self.dist[y]
+                    && disjoint(&y_path, &self.path(z))
+                {
+                    push(y, z, None);
+                }
+            }
+
+            // Even cycles: two predecessors of the same atom
+            let preds = &self.preds[y];
+            for i in 0..preds.len() {
+                for j in i + 1..preds.len() {
+                    let (p, q) = (preds[i], preds[j]);
+                    if disjoint(&self.path(p), &self.path(q)) {
+                        push(p, q, Some(y));
+                    }
+                }
+            }
+        }
+        families
+    }
+}
+
+// A prototype cycle standing in for every cycle built from the same
+// endpoints with other choices of shortest paths
+struct Family {
+    length: usize,
+    prototype: Vec<usize>,
+    tree: usize,
+    // Odd cycles close over the edge (p, q), even ones meet at the apex
+    p: usize,
+    q: usize,
+    apex: Option<usize>,
+}
+
+impl Family {
+    fn members(&self, tree: &PathTree) -> Vec<Vec<usize>> {
+        let mut cycles = Vec::new();
+        for left in tree.all_paths(self.p) {
+            for right in tree.all_paths(self.q) {
+                if disjoint(&left, &right) {
+                    cycles.push(join(&left, self.apex, &right));
+                }
+            }
+        }
+        cycles
+    }
+}
+
+fn bfs<F: Fn(usize) -> bool>(adjacency: &[Vec<usize>], root: usize, allowed: F) -> Vec<usize> {
+    let mut dist = vec![usize::MAX; adjacency.len()];
+    dist[root] = 0;
+    let mut queue = VecDeque::from([root]);
+    while let Some(atom) = queue.pop_front() {
+        for &next in &adjacency[atom] {
+            if dist[next] == usize::MAX && allowed(next) {
+                dist[next] = dist[atom] + 1;
+                queue.push_back(next);
+            }
+        }
+    }
+    dist
+}
+
+// Two root-first paths share nothing but the root
+fn disjoint(left: &[usize], right: &[usize]) -> bool {
+    left[1..].iter().all(|a| !right[1..].contains(a))
+}
+
+// Cycle root -> ... -> p (-> apex) -> q -> ... -> back towards the root
+fn join(left: &[usize], apex: Option<usize>, right: &[usize]) -> Vec<usize> {
+    let mut cycle = left.to_vec();
+    cycle.extend(apex);
+    cycle.extend(right[1..].iter().rev());
+    cycle
+}
+
+// Incremental row reduction over GF(2), each row keyed by its lowest set bit
+#[derive(Default)]
+struct Gf2Basis {
+    rows: Vec<(usize, Vec<u64>)>,
+}
+
+impl Gf2Basis {
+    fn reduce(&self, mut vector: Vec<u64>) -> Vec<u64> {
+        for (pivot, row) in &self.rows {
+            if vector[pivot / 64] >> (pivot % 64) & 1 == 1 {
+                for (v, r) in vector.iter_mut().zip(row) {
+                    *v ^= r;
+                }
+            }
+        }
+        vector
+    }
+
+    fn is_independent(&self, vector: &[u64]) -> bool {
+        self.reduce(vector.to_vec()).iter().any(|&w| w != 0)
+    }
+
+    // Adds the vector, returning false if it was already in the span
+    fn insert(&mut self, vector: Vec<u64>) -> bool {
+        let reduced = self.reduce(vector);
+        match reduced.iter().position(|&w| w != 0) {
+            Some(word) => {
+                let pivot = word * 64 + reduced[word].trailing_zeros() as usize;
+                self.rows.push((pivot, reduced));
+                true
+            }
+            None => false,
+        }
+    }
+}
@@ -0,0 +1,356 @@
+// Stereo from atom coordinates: tetrahedral parities and `/` `\` marks for
+// the stereogenic centres and double bonds that have none yet.
+//
+// 3D structures are read straight from the coordinates. In 2D drawings the
+// wedge flags lift the neighbour at the wide end of a bond above (wedge) or
+// below (hash) the page and the centre is then treated as in 3D. Molfiles
+// without coordinates carry tetrahedral stereo as MDL parities instead.
+use super::{
+    configuration::Configuration,
+    defs::{Axialness, BondIndex},
+    molecule::Molecule,
+};
+
+type Vec3 = (f64, f64, f64);
+
+// Stand-in for the implicit hydrogen when ordering neighbours around a stereocentre
+const IMPLICIT_H: usize = usize::MAX;
+
+// Signed volumes (of unit vectors) below this count as flat
+const FLAT_TOLERANCE: f64 = 1e-3;
+
+/// Stereo flag of a bond in a 2D drawing. The narrow end is at `source`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BondStereo {
+    #[default]
+    None,
+    /// The `dest` atom points towards the viewer.
+    Wedge,
+    /// The `dest` atom points away from the viewer.
+    Hash,
+    /// Configuration unknown, also used for crossed double bonds.
+    Either,
+}
+
+impl Molecule {
+    /// Sets the tetrahedral configuration of stereocentres and the bond
+    /// marks of stereogenic double bonds from `Atom.coords_3d`, for those
+    /// still unspecified. Coordinates with every z at 0 are taken as a 2D
+    /// drawing and read together with `bond_stereo`, indexed by bond; it
+    /// may be empty for 3D structures.
+    pub fn perceive_stereo_from_coords(&mut self, bond_stereo: &[BondStereo]) {
+        let Some(coords) = self
+            .atoms
+            .iter()
+            .map(|a| a.coords_3d)
+            .collect::<Option<Vec<Vec3>>>()
+        else {
+            return;
+        };
+        let flat = coords.iter().all(|c| c.2 == 0.0);
+        let stereo_of = |bond_idx: BondIndex| {
+            bond_stereo
+                .get(bond_idx)
+                .copied()
+                .unwrap_or(BondStereo::None)
+        };
+
+        let unspecified = self.cip_labels();
+        for atom_idx in unspecified.unspecified_atoms {
+            let positions: Vec<Vec3> = self
+                .bonds_of(atom_idx)
+                .iter()
+                .map(|&b| {
+                    let neighbor = self.bonds[b].other(atom_idx);
+                    let position = coords[neighbor];
+                    if !flat || self.bonds[b].source != atom_idx {
+                        return position;
+                    }
+                    let lift = distance(position, coords[atom_idx]);
+                    match stereo_of(b) {
+                        BondStereo::Wedge => (position.0, position.1, lift),
+                        BondStereo::Hash => (position.0, position.1, -lift),
+                        _ => position,
+                    }
+                })
+                .collect();
+            if flat && positions.iter().all(|p| p.2 == 0.0) {
+                continue;
+            }
+            if let Some(configuration) =
+                self.tetrahedral_from_positions(atom_idx, coords[atom_idx], positions)
+            {
+                self.atoms[atom_idx].configuration = Some(configuration);
+            }
+        }
+
+        for bond_idx in unspecified.unspecified_bonds {
+            if stereo_of(bond_idx) != BondStereo::Either {
+                self.mark_double_bond(bond_idx, &coords);
+            }
+        }
+    }
+
+    /// Wedges and hashes for drawing the molecule from its flat coordinates:
+    /// for each bond, the stereocentre it is drawn from and its flag, chosen
+    /// so that `perceive_stereo_from_coords` gives every tetrahedral
+    /// configuration back. All None for 3D or missing coordinates.
+    pub fn wedge_bonds(&self) -> Vec<Option<(usize, BondStereo)>> {
+        let mut wedges = vec![None; self.bonds.len()];
+        let Some(coords) = self
+            .atoms
+            .iter()
+            .map(|a| a.coords_3d)
+            .collect::<Option<Vec<Vec3>>>()
+        else {
+            return wedges;
+        };
+        if !coords.iter().all(|c| c.2 == 0.0) {
+            return wedges;
+        }
+
+        for (atom_idx, atom) in self.atoms.iter().enumerate() {
+            let Some(configuration @ (Configuration::TH1 | Configuration::TH2)) =
+                &atom.configuration
+            else {
+                continue;
+            };
+            // Bonds outside rings to atoms that are no stereocentres first
+            let mut candidates: Vec<BondIndex> = self
+                .bonds_of(atom_idx)
+                .iter()
+                .copied()
+                .filter(|&b| {
+                    let bond = &self.bonds[b];
+                    wedges[b].is_none() && bond.bond_order == 1 && !bond.arom
+                })
+                .collect();
+            candidates.sort_by_key(|&b| {
+                let neighbor = self.bonds[b].other(atom_idx);
+                (
+                    self.atoms[neighbor].configuration.is_some(),
+                    self.bonds[b].ring,
+                )
+            });
+
+            for wedge in candidates {
+                let positions: Vec<Vec3> = self
+                    .bonds_of(atom_idx)
+                    .iter()
+                    .map(|&b| {
+                        let position = coords[self.bonds[b].other(atom_idx)];
+                        if b != wedge {
+                            return position;
+                        }
+                        let lift = distance(position, coords[atom_idx]);
+                        (position.0, position.1, lift)
+                    })
+                    .collect();
+                if let Some(drawn) =
+                    self.tetrahedral_from_positions(atom_idx, coords[atom_idx], positions)
+                {
+                    let stereo = if &drawn == configuration {
+                        BondStereo::Wedge
+                    } else {
+                        BondStereo::Hash
+                    };
+                    wedges[wedge] = Some((atom_idx, stereo));
+                    break;
+                }
+            }
+        }
+        wedges
+    }
+
+    /// The MDL parity of a tetrahedral centre: 1 when, with the highest
+    /// numbered neighbour (a hydrogen if there is one) pointing away, the
+    /// others run clockwise in increasing order, 2 when anticlockwise. None
+    /// for centres without a configuration or without four neighbours.
+    pub(crate) fn mdl_parity(&self, atom_idx: usize) -> Option<u8> {
+        let atom = &self.atoms[atom_idx];
+        let clockwise = match atom.configuration {
+            Some(Configuration::TH1) => false,
+            Some(Configuration::TH2) => true,
+            _ => return None,
+        };
+        let mut order: Vec<usize> = self.neighbors(atom_idx).collect();
+        match atom.hydrogens {
+            0 => {}
+            1 => order.insert(order.len().min(1), IMPLICIT_H),
+            _ => return None,
+        }
+        if order.len() != 4 {
+            return None;
+        }
+        let key = |n: usize| (n == IMPLICIT_H || self.atoms[n].element == 1, n);
+        let inversions = (0..4)
+            .flat_map(|i| (i + 1..4).map(move |j| (i, j)))
+            .filter(|&(i, j)| key(order[i]) > key(order[j]))
+            .count();
+        // Clockwise (TH2) in increasing order is parity 1
+        Some(if clockwise == (inversions % 2 == 0) {
+            1
+        } else {
+            2
+        })
+    }
+
+    /// Sets the configuration of a tetrahedral centre from its MDL parity,
+    /// 1 or 2 as given by `mdl_parity`. Any other parity, or a centre
+    /// `mdl_parity` has none for, is left without a configuration.
+    pub(crate) fn set_mdl_parity(&mut self, atom_idx: usize, parity: u8) {
+        self.atoms[atom_idx].configuration = Some(Configuration::TH2);
+        match self.mdl_parity(atom_idx) {
+            Some(written) if written == parity => {}
+            Some(_) if (1..=2).contains(&parity) => {
+                self.atoms[atom_idx].configuration = Some(Configuration::TH1)
+            }
+            _ => self.atoms[atom_idx].configuration = None,
+        }
+    }
+
+    // TH1 or TH2 for neighbours at `positions`, in `outgoing_bond` order
+    fn tetrahedral_from_positions(
+        &self,
+        atom_idx: usize,
+        centre: Vec3,
+        positions: Vec<Vec3>,
+    ) -> Option<Configuration> {
+        let mut vectors: Vec<Vec3> = positions
+            .iter()
+            .map(|&p| normalize(sub(p, centre)))
+            .collect();
+        if vectors.len() == 3 && self.atoms[atom_idx].hydrogens == 1 {
+            // The implicit hydrogen sits opposite the others, in the place
+            // the parity convention gives it
+            let sum = vectors.iter().fold((0.0, 0.0, 0.0), |acc, &v| add(acc, v));
+            vectors.insert(1, scale(normalize(sum), -1.0));
+        }
+        if vectors.len() != 4 {
+            return None;
+        }
+
+        // Looking from the first neighbour, TH1 has the rest anticlockwise
+        let volume = dot(
+            sub(vectors[1], vectors[0]),
+            cross(sub(vectors[2], vectors[0]), sub(vectors[3], vectors[0])),
+        );
+        if volume.abs() < FLAT_TOLERANCE {
+            return None;
+        }
+        Some(if volume < 0.0 {
+            Configuration::TH1
+        } else {
+            Configuration::TH2
+        })
+    }
+
+    // Marks a single bond on each side of the double bond, reusing marks
+    // already placed for a conjugated neighbour
+    fn mark_double_bond(&mut self, bond_idx: BondIndex, coords: &[Vec3]) {
+        let (left, right) = (self.bonds[bond_idx].source, self.bonds[bond_idx].dest);
+        let (Some(left_bond), Some(right_bond)) = (
+            self.reference_bond(left, bond_idx),
+            self.reference_bond(right, bond_idx),
+        ) else {
+            return;
+        };
+
+        // Substituents on the same side of the bond axis are cis
+        let axis = normalize(sub(coords[right], coords[left]));
+        let off_axis = |atom: usize, from: usize| {
+            let v = sub(coords[atom], coords[from]);
+            normalize(sub(v, scale(axis, dot(v, axis))))
+        };
+        let side = dot(
+            off_axis(self.bonds[left_bond].other(left), left),
+            off_axis(self.bonds[right_bond].other(right), right),
+        );
+        if side.abs() < FLAT_TOLERANCE {
+            return;
+        }
+        let cis = side > 0.0;
+
+        let left_up = self.bond_mark(left_bond, left).unwrap_or(true);
+        let right_up = if cis { left_up } else { !left_up };
+        if self
+            .bond_mark(right_bond, right)
+            .is_some_and(|up| up != right_up)
+        {
+            return;
+        }
+        self.set_bond_mark(left_bond, left, left_up);
+        self.set_bond_mark(right_bond, right, right_up);
+    }
+
+    // A single bond off `atom_idx` to carry the mark, marked ones first
+    fn reference_bond(&self, atom_idx: usize, double_bond: BondIndex) -> Option<BondIndex> {
+        let singles: Vec<BondIndex> = self
+            .bonds_of(atom_idx)
+            .iter()
+            .copied()
+            .filter(|&b| b != double_bond && self.bonds[b].bond_order == 1 && !self.bonds[b].arom)
+            .collect();
+        singles
+            .iter()
+            .copied()
+            .find(|&b| self.bonds[b].axialness != Axialness::UNKNOWN)
+            .or(singles.first().copied())
+    }
+
+    // Whether the far atom of a marked bond is "up" as seen from `from`
+    fn bond_mark(&self, bond_idx: BondIndex, from: usize) -> Option<bool> {
+        let bond = &self.bonds[bond_idx];
+        if bond.axialness == Axialness::UNKNOWN {
+            return None;
+        }
+        Some((bond.axialness == Axialness::UP) == (bond.source == from))
+    }
+
+    fn set_bond_mark(&mut self, bond_idx: BondIndex, from: usize, up: bool) {
+        let bond = &mut self.bonds[bond_idx];
+        bond.axialness = if up == (bond.source == from) {
+            Axialness::UP
+        } else {
+            Axialness::DOWN
+        };
+    }
+}
+
+fn sub(a: Vec3, b: Vec3) -> Vec3 {
+    (a.0 - b.0, a.1 - b.1, a.2 - b.2)
+}
+
+fn add(a: Vec3, b: Vec3) -> Vec3 {
+    (a.0 + b.0, a.1 + b.1, a.2 + b.2)
+}
+
+fn scale(a: Vec3, factor: f64) -> Vec3 {
+    (a.0 * factor, a.1 * factor, a.2 * factor)
+}
+
+fn dot(a: Vec3, b: Vec3) -> f64 {
+    a.0 * b.0 + a.1 * b.1 + a.2 * b.2
+}
+
+fn cross(a: Vec3, b: Vec3) -> Vec3 {
+    (
+        a.1 * b.2 - a.2 * b.1,
+        a.2 * b.0 - a.0 * b.2,
+        a.0 * b.1 - a.1 * b.0,
+    )
+}
+
+fn normalize(a: Vec3) -> Vec3 {
+    let length = dot(a, a).sqrt();
+    if length == 0.0 {
+        a
+    } else {
+        scale(a, 1.0 / length)
+    }
+}
+
+fn distance(a: Vec3, b: Vec3) -> f64 {
+    let d = sub(a, b);
+    dot(d, d).sqrt()
+}
@@ -0,0 +1,106 @@
+// Valence model shared by the readers and writers.
+//
+// Hydrogens come in two kinds: implicit ones are the count in
+// `Atom.hydrogens`, explicit ones are hydrogen atoms in the graph.
+// Default valences come from `mendeleev`; charged p-block atoms use the
+// valences of their isoelectronic neighbour, so N+ behaves like C and O- like F.
+use super::{mendeleev::element, molecule::Molecule};
+
+/// Allowed valences of an element carrying `charge`, in increasing order.
+/// Empty when there is no default, for example charged metals.
+pub fn allowed_valences(atomic_number: usize, charge: i8) -> &'static [i8] {
+    if charge == 0 {
+        return element(atomic_number).map_or(&[], |e| e.valences);
+    }
+    let shifted = atomic_number as i32 - charge as i32;
+    match (
+        p_block_period(atomic_number),
+        p_block_period(shifted.max(0) as usize),
+    ) {
+        (Some(a), Some(b)) if a == b => element(shifted as usize).map_or(&[], |e| e.valences),
+        _ => &[],
+    }
+}
+
+/// Hydrogens needed to bring `valence` up to the next allowed valence,
+/// 0 if it is already past all of them.
+pub fn implicit_h_count(atomic_number: usize, charge: i8, valence: i32) -> usize {
+    allowed_valences(atomic_number, charge)
+        .iter()
+        .map(|&v| v as i32)
+        .find(|&v| v >= valence)
+        .map_or(0, |v| (v - valence) as usize)
+}
+
+/// Implicit hydrogens of an aromatic atom before kekulization, with aromatic
+/// bonds counted as single. One more bond is reserved for the pi system and
+/// only the lowest valence is used, so pyridine n and thiophene s get none.
+pub fn aromatic_implicit_h_count(atomic_number: usize, charge: i8, valence: i32) -> usize {
+    allowed_valences(atomic_number, charge)
+        .first()
+        .map_or(0, |&v| (v as i32 - valence - 1).max(0) as usize)
+}
+
+// Period of a group 13 to 18 element
+fn p_block_period(atomic_number: usize) -> Option<usize> {
+    match atomic_number {
+        5..=10 => Some(2),
+        13..=18 => Some(3),
+        31..=36 => Some(4),
+        49..=54 => Some(5),
+        81..=86 => Some(6),
+        _ => None,
+    }
+}
+
+impl Molecule {
+    /// Sum of the orders of the bonds in the graph, hydrogen atoms included.
+    pub fn explicit_valence(&self, atom_idx: usize) -> i32 {
+        self.bonds_of(atom_idx)
+            .iter()
+            .map(|&b| self.bonds[b].bond_order as i32)
+            .sum()
+    }
+
+    /// Explicit valence plus implicit hydrogens (SMARTS `v<n>`).
+    pub fn total_valence(&self, atom_idx: usize) -> i32 {
+        self.explicit_valence(atom_idx) + self.atoms[atom_idx].hydrogens as i32
+    }
+
+    /// Hydrogen atoms bonded to the atom in the graph.
+    pub fn explicit_h_count(&self, atom_idx: usize) -> usize {
+        self.neighbors(atom_idx)
+            .filter(|&n| self.atoms[n].element == 1)
+            .count()
+    }
+
+    /// Implicit hydrogens (SMARTS `h<n>`).
+    pub fn implicit_h_count(&self, atom_idx: usize) -> usize {
+        self.atoms[atom_idx].hydrogens
+    }
+
+    /// Implicit and explicit hydrogens (SMARTS `H<n>`).
+    pub fn total_h_count(&self, atom_idx: usize) -> usize {
+        self.implicit_h_count(atom_idx) + self.explicit_h_count(atom_idx)
+    }
+
+    /// Neighbours in the graph plus implicit hydrogens (SMARTS `X<n>`).
+    pub fn total_connections(&self, atom_idx: usize) -> usize {
+        self.degree(atom_idx) + self.atoms[atom_idx].hydrogens
+    }
+
+    /// Implicit hydrogens the default valences give the atom, from the bond
+    /// orders as they are now.
+    pub fn default_h_count(&self, atom_idx: usize) -> usize {
+        let atom = &self.atoms[atom_idx];
+        implicit_h_count(atom.element, atom.f_charge, self.explicit_valence(atom_idx))
+    }
+
+    /// Sets `Atom.hydrogens` from the default valences for every atom.
+    pub fn assign_implicit_hydrogens(&mut self) {
+        for atom_idx in 0..self.atoms.len() {
+            let h_count = self.default_h_count(atom_idx);
+            self.h_count_update(atom_idx, h_count);
+        }
+    }
+}
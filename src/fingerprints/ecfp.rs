@@ -6,7 +6,7 @@ fn atom_feature_hash(atom: &Atom) -> u64 {
     let atomic_number = atom.element as u64;
     let h_count = atom.hydrogens as u64;
     let f_charge = atom.f_charge as i64;
-    let charge_mag = f_charge.abs() as u64;
+    let charge_mag = f_charge.unsigned_abs();
     let charge_sign = if f_charge < 0 { 1_u64 } else { 0 };
 
     let heavy_atom_connections = {
@@ -43,13 +43,14 @@ pub fn ecfp(molecule: &Molecule, radius: usize) -> HashSet<u64> {
             let center_hash = current_atom_ids[i];
 
             let mut neighbors: Vec<u64> = molecule
-                .bonds
+                .bonds_of(i)
                 .iter()
-                .filter(|b| b.source == i || b.dest == i)
-                .map(|b| {
-                    let j = if b.source == i { b.dest } else { b.source };
+                .map(|&bond_idx| {
+                    let b = &molecule.bonds[bond_idx];
+                    let j = b.other(i);
                     let bond_atom_hash = current_atom_ids[j];
-                    let bond_order = b.bond_order as u64;
+                    // Aromatic bonds hash alike whichever Kekulé form they have
+                    let bond_order = if b.arom { 1 } else { b.bond_order as u64 };
                     // Hash bond order + neighbor atom id + central atom id
                     let s = format!("{}{}{}{}", center_hash, bond_order, bond_atom_hash, b.arom);
                     djb2_hash(&s)
@@ -82,7 +83,7 @@ pub fn ecfp(molecule: &Molecule, radius: usize) -> HashSet<u64> {
 
 pub fn ecfp_bitvec(molecule: &Molecule, radius: usize, n_bits: usize) -> Vec<u8> {
     let ids = ecfp(molecule, radius);
-    let mut vec = vec![0u8; (n_bits + 7) / 8];
+    let mut vec = vec![0u8; n_bits.div_ceil(8)];
 
     for id in ids {
         // Simple hash → bit index
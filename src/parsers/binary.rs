@@ -0,0 +1,228 @@
+// Reader for the encoding written by `Molecule::to_binary`. The input counts
+// as a single line, error columns are byte offsets from 1 and the token is
+// the byte where decoding failed.
+use crate::{
+    core::{
+        configuration::Configuration,
+        defs::{Atom, Axialness, Bond},
+        molecule::Molecule,
+        properties::{Property, PropertyMap},
+    },
+    parsers::error::{Error, ErrorKind},
+    writer::binary::{BINARY_MAGIC, BINARY_VERSION},
+};
+
+// Cursor over the input bytes
+struct ByteReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+// Error for the data starting at byte `position`
+fn invalid(bytes: &[u8], position: usize, reason: &str) -> Error {
+    let token = bytes
+        .get(position)
+        .map_or(String::new(), |byte| format!("{:#04x}", byte));
+    Error::new(
+        ErrorKind::Invalid(reason.to_string()),
+        1,
+        position + 1,
+        token,
+    )
+}
+
+fn truncated(bytes: &[u8]) -> Error {
+    Error::new(ErrorKind::UnexpectedEnd, 1, bytes.len() + 1, "")
+}
+
+impl ByteReader<'_> {
+    fn invalid(&self, position: usize, reason: &str) -> Error {
+        invalid(self.bytes, position, reason)
+    }
+
+    fn byte(&mut self) -> Result<u8, Error> {
+        let byte = *self
+            .bytes
+            .get(self.position)
+            .ok_or_else(|| truncated(self.bytes))?;
+        self.position += 1;
+        Ok(byte)
+    }
+
+    fn varint(&mut self) -> Result<usize, Error> {
+        let start = self.position;
+        let mut value: usize = 0;
+        for shift in (0..usize::BITS).step_by(7) {
+            let byte = self.byte()?;
+            value |= ((byte & 0x7f) as usize)
+                .checked_shl(shift)
+                .ok_or_else(|| self.invalid(start, "varint overflow"))?;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(self.invalid(start, "varint overflow"))
+    }
+
+    // A count or index that must stay below `limit`
+    fn index(&mut self, limit: usize) -> Result<usize, Error> {
+        let start = self.position;
+        let value = self.varint()?;
+        if value >= limit {
+            return Err(self.invalid(start, "count or index out of range"));
+        }
+        Ok(value)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], Error> {
+        let end = self.position + N;
+        let bytes = self
+            .bytes
+            .get(self.position..end)
+            .ok_or_else(|| truncated(self.bytes))?;
+        self.position = end;
+        Ok(bytes.try_into().unwrap())
+    }
+
+    fn f64(&mut self) -> Result<f64, Error> {
+        Ok(f64::from_le_bytes(self.array()?))
+    }
+
+    fn string(&mut self) -> Result<String, Error> {
+        let start = self.position;
+        let length = self.index(self.bytes.len())?;
+        let end = self.position + length;
+        let bytes = self
+            .bytes
+            .get(self.position..end)
+            .ok_or_else(|| truncated(self.bytes))?;
+        self.position = end;
+        String::from_utf8(bytes.to_vec()).map_err(|_| self.invalid(start, "invalid UTF-8"))
+    }
+
+    fn properties(&mut self) -> Result<PropertyMap, Error> {
+        let mut properties = PropertyMap::new();
+        let count = self.index(self.bytes.len())?;
+        for _ in 0..count {
+            let name = self.string()?;
+            let position = self.position;
+            let value = match self.byte()? {
+                0 => Property::String(self.string()?),
+                1 => Property::Int(i64::from_le_bytes(self.array()?)),
+                2 => Property::Float(self.f64()?),
+                3 => Property::Bool(self.byte()? != 0),
+                _ => return Err(self.invalid(position, "unknown property type")),
+            };
+            properties.set(&name, value);
+        }
+        Ok(properties)
+    }
+}
+
+/// Decodes a molecule written by `Molecule::to_binary`.
+pub fn read_binary(bytes: &[u8]) -> Result<Molecule, Error> {
+    let mut reader = ByteReader { bytes, position: 0 };
+    for &expected in BINARY_MAGIC {
+        let position = reader.position;
+        if reader.byte()? != expected {
+            return Err(invalid(bytes, position, "not molrus binary data"));
+        }
+    }
+    let version = reader.byte()?;
+    if version != BINARY_VERSION {
+        let reason = format!("unsupported format version {}", version);
+        return Err(invalid(bytes, BINARY_MAGIC.len(), &reason));
+    }
+
+    let mut molecule = Molecule::new();
+    // Counts are bounded by the input length so corrupt data cannot
+    // trigger huge allocations
+    let atom_count = reader.index(bytes.len())?;
+    // Where each bond list starts, to report indices past the last bond
+    let mut bond_list_positions = Vec::with_capacity(atom_count);
+    for _ in 0..atom_count {
+        let element = reader.varint()?;
+        let isotope = reader.varint()?;
+        let hydrogens = reader.varint()?;
+        let f_charge = reader.byte()? as i8;
+        let flags = reader.byte()?;
+        let symmetry_class = reader.varint()?;
+        let atom_map = reader.varint()?;
+        let configuration = if flags & 4 != 0 {
+            let position = reader.position;
+            let index = reader.byte()? as usize;
+            let configuration = Configuration::ALL
+                .get(index)
+                .ok_or_else(|| invalid(bytes, position, "unknown configuration"))?;
+            Some(configuration.clone())
+        } else {
+            None
+        };
+        let coords_3d = if flags & 8 != 0 {
+            Some((reader.f64()?, reader.f64()?, reader.f64()?))
+        } else {
+            None
+        };
+        bond_list_positions.push(reader.position);
+        let bond_count = reader.index(bytes.len())?;
+        let mut bond_list = Vec::with_capacity(bond_count);
+        for _ in 0..bond_count {
+            bond_list.push(reader.varint()?);
+        }
+        let properties = reader.properties()?;
+        molecule.atoms.push(Atom {
+            outgoing_bond: bond_list,
+            element,
+            isotope,
+            hydrogens,
+            aromatic: flags & 1 != 0,
+            f_charge,
+            configuration,
+            ring: flags & 2 != 0,
+            symmetry_class,
+            coords_3d,
+            atom_map,
+            properties,
+        });
+    }
+
+    let bond_count = reader.index(bytes.len())?;
+    for _ in 0..bond_count {
+        let source = reader.index(atom_count)?;
+        let dest = reader.index(atom_count)?;
+        let bond_order = reader.byte()? as i8;
+        let position = reader.position;
+        let flags = reader.byte()?;
+        let axialness = match flags >> 2 {
+            0 => Axialness::UNKNOWN,
+            1 => Axialness::UP,
+            2 => Axialness::DOWN,
+            _ => return Err(invalid(bytes, position, "unknown bond flags")),
+        };
+        let properties = reader.properties()?;
+        molecule.bonds.push(Bond {
+            source,
+            dest,
+            arom: flags & 1 != 0,
+            ring: flags & 2 != 0,
+            bond_order,
+            axialness,
+            properties,
+        });
+    }
+    molecule.properties = reader.properties()?;
+    for (atom, position) in molecule.atoms.iter().zip(bond_list_positions) {
+        if atom.outgoing_bond.iter().any(|&b| b >= bond_count) {
+            return Err(invalid(bytes, position, "bond index out of range"));
+        }
+    }
+
+    let rings_perceived = reader.byte()? != 0;
+    if reader.position != bytes.len() {
+        return Err(invalid(bytes, reader.position, "trailing data"));
+    }
+    if rings_perceived {
+        molecule.perceive_rings();
+    }
+    Ok(molecule)
+}
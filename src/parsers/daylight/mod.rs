@@ -1,4 +1,5 @@
 pub mod config;
+pub mod reaction;
 pub mod smarts;
 pub mod smarts_defs;
 pub mod smarts_utils;
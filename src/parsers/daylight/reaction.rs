@@ -0,0 +1,68 @@
+use crate::{
+    core::{
+        molecule::Molecule,
+        properties::{PropertyMap, NAME},
+        reaction::Reaction,
+    },
+    parsers::error::{Error, ErrorKind},
+};
+
+use super::smiles::parse_smiles;
+
+/// Reads `reactants>agents>products`, any of the three may be empty. Each
+/// side is split into its dot-separated molecules; atom maps are kept on the
+/// atoms. Text after the first whitespace becomes the reaction's name.
+pub fn parse_reaction_smiles(record: &str) -> Result<Reaction, Error> {
+    let (smiles, name) = record
+        .split_once(char::is_whitespace)
+        .unwrap_or((record, ""));
+    // Sides with the character position they start at, as the scanner counts
+    let mut sides: Vec<(usize, &str)> = Vec::new();
+    let (mut start, mut start_byte) = (0, 0);
+    for (position, (byte, c)) in smiles.char_indices().enumerate() {
+        if c == '>' {
+            if sides.len() == 2 {
+                let kind = ErrorKind::Invalid("more than three reaction roles".to_string());
+                return Err(Error::new(kind, 1, position + 1, ">"));
+            }
+            sides.push((start, &smiles[start_byte..byte]));
+            (start, start_byte) = (position + 1, byte + 1);
+        }
+    }
+    if sides.len() != 2 {
+        let column = smiles.chars().count() + 1;
+        return Err(Error::new(ErrorKind::Expected("'>'"), 1, column, ""));
+    }
+    sides.push((start, &smiles[start_byte..]));
+
+    let mut molecules = sides
+        .into_iter()
+        .map(|(offset, side)| parse_side(side, offset));
+    let mut reaction = Reaction {
+        reactants: molecules.next().unwrap()?,
+        agents: molecules.next().unwrap()?,
+        products: molecules.next().unwrap()?,
+        properties: PropertyMap::new(),
+    };
+    if !name.trim().is_empty() {
+        reaction.properties.set(NAME, name.trim());
+    }
+    Ok(reaction)
+}
+
+// Error positions are moved from the side to the whole reaction
+fn parse_side(side: &str, offset: usize) -> Result<Vec<Molecule>, Error> {
+    if side.is_empty() {
+        return Ok(Vec::new());
+    }
+    let molecule = parse_smiles(side).map_err(|e| e.shifted(offset))?;
+    Ok(molecule
+        .split_fragments()
+        .into_iter()
+        .map(|fragment| {
+            let mut molecule = fragment.molecule;
+            molecule.perceive_symmetry_classes();
+            molecule
+        })
+        .collect())
+}
@@ -2,7 +2,10 @@ use std::collections::{HashMap, VecDeque};
 
 use crate::{
     core::molecule::Molecule,
-    parsers::{error::Error, scanner::Scanner},
+    parsers::{
+        error::{Error, ErrorKind},
+        scanner::{missing_character, Scanner},
+    },
 };
 
 use super::{
@@ -72,7 +75,7 @@ fn parse_h_count_expr(scanner: &mut Scanner) -> Result<Expr, Error> {
             right: None,
         })
     } else {
-        Err(Error::Character(scanner.cursor()))
+        Err(scanner.error(ErrorKind::Expected("'H'")))
     }
 }
 
@@ -131,11 +134,12 @@ fn parse_stereo_bond_expr(scanner: &mut Scanner) -> Result<Expr, Error> {
             left: None,
             right: None,
         }),
-        _ => Err(Error::Character(scanner.cursor() - 1)),
+        _ => Err(scanner.error_at(scanner.cursor() - 1, ErrorKind::UnexpectedCharacter)),
     }
 }
 
-fn parse_bracket_atom_expr(scanner: &mut Scanner) -> Result<Expr, Error> {
+// The expression and the `:n` atom-map number, 0 without one
+fn parse_bracket_atom_expr(scanner: &mut Scanner) -> Result<(Expr, usize), Error> {
     // '[' already consumed
     let mut element: Option<i8> = None;
     let mut mass: Option<i8> = None;
@@ -143,13 +147,15 @@ fn parse_bracket_atom_expr(scanner: &mut Scanner) -> Result<Expr, Error> {
     let mut charge: Option<i8> = None;
     let mut chirality: Option<i8> = None;
     let mut connectivity: Option<i8> = None;
+    let mut counted_exprs: Vec<Expr> = Vec::new();
     let mut aromatic = false;
+    let mut atom_map = 0;
 
     loop {
         match scanner.peek() {
             Some('0'..='9') => {
                 let mut digits = String::new();
-                while scanner.peek().map_or(false, |c| c.is_ascii_digit()) {
+                while scanner.peek().is_some_and(|c| c.is_ascii_digit()) {
                     digits.push(*scanner.pop().unwrap());
                 }
                 mass = Some(digits.parse::<i8>().unwrap_or(0));
@@ -185,21 +191,6 @@ fn parse_bracket_atom_expr(scanner: &mut Scanner) -> Result<Expr, Error> {
                 element = Some(z);
                 aromatic = true;
             }
-            Some(c) if c.is_ascii_uppercase() => {
-                let z = match scanner.pop().unwrap() {
-                    'B' => 5,
-                    'C' => 6,
-                    'N' => 7,
-                    'O' => 8,
-                    'F' => 9,
-                    'P' => 15,
-                    'S' => 16,
-                    'I' => 53,
-                    _ => 0,
-                };
-                element = Some(z);
-                aromatic = false;
-            }
             Some('X') => {
                 scanner.pop();
                 let degree = match scanner.peek() {
@@ -220,12 +211,95 @@ fn parse_bracket_atom_expr(scanner: &mut Scanner) -> Result<Expr, Error> {
                 };
                 connectivity = Some(degree);
             }
+            // v<n> total valence, h<n> implicit hydrogens (bare `h` is at least one)
+            Some('v' | 'h') => {
+                let primitive = *scanner.pop().unwrap();
+                let mut digits = String::new();
+                while scanner.peek().is_some_and(|c| c.is_ascii_digit()) {
+                    digits.push(*scanner.pop().unwrap());
+                }
+                let (expr_type, default) = match primitive {
+                    'v' => (ExprType::AeValence, Some(1)),
+                    _ => (ExprType::AeImplicit, None),
+                };
+                counted_exprs.push(Expr {
+                    expr_type,
+                    val: digits.parse::<i8>().ok().or(default),
+                    left: None,
+                    right: None,
+                });
+            }
+            // ^<n> hybridization, 0 for s up to 5 for sp3d2
+            Some('^') => {
+                scanner.pop();
+                match scanner.peek() {
+                    Some(&digit @ '0'..='5') => {
+                        scanner.pop();
+                        counted_exprs.push(Expr {
+                            expr_type: ExprType::AeHyb,
+                            val: Some(digit as i8 - b'0' as i8),
+                            left: None,
+                            right: None,
+                        });
+                    }
+                    Some(_) => return Err(scanner.error(ErrorKind::Expected("a digit 0 to 5"))),
+                    None => return Err(scanner.error(ErrorKind::UnexpectedEnd)),
+                }
+            }
+            // R<n> ring count, r<n> smallest ring size, x<n> ring bonds;
+            // without a number each just means "in a ring"
+            Some('R' | 'r' | 'x') => {
+                let primitive = *scanner.pop().unwrap();
+                let mut digits = String::new();
+                while scanner.peek().is_some_and(|c| c.is_ascii_digit()) {
+                    digits.push(*scanner.pop().unwrap());
+                }
+                let expr_type = match (primitive, digits.is_empty()) {
+                    (_, true) => ExprType::AeCyclic,
+                    ('R', false) => ExprType::AeRings,
+                    ('r', false) => ExprType::AeSize,
+                    _ => ExprType::AeRingconnect,
+                };
+                counted_exprs.push(Expr {
+                    expr_type,
+                    val: digits.parse::<i8>().ok(),
+                    left: None,
+                    right: None,
+                });
+            }
+            Some(c) if c.is_ascii_uppercase() => {
+                let z = match scanner.pop().unwrap() {
+                    'B' => 5,
+                    'C' => 6,
+                    'N' => 7,
+                    'O' => 8,
+                    'F' => 9,
+                    'P' => 15,
+                    'S' => 16,
+                    'I' => 53,
+                    _ => 0,
+                };
+                element = Some(z);
+                aromatic = false;
+            }
+            // Atom-map number, a label that does not restrict the match
+            Some(':') => {
+                scanner.pop();
+                let start = scanner.cursor();
+                let mut digits = String::new();
+                while scanner.peek().is_some_and(|c| c.is_ascii_digit()) {
+                    digits.push(*scanner.pop().unwrap());
+                }
+                atom_map = digits
+                    .parse()
+                    .map_err(|_| scanner.error_at(start, ErrorKind::InvalidNumber))?;
+            }
             Some(']') => {
                 scanner.pop();
                 break;
             }
-            None => return Err(Error::EndOfLine),
-            _ => return Err(Error::Character(scanner.cursor())),
+            None => return Err(scanner.error(ErrorKind::UnexpectedEnd)),
+            _ => return Err(scanner.error(ErrorKind::UnexpectedCharacter)),
         }
     }
 
@@ -319,7 +393,11 @@ fn parse_bracket_atom_expr(scanner: &mut Scanner) -> Result<Expr, Error> {
         .unwrap();
     }
 
-    Ok(expr)
+    for ring_expr in counted_exprs {
+        expr = combine_and(Some(expr), Some(ring_expr)).unwrap();
+    }
+
+    Ok((expr, atom_map))
 }
 
 /// Parse a SMARTS atom expression (outside brackets).
@@ -509,7 +587,7 @@ fn parse_atom_expr(scanner: &mut Scanner) -> Result<Expr, Error> {
         }
         Some('[') => {
             scanner.pop();
-            parse_bracket_atom_expr(scanner)?
+            parse_bracket_atom_expr(scanner)?.0
         }
         Some('!') => {
             scanner.pop();
@@ -666,7 +744,15 @@ fn parse_bond_expr(scanner: &mut Scanner) -> Result<Expr, Error> {
 // ────────────────────────────────────────────────────
 
 impl SmartsPattern {
+    /// The pattern for `smarts_string`, panicking when it does not parse.
     pub fn new(smarts_string: &str) -> SmartsPattern {
+        match SmartsPattern::parse(smarts_string) {
+            Ok(pat) => pat,
+            Err(e) => panic!("SMARTS parse error: {}", e),
+        }
+    }
+
+    pub fn parse(smarts_string: &str) -> Result<SmartsPattern, Error> {
         let mut pat = SmartsPattern {
             nodes: Vec::new(),
             root: 0,
@@ -674,31 +760,35 @@ impl SmartsPattern {
             chirality: false,
             recursion: false,
         };
-        match pat.build_ast() {
-            Ok(_) => {}
-            Err(e) => panic!("SMARTS parse error: {:?}", e),
-        }
-        pat
+        pat.build_ast()?;
+        Ok(pat)
     }
 
     pub fn build_ast(&mut self) -> Result<(), Error> {
         let mut scanner = Scanner::new(&self.smarts_string.clone());
-        let mut branch_points: VecDeque<usize> = VecDeque::new();
+        // Open branches with the position of their '('
+        let mut branch_points: VecDeque<(usize, usize)> = VecDeque::new();
         let mut prev_atom: Option<usize> = None; // index of last SeedAtom node
-        let mut ring_closures: HashMap<u8, usize> = HashMap::new();
+        let mut ring_closures: HashMap<u8, (usize, usize)> = HashMap::new();
         let mut implicit_bond: Option<ExprType> = None; // type to use when no bond token seen
 
         while scanner.peek().is_some() {
             match scanner.peek() {
                 // ── Branch open ──────────────────────────────────────────────
                 Some('(') => {
-                    branch_points.push_back(prev_atom.expect("branch without atom"));
+                    let Some(atom) = prev_atom else {
+                        return Err(scanner.error(ErrorKind::UnexpectedCharacter));
+                    };
+                    branch_points.push_back((atom, scanner.cursor()));
                     scanner.pop();
                 }
 
                 // ── Branch close ─────────────────────────────────────────────
                 Some(')') => {
-                    prev_atom = branch_points.pop_back();
+                    let Some((atom, _)) = branch_points.pop_back() else {
+                        return Err(scanner.error(ErrorKind::UnmatchedBranch));
+                    };
+                    prev_atom = Some(atom);
                     scanner.pop();
                 }
 
@@ -715,27 +805,44 @@ impl SmartsPattern {
 
                 // ── Ring closure digit ───────────────────────────────────────
                 Some('0'..='9') => {
+                    let start = scanner.cursor();
                     let digit = scanner.pop().unwrap().to_digit(10).unwrap() as u8;
-                    self.handle_ring_closure(digit, prev_atom, &mut ring_closures)?;
+                    let Some(atom) = prev_atom else {
+                        return Err(scanner.error_at(start, ErrorKind::UnexpectedCharacter));
+                    };
+                    self.handle_ring_closure(digit, atom, start, &mut ring_closures);
                 }
 
                 // ── Two-digit ring closure (%NN) ─────────────────────────────
                 Some('%') => {
+                    let start = scanner.cursor();
                     scanner.pop();
                     let mut s = String::new();
                     for _ in 0..2 {
                         match scanner.peek() {
                             Some('0'..='9') => s.push(*scanner.pop().unwrap()),
-                            _ => return Err(Error::Character(scanner.cursor())),
+                            _ => return Err(missing_character(&mut scanner)),
                         }
                     }
                     let n = s.parse::<u8>().unwrap();
-                    self.handle_ring_closure(n, prev_atom, &mut ring_closures)?;
+                    let Some(atom) = prev_atom else {
+                        return Err(scanner.error_at(start, ErrorKind::UnexpectedCharacter));
+                    };
+                    self.handle_ring_closure(n, atom, start, &mut ring_closures);
                 }
 
                 // ── Atom (any non-bond, non-branch, non-ring token) ──────────
                 _ => {
-                    let atom_expr = parse_atom_expr(&mut scanner)?;
+                    let start = scanner.cursor();
+                    let (atom_expr, atom_map) = if scanner.peek() == Some(&'[') {
+                        scanner.pop();
+                        parse_bracket_atom_expr(&mut scanner)?
+                    } else {
+                        (parse_atom_expr(&mut scanner)?, 0)
+                    };
+                    if scanner.cursor() == start {
+                        return Err(scanner.error(ErrorKind::UnexpectedCharacter));
+                    }
                     let atom_idx = self.nodes.len();
 
                     self.nodes.push(TreeNode {
@@ -749,6 +856,7 @@ impl SmartsPattern {
                         dst: None,
                         nbrs: Some(Vec::new()),
                         visit: false,
+                        atom_map,
                     });
 
                     // Wire up the bond from the previous atom
@@ -768,6 +876,7 @@ impl SmartsPattern {
                             dst: Some(atom_idx),
                             nbrs: None,
                             visit: false,
+                            atom_map: 0,
                         });
 
                         // Register bond in both atom's neighbour lists
@@ -781,18 +890,28 @@ impl SmartsPattern {
                 }
             }
         }
+
+        if let Some((&digit, &(_, position))) = ring_closures
+            .iter()
+            .min_by_key(|(_, &(_, position))| position)
+        {
+            let kind = ErrorKind::UnclosedRing(digit as usize);
+            return Err(Error::new(kind, 1, position + 1, digit.to_string()));
+        }
+        if let Some(&(_, position)) = branch_points.front() {
+            return Err(Error::new(ErrorKind::UnclosedBranch, 1, position + 1, "("));
+        }
         Ok(())
     }
 
     fn handle_ring_closure(
         &mut self,
         digit: u8,
-        prev_atom: Option<usize>,
-        ring_closures: &mut HashMap<u8, usize>,
-    ) -> Result<(), Error> {
-        let curr_atom = prev_atom.ok_or(Error::EndOfLine)?;
-
-        if let Some(open_atom) = ring_closures.remove(&digit) {
+        curr_atom: usize,
+        position: usize,
+        ring_closures: &mut HashMap<u8, (usize, usize)>,
+    ) {
+        if let Some((open_atom, _)) = ring_closures.remove(&digit) {
             let ring_bond_idx = self.nodes.len();
             self.nodes.push(TreeNode {
                 op_code: OpCode::CloseRing,
@@ -806,6 +925,7 @@ impl SmartsPattern {
                 dst: Some(curr_atom),
                 nbrs: None,
                 visit: false,
+                atom_map: 0,
             });
             self.nodes[open_atom]
                 .nbrs
@@ -818,12 +938,17 @@ impl SmartsPattern {
                 .unwrap()
                 .push(ring_bond_idx);
         } else {
-            ring_closures.insert(digit, curr_atom);
+            ring_closures.insert(digit, (curr_atom, position));
         }
-        Ok(())
     }
 
     pub fn match_mol(&self, molecule: &Molecule) -> bool {
+        if molecule.ring_info().is_none() {
+            // Ring primitives need perceived rings, work on a copy
+            let mut molecule = molecule.clone();
+            molecule.perceive_rings();
+            return SmartsMatch::new(self, &molecule).match_smarts();
+        }
         SmartsMatch::new(self, molecule).match_smarts()
     }
 }
@@ -881,13 +1006,10 @@ impl<'a> SmartsMatch<'a> {
         }
 
         for mol_idx in 0..self.molecule.atoms.len() {
-            if self.atom_mapping.iter().any(|&m| m == Some(mol_idx)) {
+            if self.atom_mapping.contains(&Some(mol_idx)) {
                 continue;
             }
-            if eval_atom_expr(
-                &self.pattern.nodes[op_index].data,
-                &self.molecule.atoms[mol_idx],
-            ) {
+            if eval_atom_expr(&self.pattern.nodes[op_index].data, self.molecule, mol_idx) {
                 self.atom_mapping[op_index] = Some(mol_idx);
                 if self.match_recursive(op_index + 1) {
                     return true;
@@ -925,25 +1047,16 @@ impl<'a> SmartsMatch<'a> {
         }
 
         // ── EXISTING: dst not yet placed, try all neighbours ──
-        for bond_idx in 0..self.molecule.bonds.len() {
+        for &bond_idx in self.molecule.bonds_of(mol_src) {
             let mol_bond = &self.molecule.bonds[bond_idx];
-            let other = if mol_bond.source == mol_src {
-                mol_bond.dest
-            } else if mol_bond.dest == mol_src {
-                mol_bond.source
-            } else {
-                continue;
-            };
+            let other = mol_bond.other(mol_src);
 
-            if self.atom_mapping.iter().any(|&m| m == Some(other)) {
+            if self.atom_mapping.contains(&Some(other)) {
                 continue;
             }
 
             if eval_bond_expr(&node.data, mol_bond)
-                && eval_atom_expr(
-                    &self.pattern.nodes[qry_dst].data,
-                    &self.molecule.atoms[other],
-                )
+                && eval_atom_expr(&self.pattern.nodes[qry_dst].data, self.molecule, other)
             {
                 self.atom_mapping[qry_dst] = Some(other);
                 self.bond_mapping[op_index] = Some(bond_idx);
@@ -966,14 +1079,8 @@ impl<'a> SmartsMatch<'a> {
             return false;
         };
 
-        if let Some(mol_bond) = self.molecule.get_bond(mol_src, mol_dst) {
-            if eval_bond_expr(&node.data, mol_bond) {
-                let bond_pos = self
-                    .molecule
-                    .bonds
-                    .iter()
-                    .position(|b| b == mol_bond)
-                    .unwrap();
+        if let Some(bond_pos) = self.molecule.bond_between(mol_src, mol_dst) {
+            if eval_bond_expr(&node.data, &self.molecule.bonds[bond_pos]) {
                 self.bond_mapping[op_index] = Some(bond_pos);
                 if self.match_recursive(op_index + 1) {
                     return true;
@@ -76,6 +76,7 @@ pub struct TreeNode {
     pub dst: Option<usize>, // Option for bonds as atoms don't really have a dst
     pub nbrs: Option<Vec<usize>>, // None for Bond Nodes
     pub visit: bool,
+    pub atom_map: usize, // `:n` on atom nodes, 0 for none and for bonds
 }
 #[derive(Debug)]
 pub struct SmartsPattern {
@@ -1,7 +1,6 @@
 use super::smarts_defs::{Expr, ExprType};
 use crate::{
-    core::configuration::Configuration,
-    core::defs::{Atom, Bond},
+    core::configuration::Configuration, core::defs::Bond, core::molecule::Molecule,
     parsers::scanner::Scanner,
 };
 
@@ -48,7 +47,9 @@ pub fn collect_recursive_smarts(scanner: &mut Scanner) -> String {
 
 // These are utility function for matching
 
-pub fn eval_atom_expr(expr: &Expr, atom: &Atom) -> bool {
+pub fn eval_atom_expr(expr: &Expr, molecule: &Molecule, atom_idx: usize) -> bool {
+    let atom = &molecule.atoms[atom_idx];
+    let rings = molecule.ring_info();
     let mut current_expr = expr;
     loop {
         match current_expr.expr_type {
@@ -70,33 +71,39 @@ pub fn eval_atom_expr(expr: &Expr, atom: &Atom) -> bool {
                 return current_expr.val == Some(atom.element as i8) && !atom.aromatic
             }
             ExprType::AeHcount => {
-                // Assuming we have a method to get implicit hydrogen count
-                let total_h = atom.hydrogens;
+                // Implicit hydrogens plus hydrogen atoms in the graph
+                let total_h = molecule.total_h_count(atom_idx);
                 return current_expr.val == Some(total_h as i8);
             }
             ExprType::AeCharge => return current_expr.val == Some(atom.f_charge),
             ExprType::AeConnect => {
-                // Total number of heavy-atom bonds (degree), not counting implicit H
-                let degree = atom.outgoing_bond.len() as i8;
-                return current_expr.val == Some(degree);
+                // Total connections, implicit hydrogens included
+                let connections = molecule.total_connections(atom_idx) as i8;
+                return current_expr.val == Some(connections);
             }
 
             ExprType::AeDegree => return current_expr.val == Some(atom.outgoing_bond.len() as i8),
             ExprType::AeImplicit => {
-                // Assuming we have a method to get implicit hydrogen count
-                // return current_expr.val == Some(atom.get_implicit_h_count() as i8);
+                // Bare `h` means at least one
+                let implicit = molecule.implicit_h_count(atom_idx) as i8;
+                return match current_expr.val {
+                    Some(count) => implicit == count,
+                    None => implicit > 0,
+                };
             }
             ExprType::AeRings => {
-                // Assuming we have a method to get ring count
-                // return current_expr.val == Some(atom.get_ring_count() as i8);
+                // Number of SSSR rings the atom is in
+                let count = rings.map_or(0, |r| r.atom_ring_count(atom_idx));
+                return current_expr.val == Some(count as i8);
             }
             ExprType::AeSize => {
-                // Assuming we have a method to check ring size
-                // return atom.is_in_ring_size(current_expr.val.unwrap() as usize);
+                // Size of the smallest ring the atom is in
+                let size = rings.and_then(|r| r.smallest_ring_size(atom_idx));
+                return size.is_some() && current_expr.val == size.map(|s| s as i8);
             }
             ExprType::AeValence => {
-                // Assuming we have a method to get total valence
-                // return current_expr.val == Some(atom.get_total_valence() as i8);
+                let valence = molecule.total_valence(atom_idx) as i8;
+                return current_expr.val == Some(valence);
             }
             ExprType::AeChiral => {
                 return match current_expr.val {
@@ -107,24 +114,24 @@ pub fn eval_atom_expr(expr: &Expr, atom: &Atom) -> bool {
                 };
             }
             ExprType::AeHyb => {
-                // Assuming we have a method to get hybridization
-                // return current_expr.val == Some(atom.get_hyb() as i8);
+                let number = molecule.hybridization(atom_idx).smarts_number();
+                return number.is_some() && current_expr.val == number.map(|n| n as i8);
             }
             ExprType::AeRingconnect => {
-                // Assuming we have a method to count ring bonds
-                // return current_expr.val == Some(atom.count_ring_bonds() as i8);
+                let ring_bonds = rings.map_or(0, |r| r.ring_connectivity(atom_idx));
+                return current_expr.val == Some(ring_bonds as i8);
             }
             ExprType::AeNot => {
-                return !eval_atom_expr(current_expr.left.as_ref().unwrap(), atom);
+                return !eval_atom_expr(current_expr.left.as_ref().unwrap(), molecule, atom_idx);
             }
             ExprType::AeAndhi | ExprType::AeAndlo => {
-                if !eval_atom_expr(current_expr.left.as_ref().unwrap(), atom) {
+                if !eval_atom_expr(current_expr.left.as_ref().unwrap(), molecule, atom_idx) {
                     return false;
                 }
                 current_expr = current_expr.right.as_ref().unwrap();
             }
             ExprType::AeOr => {
-                if eval_atom_expr(current_expr.left.as_ref().unwrap(), atom) {
+                if eval_atom_expr(current_expr.left.as_ref().unwrap(), molecule, atom_idx) {
                     return true;
                 }
                 current_expr = current_expr.right.as_ref().unwrap();
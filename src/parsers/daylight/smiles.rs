@@ -1,12 +1,22 @@
 use crate::{
     core::{
-        defs::{Atom, Bond},
+        aromaticity::AromaticityModel,
+        configuration::Configuration,
+        defs::{Atom, Axialness, Bond, BondIndex},
         molecule::Molecule,
+        properties::PropertyMap,
+        valence::{aromatic_implicit_h_count, implicit_h_count},
+    },
+    parsers::{
+        error::{Error, ErrorKind},
+        scanner::Scanner,
     },
-    parsers::{elements::read_symbol, error::Error, scanner::Scanner},
 };
 
-use super::smiles_utils::{read_axial, read_bond, read_bracket, read_organic, read_star};
+use super::smiles_utils::{
+    read_aliphatic_organic, read_axial, read_bond, read_bracket, read_organic, read_ring_bond,
+    read_star, RingBond,
+};
 use std::collections::{HashMap, VecDeque};
 
 fn parse_atom(scanner: &mut Scanner) -> Result<Atom, Error> {
@@ -16,100 +26,108 @@ fn parse_atom(scanner: &mut Scanner) -> Result<Atom, Error> {
     if let Some(bracket_atom) = read_bracket(scanner)? {
         return Ok(bracket_atom);
     }
+    // Organic subset atoms, lowercase ones aromatic
     if let Some(element) = read_organic(scanner)? {
-        let atom_data = Atom {
-            element,
-            outgoing_bond: Vec::new(),
-            isotope: 0,
-            hydrogens: 0,
-            aromatic: true,
-            f_charge: 0,
-            configuration: None,
-            ring: false,
-            symmetry_class: 0,
-            coords_3d: None,
-        };
-        return Ok(atom_data);
+        Ok(Atom::new(element).with_aromatic(true))
     } else {
-        let element = read_symbol(scanner)?;
-        let atom_data = Atom {
-            element,
-            outgoing_bond: Vec::new(),
-            isotope: 0,
-            hydrogens: 0,
-            aromatic: false,
-            f_charge: 0,
-            configuration: None,
-            ring: false,
-            symmetry_class: 0,
-            coords_3d: None,
-        };
-        return Ok(atom_data);
+        let element = read_aliphatic_organic(scanner)?;
+        Ok(Atom::new(element))
     }
 }
 
-/// OpenSMILES-like target valence table (atomic number -> allowed valences).
-/// Only organic subset for now; extend as needed.
-fn target_valences(z: usize) -> &'static [i8] {
-    match z {
-        5 => &[3],                // B
-        6 => &[4],                // C
-        7 => &[3, 5],             // N
-        8 => &[2],                // O
-        15 => &[3, 5],            // P
-        16 => &[2, 4, 6],         // S
-        9 | 17 | 35 | 53 => &[1], // F, Cl, Br, I
-        _ => &[0],                // no known valence → implicit H = 0
+// The bond closing a ring from `opening` on `source` to `closing` on `dest`.
+// A bond symbol may be written on either side, or on both when they agree;
+// a direction written on the closing side points back at `source`.
+fn ring_closure_bond(
+    molecule: &Molecule,
+    source: usize,
+    opening: &RingBond,
+    dest: usize,
+    closing: &RingBond,
+) -> Result<Bond, ErrorKind> {
+    let label = closing.label;
+    if source == dest {
+        return Err(ErrorKind::Invalid(format!(
+            "ring bond {} closes on its own atom",
+            label
+        )));
     }
-}
-
-/// Sum of bond orders incident on atom `atom_idx`.
-fn atom_valence(molecule: &Molecule, atom_idx: usize) -> i32 {
-    molecule
-        .bonds
-        .iter()
-        .filter(|b| b.source == atom_idx || b.dest == atom_idx)
-        .map(|b| b.bond_order as i32)
-        .sum()
-}
-
-/// Compute implicit H count from SMILES valence table rule:
-/// find smallest target valence ≥ current valence, then H = target - valence.
-/// If no target valence ≥ valence, H = 0.
-fn compute_implicit_h_count(z: usize, valence: i32) -> usize {
-    let targets = target_valences(z);
-    let valence = valence.max(0);
-
-    let target = targets
-        .iter()
-        .filter(|&&t| t >= valence as i8)
-        .min()
-        .copied()
-        .unwrap_or(0);
-
-    if target == 0 {
-        0
-    } else {
-        (target - valence as i8).max(0) as usize
+    if molecule.bond_between(source, dest).is_some() {
+        return Err(ErrorKind::Invalid(format!(
+            "ring bond {} duplicates a bond",
+            label
+        )));
     }
+    let closing_axialness = match closing.axialness {
+        Axialness::UP => Axialness::DOWN,
+        Axialness::DOWN => Axialness::UP,
+        Axialness::UNKNOWN => Axialness::UNKNOWN,
+    };
+    let conflict = match (opening.bond_order, closing.bond_order) {
+        (Some(a), Some(b)) => a != b || opening.axialness != closing_axialness,
+        _ => false,
+    };
+    if conflict {
+        return Err(ErrorKind::Invalid(format!(
+            "ring bond {} has conflicting bond symbols",
+            label
+        )));
+    }
+
+    let arom = molecule.atoms[source].aromatic && molecule.atoms[dest].aromatic;
+    let bond_order = match opening.bond_order.or(closing.bond_order) {
+        Some(order) if !arom => order,
+        // Aromatic bond order 1 until kekulized
+        _ => 1,
+    };
+    let axialness = match opening.axialness.clone() {
+        Axialness::UNKNOWN => closing_axialness,
+        axialness => axialness,
+    };
+    Ok(Bond {
+        source,
+        dest,
+        arom,
+        ring: false,
+        bond_order,
+        axialness,
+        properties: PropertyMap::new(),
+    })
 }
 
 pub fn parse_smiles(smiles: &str) -> Result<Molecule, Error> {
     let mut scanner = Scanner::new(smiles);
     let mut molecule = Molecule::new();
     let mut prev_atom: Option<usize> = None;
-    let mut ring_closures: HashMap<u8, usize> = HashMap::new();
-    let mut branch_points: VecDeque<usize> = VecDeque::new();
+    // Open ring bonds and branches, with where they were opened; open ring
+    // bonds also keep their place among the atom's ring bonds
+    let mut ring_closures: HashMap<usize, (usize, RingBond, usize)> = HashMap::new();
+    let mut branch_points: VecDeque<(usize, usize)> = VecDeque::new();
+    // Bracket atoms keep the hydrogen count they were written with
+    let mut bracket_atoms: Vec<bool> = Vec::new();
+    // Where each atom was written, from its first character up to the next
+    let mut atom_spans: Vec<(usize, usize)> = Vec::new();
+    // Per atom: whether it is bonded to a previous atom, and the bonds of
+    // its ring bond numbers in written order, `None` while still open
+    let mut chained: Vec<bool> = Vec::new();
+    let mut ring_bonds: Vec<Vec<Option<BondIndex>>> = Vec::new();
+    let mut name = String::new();
 
-    while let Some(_) = scanner.peek() {
+    while scanner.peek().is_some() {
         match scanner.peek() {
             Some('(') => {
-                branch_points.push_front(prev_atom.unwrap());
+                let Some(atom) = prev_atom else {
+                    return Err(scanner.error(ErrorKind::UnexpectedCharacter));
+                };
+                branch_points.push_back((atom, scanner.cursor()));
                 scanner.pop();
                 continue;
             }
             Some(')') => {
-                prev_atom = branch_points.pop_back();
+                let Some((atom, _)) = branch_points.pop_back() else {
+                    return Err(scanner.error(ErrorKind::UnmatchedBranch));
+                };
+                prev_atom = Some(atom);
                 scanner.pop();
                 continue;
             }
@@ -118,95 +136,143 @@ pub fn parse_smiles(smiles: &str) -> Result<Molecule, Error> {
                 scanner.pop();
                 continue;
             }
+            // A SMILES record ends at whitespace, what follows is its name
+            Some(c) if c.is_whitespace() => {
+                name = smiles.chars().skip(scanner.cursor()).collect();
+                break;
+            }
             _ => {}
         }
 
-        let bond_order = read_bond(&mut scanner) as i8;
+        let bond_order = read_bond(&mut scanner);
         let bond_axialness = read_axial(&mut scanner);
-        let atom_data = parse_atom(&mut scanner)?;
+        bracket_atoms.push(scanner.peek() == Some(&'['));
+        let atom_start = scanner.cursor();
+        let mut atom_data = parse_atom(&mut scanner)?;
+        atom_spans.push((atom_start, scanner.cursor()));
+        if prev_atom.is_none() && atom_data.hydrogens > 0 {
+            // "[C@H](F)(Cl)Br" lists the hydrogen first, but parities are stored
+            // with the hydrogen after the first neighbour, so swap the two.
+            atom_data.configuration = match atom_data.configuration {
+                Some(Configuration::TH1) => Some(Configuration::TH2),
+                Some(Configuration::TH2) => Some(Configuration::TH1),
+                other => other,
+            };
+        }
         let aromatic = atom_data.aromatic;
 
         let curr_index = molecule.atoms.len();
         molecule.add_atom(atom_data);
+        chained.push(prev_atom.is_some());
+        ring_bonds.push(Vec::new());
 
-        // Handle ring closure (digit after the atom)
-        let mut bond = None;
-        if let Some(digit_char) = scanner.peek().and_then(|c| c.to_digit(10)) {
-            let ring_number = digit_char as u8;
-            scanner.pop();
-
-            if let Some(&other_atom) = ring_closures.get(&ring_number) {
-                // Flip ring flag on both atoms
-                molecule.atoms[curr_index].ring_reverse();
-                molecule.atoms[other_atom].ring_reverse();
-
-                let mut actual_bond_order = bond_order;
-                if aromatic && molecule.atoms[other_atom].aromatic {
-                    // For aromatic bonds, use 1 for now; kekulization can come later.
-                    actual_bond_order = 1;
-                }
-
-                bond = Some(Bond {
-                    source: other_atom,
-                    dest: curr_index,
-                    arom: aromatic,
-                    ring: true,
-                    bond_order: actual_bond_order,
-                    axialness: bond_axialness.clone(),
-                });
-
-                ring_closures.remove(&ring_number);
-            } else {
-                // First side of the closure
-                ring_closures.insert(ring_number, curr_index);
-            }
-        }
-
-        // Handle connection to previous atom (unless ring closure already made the bond)
+        // Handle connection to previous atom
         if let Some(last_atom) = prev_atom {
             let mut actual_bond_order = bond_order;
-            if aromatic && molecule.atoms[last_atom].aromatic {
-                actual_bond_order = 1; // aromatic bond order 1; kekulization later
+            let arom = aromatic && molecule.atoms[last_atom].aromatic;
+            if arom {
+                actual_bond_order = 1; // aromatic bond order 1 until kekulized
             }
 
-            bond = Some(Bond {
+            molecule.add_bond(Bond {
                 source: last_atom,
                 dest: curr_index,
-                arom: aromatic,
+                arom,
                 ring: false,
                 bond_order: actual_bond_order,
                 axialness: bond_axialness,
+                properties: PropertyMap::new(),
             });
         }
 
-        // Record bond and update outgoing_bond
-        if let Some(bond) = bond {
-            let bond_index = molecule.bonds.len();
-            molecule.add_bond(bond);
-
-            molecule.atoms[curr_index].add_to_bond_list(bond_index);
-            if let Some(last_atom) = prev_atom {
-                molecule.atoms[last_atom].add_to_bond_list(bond_index);
+        // Ring bonds after the atom, opened here or closed on an earlier atom
+        while let Some(ring_bond) = read_ring_bond(&mut scanner)? {
+            match ring_closures.remove(&ring_bond.label) {
+                Some((other_atom, opening, slot)) => {
+                    let bond =
+                        ring_closure_bond(&molecule, other_atom, &opening, curr_index, &ring_bond)
+                            .map_err(|kind| scanner.error_at(ring_bond.position, kind))?;
+                    let bond_index = molecule.add_bond(bond);
+                    ring_bonds[other_atom][slot] = Some(bond_index);
+                    ring_bonds[curr_index].push(Some(bond_index));
+                }
+                None => {
+                    let slot = ring_bonds[curr_index].len();
+                    ring_bonds[curr_index].push(None);
+                    ring_closures.insert(ring_bond.label, (curr_index, ring_bond, slot));
+                }
             }
         }
 
-        // Recompute implicit H count for the **previous** atom (curr_index is just added)
-        if let Some(last_atom) = prev_atom {
-            let current_valence = atom_valence(&molecule, last_atom) as i32;
-            let h_count =
-                compute_implicit_h_count(molecule.atoms[last_atom].element, current_valence);
-            molecule.h_count_update(last_atom, h_count);
-        }
-
         prev_atom = Some(curr_index);
     }
 
-    // Final pass: fix last atom too
-    if let Some(last_atom) = prev_atom {
-        let valence = atom_valence(&molecule, last_atom) as i32;
-        let h_count = compute_implicit_h_count(molecule.atoms[last_atom].element, valence);
-        molecule.h_count_update(last_atom, h_count);
+    // Report the first ring bond or branch left open
+    if let Some((&label, (_, ring_bond, _))) = ring_closures
+        .iter()
+        .min_by_key(|(_, (_, ring_bond, _))| ring_bond.position)
+    {
+        let kind = ErrorKind::UnclosedRing(label);
+        return Err(Error::new(
+            kind,
+            1,
+            ring_bond.position + 1,
+            label.to_string(),
+        ));
+    }
+    if let Some(&(_, position)) = branch_points.front() {
+        return Err(Error::new(ErrorKind::UnclosedBranch, 1, position + 1, "("));
     }
 
+    // A ring bond joins the opening atom's neighbours where its number was
+    // written, straight after the previous atom and ahead of branches and
+    // the next atom; parities are read against that order
+    for (atom_idx, ring_bonds) in ring_bonds.into_iter().enumerate() {
+        let ring_bonds: Vec<BondIndex> = ring_bonds.into_iter().flatten().collect();
+        if ring_bonds.is_empty() {
+            continue;
+        }
+        let others: Vec<BondIndex> = molecule.atoms[atom_idx]
+            .outgoing_bond
+            .iter()
+            .copied()
+            .filter(|b| !ring_bonds.contains(b))
+            .collect();
+        let split = usize::from(chained[atom_idx]);
+        let mut ordered = others[..split].to_vec();
+        ordered.extend(ring_bonds);
+        ordered.extend(&others[split..]);
+        molecule.atoms[atom_idx].outgoing_bond = ordered;
+    }
+
+    // Final pass: implicit H for every atom written without brackets
+    for (atom_idx, &bracket) in bracket_atoms.iter().enumerate() {
+        if bracket {
+            continue;
+        }
+        // Aromatic bonds are all still single here
+        let valence = molecule.explicit_valence(atom_idx);
+        let atom = &molecule.atoms[atom_idx];
+        let h_count = if atom.aromatic {
+            aromatic_implicit_h_count(atom.element, atom.f_charge, valence)
+        } else {
+            implicit_h_count(atom.element, atom.f_charge, valence)
+        };
+        molecule.h_count_update(atom_idx, h_count);
+    }
+
+    // Lowercase atoms only say where to kekulize, the model has the last word;
+    // a failure points at the first atom left without a double bond
+    molecule.kekulize().map_err(|e| {
+        let (start, end) = atom_spans[e.unmatched[0]];
+        let token: String = smiles.chars().skip(start).take(end - start).collect();
+        Error::new(ErrorKind::Kekulize(e), 1, start + 1, token)
+    })?;
+    molecule.perceive_rings();
+    molecule.perceive_aromaticity(AromaticityModel::Daylight);
+    molecule.perceive_symmetry_classes();
+    if !name.trim().is_empty() {
+        molecule.set_name(name.trim());
+    }
     Ok(molecule)
 }
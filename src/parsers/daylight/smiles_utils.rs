@@ -1,6 +1,13 @@
 use crate::{
-    core::defs::{Atom, Axialness},
-    parsers::{elements::read_symbol, error::Error, scanner::Scanner},
+    core::{
+        defs::{Atom, Axialness},
+        properties::PropertyMap,
+    },
+    parsers::{
+        elements::read_symbol,
+        error::{Error, ErrorKind},
+        scanner::{missing_character, Scanner},
+    },
 };
 
 use super::config::read_configuration;
@@ -16,7 +23,7 @@ pub fn read_bracket(scanner: &mut Scanner) -> Result<Option<Atom>, Error> {
     let symbol;
     let aromatic;
 
-    if let Some(element) = read_organic(scanner)? {
+    if let Some(element) = read_bracket_aromatic(scanner)? {
         symbol = element;
         aromatic = true;
     } else {
@@ -27,7 +34,7 @@ pub fn read_bracket(scanner: &mut Scanner) -> Result<Option<Atom>, Error> {
     let configuration = read_configuration(scanner)?;
     let hcount = read_hcount(scanner)?;
     let charge = read_charge(scanner)?;
-    // let map: Option<i8> = read_map(scanner)?;
+    let atom_map = read_map(scanner)?;
 
     match scanner.peek() {
         Some(']') => {
@@ -37,17 +44,47 @@ pub fn read_bracket(scanner: &mut Scanner) -> Result<Option<Atom>, Error> {
                 element: symbol,
                 outgoing_bond: Vec::new(),
                 hydrogens: hcount,
-                aromatic: aromatic,
+                aromatic,
                 symmetry_class: 0,
                 f_charge: charge,
-                isotope: isotope,
-                configuration: configuration,
+                isotope,
+                configuration,
                 ring: false,
                 coords_3d: None,
+                atom_map,
+                properties: PropertyMap::new(),
             }))
         }
-        None => Err(Error::EndOfLine),
-        _ => Err(Error::Character(scanner.cursor())),
+        None => Err(scanner.error(ErrorKind::UnexpectedEnd)),
+        _ => Err(scanner.error(ErrorKind::Expected("']'"))),
+    }
+}
+
+// Aromatic symbols only allowed inside brackets, falling back to the organic ones
+fn read_bracket_aromatic(scanner: &mut Scanner) -> Result<Option<usize>, Error> {
+    match scanner.peek() {
+        Some('a') => {
+            let start = scanner.cursor();
+            scanner.pop();
+            match scanner.peek() {
+                Some('s') => {
+                    scanner.pop();
+                    Ok(Some(33)) // Atomic number for Arsenic is 33
+                }
+                _ => Err(scanner.error_at(start, ErrorKind::UnknownElement("a".to_string()))),
+            }
+        }
+        Some('s') => {
+            scanner.pop();
+            match scanner.peek() {
+                Some('e') => {
+                    scanner.pop();
+                    Ok(Some(34)) // Atomic number for Selenium is 34
+                }
+                _ => Ok(Some(16)),
+            }
+        }
+        _ => read_organic(scanner),
     }
 }
 
@@ -69,7 +106,7 @@ pub fn read_hcount(scanner: &mut Scanner) -> Result<usize, Error> {
                     Some('9') => Ok(9),
                     _ => Ok(1),
                 },
-                _ => Ok(0),
+                _ => Ok(1),
             }
         }
         _ => Ok(0),
@@ -93,35 +130,30 @@ pub fn read_isotope(scanner: &mut Scanner) -> Result<usize, Error> {
     }
 }
 
-// fn read_map(scanner: &mut Scanner) -> Result<Option<i8>, Error> {
-//     match scanner.peek() {
-//         Some(':') => {
-//             scanner.pop();
-
-//             let mut digits = String::new();
-
-//             match scanner.pop() {
-//                 Some(next) => if next.is_ascii_digit() {
-//                     digits.push(*next);
-//                 } else {
-//                     return Err(Error::Character(scanner.cursor() - 1))
-//                 },
-//                 None => return Err(missing_character(scanner))
-//             }
-
-//             for _ in 0..2 {
-//                 match scanner.peek() {
-//                     Some('0'..='9') =>
-//                         digits.push(*scanner.pop().expect("digit")),
-//                     _ => break
-//                 }
-//             }
-
-//             Ok(Some(digits.parse::<i8>().unwrap()))
-//         },
-//         _ => Ok(None)
-//     }
-// }
+// Atom-map or class number after ':', 0 when there is none
+pub fn read_map(scanner: &mut Scanner) -> Result<usize, Error> {
+    match scanner.peek() {
+        Some(':') => {
+            scanner.pop();
+            let start = scanner.cursor();
+            let mut digits = String::new();
+            while let Some(&c) = scanner.peek() {
+                if !c.is_ascii_digit() {
+                    break;
+                }
+                digits.push(c);
+                scanner.pop();
+            }
+            if digits.is_empty() {
+                return Err(missing_character(scanner));
+            }
+            digits
+                .parse()
+                .map_err(|_| scanner.error_at(start, ErrorKind::InvalidNumber))
+        }
+        _ => Ok(0),
+    }
+}
 
 pub fn read_charge(scanner: &mut Scanner) -> Result<i8, Error> {
     match scanner.peek() {
@@ -129,7 +161,7 @@ pub fn read_charge(scanner: &mut Scanner) -> Result<i8, Error> {
             scanner.pop();
 
             match fifteen(scanner) {
-                Some(value) => Ok(value.try_into().expect("charge")),
+                Some(value) => Ok(value),
                 None => match scanner.peek() {
                     Some('+') => {
                         scanner.pop();
@@ -144,7 +176,7 @@ pub fn read_charge(scanner: &mut Scanner) -> Result<i8, Error> {
             scanner.pop();
 
             match fifteen(scanner) {
-                Some(value) => Ok((-value).try_into().expect("charge")),
+                Some(value) => Ok(-value),
                 None => match scanner.peek() {
                     Some('-') => {
                         scanner.pop();
@@ -222,31 +254,93 @@ pub fn read_axial(scanner: &mut Scanner) -> Axialness {
         }
         Some('\\') => {
             scanner.pop();
-            Axialness::UP
+            Axialness::DOWN
         }
         _ => Axialness::UNKNOWN,
     };
     result
 }
 
+/// A ring bond label written after an atom, with its bond symbol if any.
+pub struct RingBond {
+    pub label: usize,
+    pub bond_order: Option<i8>,
+    pub axialness: Axialness,
+    /// Where the ring bond starts in the input.
+    pub position: usize,
+}
+
+// <ring_bond> ::= <bond>? (<digit> | '%' <digit> <digit> | '%(' <digit>+ ')')
+pub fn read_ring_bond(scanner: &mut Scanner) -> Result<Option<RingBond>, Error> {
+    let position = scanner.cursor();
+    let has_symbol = matches!(
+        scanner.peek(),
+        Some('-' | '=' | '#' | '$' | ':' | '/' | '\\')
+    );
+    let label_start = scanner.peek_nth(has_symbol as usize);
+    if !label_start.is_some_and(|&c| c == '%' || c.is_ascii_digit()) {
+        // A bond symbol not followed by a label belongs to the next atom
+        return Ok(None);
+    }
+
+    let (bond_order, axialness) = if !has_symbol {
+        (None, Axialness::UNKNOWN)
+    } else if matches!(scanner.peek(), Some('/' | '\\')) {
+        (Some(1), read_axial(scanner))
+    } else {
+        (Some(read_bond(scanner)), Axialness::UNKNOWN)
+    };
+
+    let label = match scanner.pop() {
+        Some('%') => read_ring_label(scanner)?,
+        Some(&c) => c.to_digit(10).unwrap() as usize,
+        None => unreachable!("ring bond label"),
+    };
+    Ok(Some(RingBond {
+        label,
+        bond_order,
+        axialness,
+        position,
+    }))
+}
+
+// The number after '%': two digits, or any number of them in parentheses
+fn read_ring_label(scanner: &mut Scanner) -> Result<usize, Error> {
+    let start = scanner.cursor() - 1;
+    let parenthesized = scanner.peek() == Some(&'(');
+    if parenthesized {
+        scanner.pop();
+    }
+    let digits_start = scanner.cursor();
+    while scanner.peek().is_some_and(|c| c.is_ascii_digit()) {
+        if !parenthesized && scanner.cursor() - digits_start == 2 {
+            break;
+        }
+        scanner.pop();
+    }
+    let digits = scanner.text_from(digits_start);
+    if digits.is_empty() || (!parenthesized && digits.len() < 2) {
+        return Err(scanner.error_at(start, ErrorKind::Expected("ring bond number")));
+    }
+    if parenthesized {
+        match scanner.pop() {
+            Some(')') => {}
+            None => return Err(scanner.error(ErrorKind::UnexpectedEnd)),
+            _ => return Err(scanner.error_at(scanner.cursor() - 1, ErrorKind::Expected("')'"))),
+        }
+    }
+    digits
+        .parse()
+        .map_err(|_| scanner.error_at(start, ErrorKind::InvalidNumber))
+}
+
 // <star> = "*"
 pub fn read_star(scanner: &mut Scanner) -> Result<Option<Atom>, Error> {
     match scanner.peek() {
         Some('*') => {
             scanner.pop();
 
-            Ok(Some(Atom {
-                element: 0,
-                outgoing_bond: Vec::new(),
-                isotope: 0,
-                hydrogens: 0,
-                aromatic: false,
-                f_charge: 0,
-                configuration: None,
-                ring: false,
-                symmetry_class: 0,
-                coords_3d: None,
-            }))
+            Ok(Some(Atom::new(0)))
         }
         _ => Ok(None),
     }
@@ -280,3 +374,54 @@ pub fn read_organic(scanner: &mut Scanner) -> Result<Option<usize>, Error> {
         _ => Ok(None),
     }
 }
+
+// Organic subset atoms allowed without brackets: B, C, N, O, P, S, F, Cl, Br, I
+pub fn read_aliphatic_organic(scanner: &mut Scanner) -> Result<usize, Error> {
+    match scanner.peek() {
+        Some('B') => {
+            scanner.pop();
+            match scanner.peek() {
+                Some('r') => {
+                    scanner.pop();
+                    Ok(35) // Atomic number for Bromine is 35
+                }
+                _ => Ok(5),
+            }
+        }
+        Some('C') => {
+            scanner.pop();
+            match scanner.peek() {
+                Some('l') => {
+                    scanner.pop();
+                    Ok(17) // Atomic number for Chlorine is 17
+                }
+                _ => Ok(6),
+            }
+        }
+        Some('N') => {
+            scanner.pop();
+            Ok(7)
+        }
+        Some('O') => {
+            scanner.pop();
+            Ok(8)
+        }
+        Some('P') => {
+            scanner.pop();
+            Ok(15)
+        }
+        Some('S') => {
+            scanner.pop();
+            Ok(16)
+        }
+        Some('F') => {
+            scanner.pop();
+            Ok(9)
+        }
+        Some('I') => {
+            scanner.pop();
+            Ok(53)
+        }
+        _ => Err(missing_character(scanner)),
+    }
+}
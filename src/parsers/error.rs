@@ -1,5 +1,104 @@
-#[derive(Debug, PartialEq)]
-pub enum Error {
-    EndOfLine,
-    Character(usize),
+// Errors shared by every reader: why the input was rejected and where.
+use std::fmt;
+
+use crate::core::kekulize::KekulizeError;
+
+/// Why a parser rejected its input.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ErrorKind {
+    /// The input stopped in the middle of a token or record.
+    UnexpectedEnd,
+    /// A character that does not fit where it appears.
+    UnexpectedCharacter,
+    /// A required character or token that is missing.
+    Expected(&'static str),
+    UnknownElement(String),
+    UnclosedRing(usize),
+    UnclosedBranch,
+    UnmatchedBranch,
+    InvalidNumber,
+    Kekulize(KekulizeError),
+    /// A problem particular to the format, e.g. a count out of range.
+    Invalid(String),
+    /// Reading the input failed.
+    Io(String),
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ErrorKind::UnexpectedEnd => write!(f, "unexpected end of input"),
+            ErrorKind::UnexpectedCharacter => write!(f, "unexpected character"),
+            ErrorKind::Expected(what) => write!(f, "expected {}", what),
+            ErrorKind::UnknownElement(symbol) => write!(f, "unknown element {}", symbol),
+            ErrorKind::UnclosedRing(number) => write!(f, "unclosed ring bond {}", number),
+            ErrorKind::UnclosedBranch => write!(f, "unclosed branch"),
+            ErrorKind::UnmatchedBranch => write!(f, "')' without an open branch"),
+            ErrorKind::InvalidNumber => write!(f, "invalid number"),
+            ErrorKind::Kekulize(error) => write!(f, "{}", error),
+            ErrorKind::Invalid(reason) => write!(f, "{}", reason),
+            ErrorKind::Io(reason) => write!(f, "read failed: {}", reason),
+        }
+    }
+}
+
+/// A parse error with its place in the input.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Error {
+    pub kind: ErrorKind,
+    /// Line of the input, from 1; 0 when the input could not be opened.
+    pub line: usize,
+    /// Column on that line, from 1, in characters (bytes for binary input).
+    /// 0 when the error concerns the whole line or record.
+    pub column: usize,
+    /// The offending text, empty at the end of the input.
+    pub token: String,
+}
+
+impl Error {
+    pub fn new(kind: ErrorKind, line: usize, column: usize, token: impl Into<String>) -> Error {
+        Error {
+            kind,
+            line,
+            column,
+            token: token.into(),
+        }
+    }
+
+    /// The reason without the position, e.g. "unclosed ring bond 3".
+    pub fn reason(&self) -> String {
+        self.kind.to_string()
+    }
+
+    // For inputs parsed in pieces: moves an error found in a piece to where
+    // the piece starts on the line
+    pub(crate) fn shifted(mut self, columns: usize) -> Error {
+        if self.column > 0 {
+            self.column += columns;
+        }
+        self
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}", self.line)?;
+        if self.column > 0 {
+            write!(f, ", column {}", self.column)?;
+        }
+        write!(f, ": {}", self.kind)?;
+        if !self.token.is_empty() {
+            write!(f, " at {:?}", self.token)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.kind {
+            ErrorKind::Kekulize(error) => Some(error),
+            _ => None,
+        }
+    }
 }
@@ -1,3 +1,4 @@
+pub mod binary;
 pub mod daylight;
 pub mod elements;
 pub mod error;
@@ -1,4 +1,4 @@
-use super::error::Error;
+use super::error::{Error, ErrorKind};
 
 #[derive(Debug)]
 pub struct Scanner {
@@ -20,6 +20,29 @@ impl Scanner {
         self.cursor
     }
 
+    /// An error at the cursor, with the character there as its token.
+    pub fn error(&self, kind: ErrorKind) -> Error {
+        self.error_at(self.cursor, kind)
+    }
+
+    /// An error for the text from `start` up to the cursor, or for the
+    /// character at `start` when nothing has been read past it.
+    pub fn error_at(&self, start: usize, kind: ErrorKind) -> Error {
+        let end = self.cursor.max(start + 1).min(self.characters.len());
+        let token: String = self
+            .characters
+            .get(start..end)
+            .map_or(String::new(), |chars| chars.iter().collect());
+        Error::new(kind, 1, start + 1, token)
+    }
+
+    /// The characters from `start` up to the cursor.
+    pub fn text_from(&self, start: usize) -> String {
+        self.characters[start.min(self.cursor)..self.cursor]
+            .iter()
+            .collect()
+    }
+
     pub fn is_done(&self) -> bool {
         self.cursor == self.characters.len()
     }
@@ -27,13 +50,17 @@ impl Scanner {
     pub fn peek(&self) -> Option<&char> {
         self.characters.get(self.cursor)
     }
+    /// The character `n` places after the cursor, without moving it.
+    pub fn peek_nth(&self, n: usize) -> Option<&char> {
+        self.characters.get(self.cursor + n)
+    }
     pub fn look_back(&mut self) -> Option<&char> {
         self.characters.get(self.cursor - 1)
     }
     pub fn pop(&mut self) -> Option<&char> {
         match self.characters.get(self.cursor) {
             Some(result) => {
-                self.cursor = self.cursor + 1;
+                self.cursor += 1;
 
                 Some(result)
             }
@@ -42,27 +69,12 @@ impl Scanner {
     }
 }
 
+/// Error for the character at the cursor: unexpected there, or missing
+/// when the input has ended.
 pub fn missing_character(scanner: &mut Scanner) -> Error {
     if scanner.is_done() {
-        Error::EndOfLine
-    } else {
-        println!(
-            "Missing Characters: {}",
-            scanner.characters[scanner.cursor()],
-        );
-        Error::Character(scanner.cursor())
-    }
-}
-
-pub fn defined_error(scanner: &mut Scanner, error_string: &str) -> Error {
-    if scanner.is_done() {
-        Error::EndOfLine
+        scanner.error(ErrorKind::UnexpectedEnd)
     } else {
-        println!(
-            "Error at {} and it probably is because: {}",
-            scanner.characters[scanner.cursor()],
-            error_string
-        );
-        Error::Character(scanner.cursor())
+        scanner.error(ErrorKind::UnexpectedCharacter)
     }
 }
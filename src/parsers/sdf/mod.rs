@@ -1 +1,3 @@
+#[allow(clippy::module_inception)]
 pub mod sdf;
+mod v3000;
@@ -1,110 +1,502 @@
+// MDL molfiles and SD files, V2000 and V3000 (see `v3000`) connection
+// tables as described in the BIOVIA "CTfile Formats" specification. Each
+// record is read on its own: a record that fails to parse is reported and
+// skipped up to its "$$$$" line, and reading carries on with the next one.
 use std::{
     fs::File,
     io::{self, BufRead, BufReader},
+    ops::Range,
+    str::FromStr,
 };
 
-// Honestly Just Copied from CDK
-use crate::core::{
-    defs::{Atom, Axialness, Bond},
-    molecule::Molecule,
+use crate::{
+    core::{
+        aromaticity::AromaticityModel,
+        defs::{Atom, Bond},
+        mendeleev::{element_by_symbol, Element},
+        molecule::Molecule,
+        stereo_perception::BondStereo,
+        valence::{aromatic_implicit_h_count, implicit_h_count},
+    },
+    parsers::error::{Error, ErrorKind},
 };
-use std::str::FromStr;
-
-// Just ChatGPT Code with minimal Validation.
-pub fn read_sdf(file_path: &str) -> io::Result<Vec<Molecule>> {
-    let file = File::open(file_path)?;
-    let reader = BufReader::new(file);
-    let mut molecules = Vec::new();
-    let mut lines = reader.lines();
-
-    while let Some(line) = lines.next() {
-        let line = line?;
-        if line.trim() == "$$$$" {
-            continue;
+
+use super::v3000::read_v3000_ctab;
+
+// Lines of the input numbered from 1, read failures turned into errors
+pub(super) struct Lines<R> {
+    lines: io::Lines<R>,
+    pub(super) number: usize,
+    // Whether the last line read ended a record
+    record_end: bool,
+}
+
+impl<R: BufRead> Lines<R> {
+    pub(super) fn next(&mut self) -> Result<Option<String>, Error> {
+        match self.lines.next() {
+            None => Ok(None),
+            Some(line) => {
+                self.number += 1;
+                let line =
+                    line.map_err(|e| Error::new(ErrorKind::Io(e.to_string()), self.number, 0, ""))?;
+                self.record_end = line.trim() == "$$$$";
+                Ok(Some(line))
+            }
         }
+    }
 
-        let mut molecule = Molecule::new();
-        if let Some(line) = lines.next() {
-            line?;
-        } // Skip line 2
-          // if let Some(line) = lines.next()
-
-        if let Some(line) = lines.next() {
-            let line = line?;
-            let num_atoms = usize::from_str(&line[0..3].trim()).unwrap_or(0);
-            let num_bonds = usize::from_str(&line[3..6].trim()).unwrap_or(0);
-
-            for _ in 0..num_atoms {
-                if let Some(line) = lines.next() {
-                    let line = line?;
-                    let x = f64::from_str(&line[0..10].trim()).unwrap_or(0.0);
-                    let y = f64::from_str(&line[10..20].trim()).unwrap_or(0.0);
-                    let z = f64::from_str(&line[20..30].trim()).unwrap_or(0.0);
-                    let element = line[31..34].trim().to_string();
-                    let mass_diff = usize::from_str(&line[34..36].trim()).unwrap_or(0);
-                    let charge = i8::from_str(&line[36..39].trim()).unwrap_or(0);
-
-                    let atom = Atom {
-                        outgoing_bond: Vec::new(),
-                        element: element.parse().unwrap_or(0),
-                        isotope: mass_diff,
-                        hydrogens: 0,
-                        aromatic: false,
-                        f_charge: charge,
-                        configuration: None,
-                        ring: false,
-                        symmetry_class: 0,
-                        coords_3d: Some((x, y, z)),
-                    };
-
-                    molecule.atoms.push(atom);
-                }
+    // A line the record cannot do without
+    pub(super) fn expect(&mut self) -> Result<String, Error> {
+        self.next()?
+            .ok_or_else(|| Error::new(ErrorKind::UnexpectedEnd, self.number + 1, 0, ""))
+    }
+
+    // Moves past the "$$$$" line of the current record
+    fn skip_record(&mut self) -> Result<(), Error> {
+        while !self.record_end {
+            if self.next()?.is_none() {
+                break;
             }
+        }
+        Ok(())
+    }
+}
 
-            for _ in 0..num_bonds {
-                if let Some(line) = lines.next() {
-                    let line = line?;
-                    let atom1 = usize::from_str(&line[0..3].trim()).unwrap_or(0) - 1;
-                    let atom2 = usize::from_str(&line[3..6].trim()).unwrap_or(0) - 1;
-                    let bond_order = i8::from_str(&line[6..9].trim()).unwrap_or(0);
-
-                    let bond = Bond {
-                        source: atom1,
-                        dest: atom2,
-                        arom: false,
-                        ring: false,
-                        bond_order,
-                        axialness: Axialness::UNKNOWN,
-                    };
-
-                    molecule.bonds.push(bond);
+/// Reads the records of an SD file (or a single molfile) one at a time.
+pub struct SdfReader<R> {
+    lines: Lines<R>,
+    done: bool,
+}
+
+impl<R: BufRead> SdfReader<R> {
+    pub fn new(reader: R) -> SdfReader<R> {
+        SdfReader {
+            lines: Lines {
+                lines: reader.lines(),
+                number: 0,
+                record_end: false,
+            },
+            done: false,
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for SdfReader<R> {
+    type Item = Result<Molecule, Error>;
+
+    /// The next record, or its error; reading goes on with the record after
+    /// it unless the input itself could not be read.
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        match read_record(&mut self.lines) {
+            Ok(Some(molecule)) => Some(Ok(molecule)),
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(error) => {
+                if matches!(error.kind, ErrorKind::Io(_)) || self.lines.skip_record().is_err() {
+                    self.done = true;
                 }
+                Some(Err(error))
             }
+        }
+    }
+}
 
-            // Parse the properties block
-            while let Some(line) = lines.next() {
-                let line = line?;
-                if line.trim() == "M  END" {
-                    break;
+/// Every record of the SD file at `file_path`, each parsed or with the
+/// error that rejected it. Fails as a whole only if the file cannot be opened.
+pub fn read_sdf(file_path: &str) -> Result<Vec<Result<Molecule, Error>>, Error> {
+    let file =
+        File::open(file_path).map_err(|e| Error::new(ErrorKind::Io(e.to_string()), 0, 0, ""))?;
+    Ok(SdfReader::new(BufReader::new(file)).collect())
+}
+
+/// The first record of a molfile or SD file held in a string.
+pub fn parse_molfile(text: &str) -> Result<Molecule, Error> {
+    SdfReader::new(text.as_bytes())
+        .next()
+        .unwrap_or_else(|| Err(Error::new(ErrorKind::UnexpectedEnd, 1, 0, "")))
+}
+
+// The fixed-width field at `columns`, `default` when it is blank or missing
+pub(super) fn field<T: FromStr>(
+    line: &str,
+    number: usize,
+    columns: Range<usize>,
+    default: Option<T>,
+) -> Result<T, Error> {
+    let start = columns.start;
+    let text = line.get(columns).or(line.get(start..)).unwrap_or("").trim();
+    if text.is_empty() {
+        if let Some(default) = default {
+            return Ok(default);
+        }
+    }
+    text.parse()
+        .map_err(|_| Error::new(ErrorKind::InvalidNumber, number, start + 1, text))
+}
+
+pub(super) fn invalid(
+    reason: String,
+    number: usize,
+    column: usize,
+    token: impl Into<String>,
+) -> Error {
+    Error::new(ErrorKind::Invalid(reason), number, column, token)
+}
+
+// Index of atom `index`, numbered from 1 in the file
+fn atom_index(
+    index: usize,
+    num_atoms: usize,
+    number: usize,
+    column: usize,
+) -> Result<usize, Error> {
+    if index == 0 || index > num_atoms {
+        let reason = format!("no atom {} in the atom block", index);
+        return Err(invalid(reason, number, column, index.to_string()));
+    }
+    Ok(index - 1)
+}
+
+// Radicals as written in the file: 1 singlet, 2 doublet, 3 triplet
+fn radical_electrons(radical: usize) -> usize {
+    match radical {
+        2 => 1,
+        1 | 3 => 2,
+        _ => 0,
+    }
+}
+
+// Atom values with no field of their own in `Atom`
+#[derive(Clone, Default)]
+pub(super) struct AtomExtras {
+    // Line, column and text of the atom symbol, for errors about the atom
+    pub(super) line: usize,
+    pub(super) column: usize,
+    pub(super) symbol: String,
+    pub(super) radical: usize,
+    // The valence column, hydrogens make up the difference
+    pub(super) valence: Option<i32>,
+    // The stereo parity, read only when there are no coordinates
+    pub(super) parity: u8,
+}
+
+// The element of an atom symbol, None for query atoms and R groups
+pub(super) fn symbol_element(symbol: &str) -> Result<Option<&'static Element>, ErrorKind> {
+    match symbol {
+        "*" | "A" | "Q" | "L" | "LP" | "R#" => Ok(None),
+        _ => element_by_symbol(symbol)
+            .map(Some)
+            .ok_or_else(|| ErrorKind::UnknownElement(symbol.to_string())),
+    }
+}
+
+// A bond of type 1 to 4. Aromatic bonds (4) are single until kekulized and
+// make both their atoms aromatic.
+pub(super) fn typed_bond(
+    molecule: &mut Molecule,
+    atom1: usize,
+    atom2: usize,
+    bond_type: u8,
+) -> Result<Bond, String> {
+    if atom1 == atom2 {
+        return Err(format!("bond from atom {} to itself", atom1 + 1));
+    }
+    match bond_type {
+        1..=3 => Ok(Bond::new(atom1, atom2, bond_type as i8)),
+        4 => {
+            molecule.atoms[atom1].aromatic = true;
+            molecule.atoms[atom2].aromatic = true;
+            let mut bond = Bond::new(atom1, atom2, 1);
+            bond.arom = true;
+            Ok(bond)
+        }
+        5..=8 => Err(format!("query bond type {} is not supported", bond_type)),
+        _ => Err(format!("bond type {} out of range", bond_type)),
+    }
+}
+
+fn read_record<R: BufRead>(lines: &mut Lines<R>) -> Result<Option<Molecule>, Error> {
+    let title = loop {
+        match lines.next()? {
+            None => return Ok(None),
+            Some(line) if line.trim() == "$$$$" => continue,
+            Some(line) => break line,
+        }
+    };
+    let record_start = lines.number;
+    // Program and comment lines
+    if lines.next()?.is_none() {
+        // Blank lines after the last record
+        if title.trim().is_empty() {
+            return Ok(None);
+        }
+        return Err(Error::new(
+            ErrorKind::UnexpectedEnd,
+            record_start + 1,
+            0,
+            "",
+        ));
+    }
+    lines.expect()?;
+
+    let mut molecule = Molecule::new();
+    if !title.trim().is_empty() {
+        molecule.set_name(title.trim());
+    }
+
+    let counts = lines.expect()?;
+    let number = lines.number;
+    let version = counts.get(33..39).map_or("", str::trim);
+    let (extras, bond_stereo) = match version {
+        // Old files leave the version out
+        "V2000" | "" => read_v2000_ctab(lines, &counts, &mut molecule)?,
+        "V3000" => read_v3000_ctab(lines, &mut molecule)?,
+        _ => {
+            let reason = format!("unknown molfile version {}", version);
+            return Err(invalid(reason, number, 35, version));
+        }
+    };
+    read_data_items(lines, &mut molecule)?;
+
+    for (atom_idx, extra) in extras.iter().enumerate() {
+        let valence = molecule.explicit_valence(atom_idx);
+        let atom = &molecule.atoms[atom_idx];
+        let h_count = match extra.valence {
+            // An aromatic atom has one more bond once kekulized
+            Some(total) => (total - valence - atom.aromatic as i32).max(0) as usize,
+            None if atom.aromatic => {
+                aromatic_implicit_h_count(atom.element, atom.f_charge, valence)
+                    .saturating_sub(radical_electrons(extra.radical))
+            }
+            None => implicit_h_count(atom.element, atom.f_charge, valence)
+                .saturating_sub(radical_electrons(extra.radical)),
+        };
+        molecule.h_count_update(atom_idx, h_count);
+    }
+    // A failure points at the first atom left without a double bond
+    molecule.kekulize().map_err(|e| {
+        let extra = &extras[e.unmatched[0]];
+        let (line, column, symbol) = (extra.line, extra.column, extra.symbol.clone());
+        Error::new(ErrorKind::Kekulize(e), line, column, symbol)
+    })?;
+    molecule.perceive_rings();
+    molecule.perceive_aromaticity(AromaticityModel::Daylight);
+    molecule.perceive_stereo_from_coords(&bond_stereo);
+    let no_geometry = (molecule.atoms.iter()).all(|a| a.coords_3d == Some((0.0, 0.0, 0.0)));
+    if no_geometry {
+        for (atom_idx, extra) in extras.iter().enumerate() {
+            if extra.parity != 0 {
+                molecule.set_mdl_parity(atom_idx, extra.parity);
+            }
+        }
+    }
+    molecule.perceive_symmetry_classes();
+    Ok(Some(molecule))
+}
+
+// The V2000 atom and bond blocks after the counts line, then the
+// properties block
+fn read_v2000_ctab<R: BufRead>(
+    lines: &mut Lines<R>,
+    counts: &str,
+    molecule: &mut Molecule,
+) -> Result<(Vec<AtomExtras>, Vec<BondStereo>), Error> {
+    let number = lines.number;
+    let num_atoms: usize = field(counts, number, 0..3, None)?;
+    let num_bonds: usize = field(counts, number, 3..6, None)?;
+
+    let mut extras = vec![AtomExtras::default(); num_atoms];
+    for extra in extras.iter_mut() {
+        let line = lines.expect()?;
+        let number = lines.number;
+        let x = field(&line, number, 0..10, None)?;
+        let y = field(&line, number, 10..20, None)?;
+        let z = field(&line, number, 20..30, None)?;
+        let symbol = line.get(31..34).or(line.get(31..)).unwrap_or("").trim();
+        (extra.line, extra.column, extra.symbol) = (number, 32, symbol.to_string());
+        let mass_diff: i32 = field(&line, number, 34..36, Some(0))?;
+        let charge_code: u8 = field(&line, number, 36..39, Some(0))?;
+        // The spec has the parity ignored on reading, it is only used when
+        // there are no coordinates for the stereo to come from
+        extra.parity = field(&line, number, 39..42, Some(0))?;
+        let valence: u8 = field(&line, number, 48..51, Some(0))?;
+        let atom_map = field(&line, number, 60..63, Some(0))?;
+
+        let element =
+            symbol_element(symbol).map_err(|kind| Error::new(kind, number, 32, symbol))?;
+        let charge = match charge_code {
+            0 | 4 => 0,
+            1..=7 => 4 - charge_code as i8,
+            _ => {
+                let reason = format!("charge code {} out of range", charge_code);
+                return Err(invalid(reason, number, 37, charge_code.to_string()));
+            }
+        };
+        if charge_code == 4 {
+            extra.radical = 2;
+        }
+        extra.valence = match valence {
+            0 => None,
+            1..=14 => Some(valence as i32),
+            15 => Some(0),
+            _ => {
+                let reason = format!("valence {} out of range", valence);
+                return Err(invalid(reason, number, 49, valence.to_string()));
+            }
+        };
+        // Mass differences count from the mass in the periodic table
+        let isotope = match element {
+            Some(element) if mass_diff != 0 => {
+                (element.average_mass.round() as i32 + mass_diff).max(0) as usize
+            }
+            _ => 0,
+        };
+
+        let atom = Atom::new(element.map_or(0, |e| e.atomic_number))
+            .with_isotope(isotope)
+            .with_charge(charge)
+            .with_coords((x, y, z))
+            .with_atom_map(atom_map);
+        molecule.add_atom(atom);
+    }
+
+    let mut bond_stereo = Vec::with_capacity(num_bonds);
+    for _ in 0..num_bonds {
+        let line = lines.expect()?;
+        let number = lines.number;
+        let atom1 = atom_index(field(&line, number, 0..3, None)?, num_atoms, number, 1)?;
+        let atom2 = atom_index(field(&line, number, 3..6, None)?, num_atoms, number, 4)?;
+        let bond_type: u8 = field(&line, number, 6..9, None)?;
+        let stereo: u8 = field(&line, number, 9..12, Some(0))?;
+
+        let bond = typed_bond(molecule, atom1, atom2, bond_type)
+            .map_err(|reason| invalid(reason, number, 7, bond_type.to_string()))?;
+        bond_stereo.push(match (bond_type, stereo) {
+            (1, 1) => BondStereo::Wedge,
+            (1, 6) => BondStereo::Hash,
+            (1, 4) | (2, 3) => BondStereo::Either,
+            _ => BondStereo::None,
+        });
+        molecule.add_bond(bond);
+    }
+
+    read_properties_block(lines, molecule, &mut extras)?;
+    Ok((extras, bond_stereo))
+}
+
+// The properties block up to "M  END". `M  CHG`, `M  RAD` and `M  ISO`
+// replace the charges, radicals and mass differences of the atom block.
+fn read_properties_block<R: BufRead>(
+    lines: &mut Lines<R>,
+    molecule: &mut Molecule,
+    extras: &mut [AtomExtras],
+) -> Result<(), Error> {
+    let mut charges_reset = false;
+    let mut isotopes_reset = false;
+    loop {
+        let line = lines.expect()?;
+        let number = lines.number;
+        if line.trim() == "$$$$" || line.starts_with('>') {
+            return Err(Error::new(
+                ErrorKind::Expected("'M  END'"),
+                number,
+                1,
+                line.trim(),
+            ));
+        }
+        let tag = line.get(0..6).unwrap_or(line.as_str());
+        match tag {
+            "M  END" => return Ok(()),
+            "M  CHG" | "M  RAD" | "M  ISO" => {}
+            // Atom aliases and group abbreviations run over two lines
+            _ if tag.starts_with("A  ") || tag.starts_with("G  ") => {
+                lines.expect()?;
+                continue;
+            }
+            "S  SKP" => {
+                let count: usize = field(&line, number, 6..9, None)?;
+                for _ in 0..count {
+                    lines.expect()?;
                 }
+                continue;
+            }
+            _ => continue,
+        }
+
+        if tag != "M  ISO" && !charges_reset {
+            charges_reset = true;
+            for (atom, extra) in molecule.atoms.iter_mut().zip(extras.iter_mut()) {
+                atom.f_charge = 0;
+                extra.radical = 0;
+            }
+        }
+        if tag == "M  ISO" && !isotopes_reset {
+            isotopes_reset = true;
+            for atom in molecule.atoms.iter_mut() {
+                atom.isotope = 0;
+            }
+        }
 
-                if line.starts_with("> ") {
-                    if let Some(_property_name) =
-                        line.split('<').nth(1).and_then(|s| s.split('>').next())
-                    {
-                        let mut property_value = String::new();
-                        while let Some(line) = lines.next() {
-                            let line = line?;
-                            if line.trim().is_empty() {
-                                break;
-                            }
-                            property_value.push_str(&line);
-                        }
+        // "M  CHGnn8 aaa vvv ...": a count, then atom and value pairs
+        let count: usize = field(&line, number, 6..9, None)?;
+        for entry in 0..count {
+            let start = 9 + entry * 8;
+            let index: usize = field(&line, number, start..start + 4, None)?;
+            let atom_idx = atom_index(index, extras.len(), number, start + 1)?;
+            let value: i32 = field(&line, number, start + 4..start + 8, None)?;
+            let out_of_range = || {
+                let reason = format!("{} value {} out of range", &tag[3..], value);
+                invalid(reason, number, start + 5, value.to_string())
+            };
+            match tag {
+                "M  CHG" => {
+                    molecule.atoms[atom_idx].f_charge = i8::try_from(value)
+                        .ok()
+                        .filter(|c| (-15..=15).contains(c))
+                        .ok_or_else(out_of_range)?;
+                }
+                "M  RAD" => {
+                    if !(0..=3).contains(&value) {
+                        return Err(out_of_range());
                     }
+                    extras[atom_idx].radical = value as usize;
+                }
+                _ => {
+                    molecule.atoms[atom_idx].isotope =
+                        usize::try_from(value).map_err(|_| out_of_range())?;
                 }
             }
         }
-        molecules.push(molecule);
     }
-    Ok(molecules)
+}
+
+// Data items up to the end of the record, as strings: SD files have no
+// types, `Property::infer` gives numbers and booleans back
+fn read_data_items<R: BufRead>(lines: &mut Lines<R>, molecule: &mut Molecule) -> Result<(), Error> {
+    while !lines.record_end {
+        let Some(line) = lines.next()? else { break };
+        let Some(property_name) = line
+            .strip_prefix('>')
+            .and_then(|s| s.split('<').nth(1))
+            .and_then(|s| s.split('>').next())
+        else {
+            continue;
+        };
+        let mut value_lines = Vec::new();
+        while let Some(line) = lines.next()? {
+            if lines.record_end || line.trim().is_empty() {
+                break;
+            }
+            value_lines.push(line);
+        }
+        molecule
+            .properties
+            .set(property_name, value_lines.join("\n"));
+    }
+    Ok(())
 }
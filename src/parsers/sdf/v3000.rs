@@ -0,0 +1,425 @@
+// V3000 connection tables: the "M  V30" lines from BEGIN CTAB to END CTAB.
+// A line ending in '-' continues on the next one, and atoms and bonds carry
+// their optional values as KEY=value attributes. Stereo collections become
+// the `STEREO_GROUP` property of their atoms and the SGroup block the
+// `SGROUPS` property of the molecule; other blocks are skipped.
+use std::{collections::HashMap, io::BufRead, str::FromStr};
+
+use crate::{
+    core::{
+        defs::Atom,
+        molecule::Molecule,
+        properties::{SGROUPS, STEREO_GROUP},
+        stereo_perception::BondStereo,
+    },
+    parsers::error::{Error, ErrorKind},
+};
+
+use super::sdf::{invalid, symbol_element, typed_bond, AtomExtras, Lines};
+
+// One V30 line with its continuations joined, and the number of its first line
+fn read_v30_line<R: BufRead>(lines: &mut Lines<R>) -> Result<(String, usize), Error> {
+    let mut text = String::new();
+    let start = lines.number + 1;
+    loop {
+        let line = lines.expect()?;
+        let number = lines.number;
+        let Some(content) = line
+            .strip_prefix("M  V30")
+            .map(|c| c.strip_prefix(' ').unwrap_or(c))
+        else {
+            let kind = ErrorKind::Expected("'M  V30'");
+            return Err(Error::new(kind, number, 1, line.trim()));
+        };
+        let content = content.trim_end();
+        match content.strip_suffix('-') {
+            Some(content) => text.push_str(content),
+            None => {
+                text.push_str(content);
+                return Ok((text.trim().to_string(), start));
+            }
+        }
+    }
+}
+
+// Fields separated by spaces, keeping "quoted text" and (lists) whole
+fn tokens(text: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+    let mut depth = 0;
+    let mut quoted = false;
+    for (i, c) in text.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            '(' if !quoted => depth += 1,
+            ')' if !quoted && depth > 0 => depth -= 1,
+            ' ' if !quoted && depth == 0 => {
+                if let Some(s) = start.take() {
+                    tokens.push(&text[s..i]);
+                }
+                continue;
+            }
+            _ => {}
+        }
+        start.get_or_insert(i);
+    }
+    if let Some(s) = start {
+        tokens.push(&text[s..]);
+    }
+    tokens
+}
+
+fn parse<T: FromStr>(token: &str, line: usize) -> Result<T, Error> {
+    token
+        .parse()
+        .map_err(|_| Error::new(ErrorKind::InvalidNumber, line, 0, token))
+}
+
+// "(n a b ...)": the n values of a list attribute
+fn list(token: &str, line: usize) -> Result<Vec<usize>, Error> {
+    let values = token
+        .strip_prefix('(')
+        .and_then(|t| t.strip_suffix(')'))
+        .ok_or_else(|| Error::new(ErrorKind::Expected("'('"), line, 0, token))?;
+    let mut values = values.split_whitespace();
+    let count: usize = parse(values.next().unwrap_or(""), line)?;
+    let values = values
+        .map(|v| parse(v, line))
+        .collect::<Result<Vec<usize>, Error>>()?;
+    if values.len() != count {
+        let reason = format!("list of {} values holds {}", count, values.len());
+        return Err(invalid(reason, line, 0, token));
+    }
+    Ok(values)
+}
+
+/// Reads a V3000 connection table into `molecule`, from the line after the
+/// counts line up to "M  END".
+pub(super) fn read_v3000_ctab<R: BufRead>(
+    lines: &mut Lines<R>,
+    molecule: &mut Molecule,
+) -> Result<(Vec<AtomExtras>, Vec<BondStereo>), Error> {
+    let (line, number) = read_v30_line(lines)?;
+    if line != "BEGIN CTAB" {
+        return Err(Error::new(
+            ErrorKind::Expected("'BEGIN CTAB'"),
+            number,
+            0,
+            line,
+        ));
+    }
+
+    let mut counts = None;
+    // Atoms and bonds are numbered freely in V3000, these map them to indices
+    let mut atom_indices = HashMap::new();
+    let mut bond_indices = HashMap::new();
+    let mut sgroups = Vec::new();
+    let mut extras = Vec::new();
+    let mut bond_stereo = Vec::new();
+    loop {
+        let (line, number) = read_v30_line(lines)?;
+        match tokens(&line).as_slice() {
+            ["COUNTS", num_atoms, num_bonds, ..] => {
+                let num_atoms: usize = parse(num_atoms, number)?;
+                let num_bonds: usize = parse(num_bonds, number)?;
+                counts = Some((num_atoms, num_bonds, number));
+            }
+            ["BEGIN", "ATOM"] => {
+                read_atoms(lines, molecule, &mut atom_indices, &mut extras)?;
+            }
+            ["BEGIN", "BOND"] => {
+                read_bonds(
+                    lines,
+                    molecule,
+                    &atom_indices,
+                    &mut bond_indices,
+                    &mut bond_stereo,
+                )?;
+            }
+            ["BEGIN", "SGROUP"] => read_sgroups(lines, &mut sgroups)?,
+            ["BEGIN", "COLLECTION"] => read_collections(lines, molecule, &atom_indices)?,
+            // 3D features and anything else
+            ["BEGIN", _] => skip_block(lines)?,
+            ["END", "CTAB"] => break,
+            _ => {
+                let reason = "unexpected line in the connection table".to_string();
+                return Err(invalid(reason, number, 0, line));
+            }
+        }
+    }
+
+    let Some((num_atoms, num_bonds, number)) = counts else {
+        return Err(Error::new(
+            ErrorKind::Expected("'COUNTS'"),
+            lines.number,
+            0,
+            "",
+        ));
+    };
+    if num_atoms != molecule.atoms.len() || num_bonds != molecule.bonds.len() {
+        let reason = format!(
+            "counts of {} atoms and {} bonds, the blocks hold {} and {}",
+            num_atoms,
+            num_bonds,
+            molecule.atoms.len(),
+            molecule.bonds.len()
+        );
+        return Err(invalid(reason, number, 0, ""));
+    }
+    if !sgroups.is_empty() {
+        let sgroups = sgroups
+            .iter()
+            .map(|(line, number)| renumber_sgroup(line, *number, &atom_indices, &bond_indices))
+            .collect::<Result<Vec<String>, Error>>()?;
+        molecule.properties.set(SGROUPS, sgroups.join("\n"));
+    }
+
+    // R-group definitions and the like up to the end of the molfile
+    loop {
+        let line = lines.expect()?;
+        if line.trim_end() == "M  END" {
+            return Ok((extras, bond_stereo));
+        }
+        if line.trim() == "$$$$" || line.starts_with('>') {
+            let kind = ErrorKind::Expected("'M  END'");
+            return Err(Error::new(kind, lines.number, 1, line.trim()));
+        }
+    }
+}
+
+// "index type x y z aamap [KEY=value ...]" lines up to END ATOM
+fn read_atoms<R: BufRead>(
+    lines: &mut Lines<R>,
+    molecule: &mut Molecule,
+    atom_indices: &mut HashMap<usize, usize>,
+    extras: &mut Vec<AtomExtras>,
+) -> Result<(), Error> {
+    loop {
+        let (line, number) = read_v30_line(lines)?;
+        let fields = tokens(&line);
+        let [index, symbol, x, y, z, atom_map, attributes @ ..] = fields.as_slice() else {
+            if line == "END ATOM" {
+                return Ok(());
+            }
+            return Err(Error::new(ErrorKind::UnexpectedEnd, number, 0, line));
+        };
+        if symbol.starts_with('[') || *symbol == "NOT" {
+            let reason = "atom lists are not supported".to_string();
+            return Err(invalid(reason, number, 0, *symbol));
+        }
+        let element =
+            symbol_element(symbol).map_err(|kind| Error::new(kind, number, 0, *symbol))?;
+        let mut atom = Atom::new(element.map_or(0, |e| e.atomic_number))
+            .with_coords((parse(x, number)?, parse(y, number)?, parse(z, number)?))
+            .with_atom_map(parse(atom_map, number)?);
+        // After "M  V30 ", symbols come early enough not to be continued
+        let offset = symbol.as_ptr() as usize - line.as_ptr() as usize;
+        let mut extra = AtomExtras {
+            line: number,
+            column: 8 + offset,
+            symbol: symbol.to_string(),
+            ..AtomExtras::default()
+        };
+
+        for attribute in attributes {
+            let Some((key, value)) = attribute.split_once('=') else {
+                continue;
+            };
+            let out_of_range = || {
+                let reason = format!("{} value {} out of range", key, value);
+                invalid(reason, number, 0, *attribute)
+            };
+            match key {
+                "CHG" => {
+                    atom.f_charge = parse(value, number)?;
+                }
+                "RAD" => {
+                    extra.radical = parse(value, number)?;
+                    if extra.radical > 3 {
+                        return Err(out_of_range());
+                    }
+                }
+                "MASS" => atom.isotope = parse(value, number)?,
+                "VAL" => {
+                    extra.valence = match parse(value, number)? {
+                        0 => None,
+                        -1 => Some(0),
+                        valence @ 1..=14 => Some(valence),
+                        _ => return Err(out_of_range()),
+                    }
+                }
+                // The atom parity, used as in V2000
+                "CFG" => extra.parity = parse(value, number)?,
+                // HCOUNT and the other query attributes do not apply
+                _ => {}
+            }
+        }
+
+        let index: usize = parse(index, number)?;
+        if atom_indices.insert(index, molecule.atoms.len()).is_some() {
+            let reason = format!("atom {} defined twice", index);
+            return Err(invalid(reason, number, 0, index.to_string()));
+        }
+        molecule.add_atom(atom);
+        extras.push(extra);
+    }
+}
+
+// "index type atom1 atom2 [KEY=value ...]" lines up to END BOND
+fn read_bonds<R: BufRead>(
+    lines: &mut Lines<R>,
+    molecule: &mut Molecule,
+    atom_indices: &HashMap<usize, usize>,
+    bond_indices: &mut HashMap<usize, usize>,
+    bond_stereo: &mut Vec<BondStereo>,
+) -> Result<(), Error> {
+    loop {
+        let (line, number) = read_v30_line(lines)?;
+        let fields = tokens(&line);
+        let [index, bond_type, atom1, atom2, attributes @ ..] = fields.as_slice() else {
+            if line == "END BOND" {
+                return Ok(());
+            }
+            return Err(Error::new(ErrorKind::UnexpectedEnd, number, 0, line));
+        };
+        let atom_index = |token: &str| {
+            let index: usize = parse(token, number)?;
+            atom_indices.get(&index).copied().ok_or_else(|| {
+                let reason = format!("no atom {} in the atom block", index);
+                invalid(reason, number, 0, token)
+            })
+        };
+        let (atom1, atom2) = (atom_index(atom1)?, atom_index(atom2)?);
+        let bond_type: u8 = parse(bond_type, number)?;
+        let bond = typed_bond(molecule, atom1, atom2, bond_type)
+            .map_err(|reason| invalid(reason, number, 0, line.as_str()))?;
+
+        let mut stereo = BondStereo::None;
+        for attribute in attributes {
+            if let Some(value) = attribute.strip_prefix("CFG=") {
+                stereo = match (bond_type, parse(value, number)?) {
+                    (1, 1) => BondStereo::Wedge,
+                    (1, 3) => BondStereo::Hash,
+                    (1 | 2, 2) => BondStereo::Either,
+                    _ => BondStereo::None,
+                };
+            }
+        }
+        let index: usize = parse(index, number)?;
+        if bond_indices.insert(index, molecule.bonds.len()).is_some() {
+            let reason = format!("bond {} defined twice", index);
+            return Err(invalid(reason, number, 0, index.to_string()));
+        }
+        bond_stereo.push(stereo);
+        molecule.add_bond(bond);
+    }
+}
+
+// SGroup lines up to END SGROUP, with their line numbers
+fn read_sgroups<R: BufRead>(
+    lines: &mut Lines<R>,
+    sgroups: &mut Vec<(String, usize)>,
+) -> Result<(), Error> {
+    loop {
+        let (line, number) = read_v30_line(lines)?;
+        if line == "END SGROUP" {
+            return Ok(());
+        }
+        sgroups.push((line, number));
+    }
+}
+
+// An SGroup line with its atoms and bonds numbered from 1 by index: the
+// ATOMS, PATOMS, XBONDS and CBONDS lists, the bond of CSTATE and the atoms
+// of SAP. Everything else is kept as written.
+fn renumber_sgroup(
+    line: &str,
+    number: usize,
+    atom_indices: &HashMap<usize, usize>,
+    bond_indices: &HashMap<usize, usize>,
+) -> Result<String, Error> {
+    let mut fields = Vec::new();
+    for field in tokens(line) {
+        let Some((key, value)) = field.split_once('=') else {
+            fields.push(field.to_string());
+            continue;
+        };
+        let (indices, what, renumbered) = match key {
+            "ATOMS" | "PATOMS" => (atom_indices, "atom", usize::MAX),
+            "XBONDS" | "CBONDS" => (bond_indices, "bond", usize::MAX),
+            "CSTATE" => (bond_indices, "bond", 1),
+            "SAP" => (atom_indices, "atom", 2),
+            _ => {
+                fields.push(field.to_string());
+                continue;
+            }
+        };
+        let values = value
+            .strip_prefix('(')
+            .and_then(|v| v.strip_suffix(')'))
+            .ok_or_else(|| Error::new(ErrorKind::Expected("'('"), number, 0, field))?;
+        let mut values: Vec<String> = values.split_whitespace().map(String::from).collect();
+        // The count, then the values; a leaving atom of 0 in SAP is none
+        for value in values.iter_mut().skip(1).take(renumbered) {
+            let index: usize = parse(value, number)?;
+            if index == 0 && key == "SAP" {
+                continue;
+            }
+            let &idx = indices.get(&index).ok_or_else(|| {
+                let reason = format!("no {} {} in the {} block", what, index, what);
+                invalid(reason, number, 0, field)
+            })?;
+            *value = (idx + 1).to_string();
+        }
+        fields.push(format!("{}=({})", key, values.join(" ")));
+    }
+    Ok(fields.join(" "))
+}
+
+// "MDLV30/STEABS ATOMS=(n ...)" and the racemic (STERACn) and relative
+// (STERELn) groups; other collections are skipped
+fn read_collections<R: BufRead>(
+    lines: &mut Lines<R>,
+    molecule: &mut Molecule,
+    atom_indices: &HashMap<usize, usize>,
+) -> Result<(), Error> {
+    loop {
+        let (line, number) = read_v30_line(lines)?;
+        if line == "END COLLECTION" {
+            return Ok(());
+        }
+        let fields = tokens(&line);
+        let group = match fields.first().and_then(|f| f.strip_prefix("MDLV30/")) {
+            Some("STEABS") => "abs".to_string(),
+            Some(name) if name.starts_with("STERAC") => format!("and{}", &name[6..]),
+            Some(name) if name.starts_with("STEREL") => format!("or{}", &name[6..]),
+            _ => continue,
+        };
+        let Some(atoms) = fields.iter().find_map(|f| f.strip_prefix("ATOMS=")) else {
+            continue;
+        };
+        for index in list(atoms, number)? {
+            let &atom_idx = atom_indices.get(&index).ok_or_else(|| {
+                let reason = format!("no atom {} in the atom block", index);
+                invalid(reason, number, 0, atoms)
+            })?;
+            molecule.atoms[atom_idx]
+                .properties
+                .set(STEREO_GROUP, group.as_str());
+        }
+    }
+}
+
+// Lines up to the END matching a BEGIN just read, nested blocks included
+fn skip_block<R: BufRead>(lines: &mut Lines<R>) -> Result<(), Error> {
+    let mut depth = 1;
+    while depth > 0 {
+        let (line, _) = read_v30_line(lines)?;
+        if line.starts_with("BEGIN ") {
+            depth += 1;
+        } else if line.starts_with("END ") {
+            depth -= 1;
+        }
+    }
+    Ok(())
+}
@@ -1,2 +1,5 @@
+mod test_calc;
+mod test_core;
 mod test_fingerprints;
 mod test_parsers;
+mod test_writers;
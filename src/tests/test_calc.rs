@@ -0,0 +1,82 @@
+#[cfg(test)]
+use crate::{
+    calc::formula::{parse_formula, Formula},
+    parsers::{daylight::smiles::parse_smiles, error::ErrorKind},
+};
+
+#[test]
+fn test_formula_and_mass() {
+    let ethanol = parse_smiles("CCO").unwrap();
+    assert_eq!(ethanol.molecular_formula(), "C2H6O");
+    assert!((ethanol.molecular_weight() - 46.069).abs() < 1e-2);
+    assert!((ethanol.exact_mass() - 46.041865).abs() < 1e-5);
+
+    // Hill order puts C and H first only when carbon is present
+    assert_eq!(
+        parse_smiles("c1ccccc1C(=O)[O-]")
+            .unwrap()
+            .molecular_formula(),
+        "C7H5O2-"
+    );
+    assert_eq!(parse_smiles("[NH4+]").unwrap().molecular_formula(), "H4N+");
+    assert_eq!(
+        parse_smiles("OS(=O)(=O)O").unwrap().molecular_formula(),
+        "H2O4S"
+    );
+    assert_eq!(parse_smiles("[O-2]").unwrap().molecular_formula(), "O-2");
+
+    // Labelled atoms are listed and weighed separately
+    let labelled = parse_smiles("[13CH4]").unwrap();
+    assert_eq!(labelled.molecular_formula(), "[13C]H4");
+    let methane = parse_smiles("C").unwrap();
+    assert!((labelled.exact_mass() - methane.exact_mass() - 1.003355).abs() < 1e-5);
+    // Tracers have exact masses too, not their mass numbers
+    let tritiated = parse_smiles("[3H]C([3H])[3H]").unwrap();
+    assert!((tritiated.exact_mass() - 22.055973).abs() < 1e-5);
+    for (smiles, shift) in [("[14CH4]", 2.003242), ("[11CH4]", -0.988566)] {
+        let labelled = parse_smiles(smiles).unwrap();
+        assert!((labelled.exact_mass() - methane.exact_mass() - shift).abs() < 1e-5);
+    }
+    let fluoride = parse_smiles("[18F-]").unwrap();
+    assert!((fluoride.exact_mass() - 18.001486).abs() < 1e-5);
+    let technetium = parse_smiles("[99Tc]").unwrap();
+    assert!((technetium.exact_mass() - 98.906251).abs() < 1e-5);
+    let mixed = parse_smiles("CC[13CH3]").unwrap();
+    assert_eq!(mixed.molecular_formula(), "C2[13C]H8");
+
+    // Cations lose an electron mass
+    let ammonium = parse_smiles("[NH4+]").unwrap();
+    assert!((ammonium.exact_mass() - 18.033826).abs() < 1e-5);
+
+    let glucose = parse_formula("C6H12O6").unwrap();
+    assert_eq!(glucose.count(6), 6);
+    assert_eq!(glucose.count(1), 12);
+    assert_eq!(glucose.count(8), 6);
+    assert_eq!(glucose.to_string(), "C6H12O6");
+
+    let hydroxide: Formula = "Ca(OH)2".parse().unwrap();
+    assert_eq!(hydroxide.to_string(), "CaH2O2");
+    let ion = parse_formula("C5[13C]H7O2-").unwrap();
+    assert_eq!(ion.charge, -1);
+    assert_eq!(ion.count(6), 6);
+    assert_eq!(ion.counts[&(6, 13)], 1);
+    assert_eq!(parse_formula("Fe+3").unwrap().charge, 3);
+    assert_eq!(parse_formula(&ion.to_string()).unwrap(), ion);
+
+    assert!(parse_formula("C6H12O6x").is_err());
+    assert!(parse_formula("Ca(OH2").is_err());
+    assert!(parse_formula("[13C").is_err());
+
+    // Counts and charges too large for their types are errors, not C1
+    for (formula, column, token) in [
+        ("C99999999999999999999", 2, "99999999999999999999"),
+        ("C-99999999999", 3, "99999999999"),
+        ("(C9999999999)9999999999", 14, "9999999999"),
+        ("C18446744073709551615C", 22, "C"),
+        ("[13C]18446744073709551615[13C]2", 26, "[13C]2"),
+    ] {
+        let error = parse_formula(formula).unwrap_err();
+        assert_eq!(error.kind, ErrorKind::InvalidNumber, "{}", formula);
+        assert_eq!((error.column, error.token.as_str()), (column, token));
+    }
+}
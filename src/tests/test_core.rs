@@ -0,0 +1,903 @@
+#[cfg(test)]
+use crate::core::mendeleev::{element, element_by_symbol, symbol, ELEMENTS};
+
+#[test]
+fn test_element_lookup() {
+    assert_eq!(ELEMENTS.len(), 118);
+    for (i, e) in ELEMENTS.iter().enumerate() {
+        assert_eq!(e.atomic_number, i + 1);
+        assert_eq!(element_by_symbol(e.symbol).unwrap().atomic_number, i + 1);
+    }
+
+    let chlorine = element_by_symbol("Cl").unwrap();
+    assert_eq!(chlorine.atomic_number, 17);
+    assert_eq!(chlorine.name, "Chlorine");
+    assert_eq!(chlorine.valences, &[1]);
+    assert!(element_by_symbol("CL").is_none());
+    assert!(element_by_symbol("Xx").is_none());
+
+    assert_eq!(symbol(6), "C");
+    assert_eq!(symbol(0), "*");
+    assert!(element(0).is_none());
+    assert!(element(119).is_none());
+}
+
+#[test]
+fn test_isotopes() {
+    // Abundance weighted isotope masses should reproduce the standard weight
+    for e in ELEMENTS.iter().filter(|e| !e.isotopes.is_empty()) {
+        let total: f64 = e.isotopes.iter().map(|i| i.abundance).sum();
+        let avg: f64 = e.isotopes.iter().map(|i| i.mass * i.abundance).sum::<f64>() / total;
+        assert!((total - 1.0).abs() < 2e-3, "{} abundances", e.symbol);
+        assert!((avg - e.average_mass).abs() < 2e-2, "{} mass", e.symbol);
+    }
+
+    let carbon = element(6).unwrap();
+    assert_eq!(carbon.monoisotopic_mass, 12.0);
+    assert_eq!(carbon.most_abundant_isotope().unwrap().mass_number, 12);
+    assert!((carbon.isotope(13).unwrap().abundance - 0.0107).abs() < 1e-9);
+    assert!(carbon.isotope(14).is_none());
+    assert!(element(43).unwrap().isotopes.is_empty());
+}
+
+#[cfg(test)]
+use crate::parsers::daylight::smiles::parse_smiles;
+
+#[test]
+fn test_neighbor_api() {
+    // Isobutanol, atom 1 is the branch point
+    let mol = parse_smiles("CC(C)CO").unwrap();
+    assert_eq!(mol.degree(1), 3);
+    assert_eq!(mol.neighbors(1).collect::<Vec<_>>(), vec![0, 2, 3]);
+    assert_eq!(mol.bonds_of(4).len(), 1);
+    assert_eq!(mol.degree(4), 1);
+
+    let bond = mol.bond_between(3, 1).unwrap();
+    assert_eq!(mol.bond_between(1, 3), Some(bond));
+    assert!(mol.bond_between(0, 2).is_none());
+    assert_eq!(mol.get_bond(3, 4).unwrap().bond_order, 1);
+
+    // Rebuilding from the raw bond vector gives the same adjacency
+    let mut rebuilt = mol.clone();
+    for atom in rebuilt.atoms.iter_mut() {
+        atom.outgoing_bond.clear();
+    }
+    rebuilt.rebuild_bond_lists();
+    for atom_idx in 0..mol.atoms.len() {
+        assert_eq!(rebuilt.bonds_of(atom_idx), mol.bonds_of(atom_idx));
+    }
+}
+
+#[cfg(test)]
+use crate::core::{
+    defs::{Atom, Axialness, Bond},
+    properties::PropertyMap,
+};
+
+#[test]
+fn test_ring_perception() {
+    // Naphthalene: two fused six rings sharing one bond
+    let mol = parse_smiles("c1ccc2ccccc2c1").unwrap();
+    let rings = mol.ring_info().unwrap();
+    assert_eq!(rings.num_rings(), 2);
+    assert!(rings.sssr().iter().all(|r| r.len() == 6));
+    assert_eq!(rings.relevant_cycles().len(), 2);
+    assert_eq!(rings.atom_ring_count(3), 2);
+    assert_eq!(rings.atom_ring_count(0), 1);
+    assert_eq!(rings.ring_connectivity(3), 3);
+    assert_eq!(rings.bond_ring_count(mol.bond_between(3, 8).unwrap()), 2);
+    assert_eq!(rings.ring_systems(), vec![vec![0, 1]]);
+    assert!(mol.atoms.iter().all(|a| a.ring));
+
+    // Spiro rings share an atom but are separate systems, the chain is not a ring
+    let mol = parse_smiles("C1CCC2(C1)CCC2CC").unwrap();
+    let rings = mol.ring_info().unwrap();
+    assert_eq!(rings.num_rings(), 2);
+    assert_eq!(rings.ring_systems().len(), 2);
+    assert_eq!(rings.atom_ring_count(3), 2);
+    assert_eq!(rings.smallest_ring_size(3), Some(4));
+    assert!(rings.is_atom_in_ring_of_size(3, 5));
+    assert!(!mol.atoms[9].ring && !mol.atoms[8].ring);
+    assert!(!mol.bonds[mol.bond_between(7, 8).unwrap()].ring);
+
+    // Cubane: the SSSR holds five of the six faces, the relevant cycles all six
+    let mut mol = parse_smiles("CCCCCCCC").unwrap();
+    for (source, dest) in [(3, 0), (7, 4), (0, 7), (1, 6), (2, 5)] {
+        mol.add_bond(Bond {
+            source,
+            dest,
+            arom: false,
+            ring: false,
+            bond_order: 1,
+            axialness: Axialness::UNKNOWN,
+            properties: PropertyMap::new(),
+        });
+    }
+    mol.perceive_rings();
+    let rings = mol.ring_info().unwrap();
+    assert_eq!(rings.num_rings(), 5);
+    assert_eq!(rings.relevant_cycles().len(), 6);
+    assert!(rings.relevant_cycles().iter().all(|r| r.len() == 4));
+    assert!((0..8).all(|a| rings.smallest_ring_size(a) == Some(4)));
+    assert!(mol.bonds.iter().all(|b| b.ring));
+}
+
+#[cfg(test)]
+use crate::{core::kekulize::KekulizeError, parsers::error::ErrorKind};
+
+#[test]
+fn test_kekulize() {
+    let double_bonds = |smiles: &str| {
+        let mol = parse_smiles(smiles).unwrap();
+        mol.bonds.iter().filter(|b| b.bond_order == 2).count()
+    };
+    assert_eq!(double_bonds("c1ccccc1"), 3);
+    assert_eq!(double_bonds("c1ccc2ccccc2c1"), 5);
+    // Pyrrole nitrogen and furan oxygen keep their lone pair
+    assert_eq!(double_bonds("c1cc[nH]c1"), 2);
+    assert_eq!(double_bonds("c1ccoc1"), 2);
+    // Caffeine: the carbonyls take the ring carbons out of the matching
+    assert_eq!(double_bonds("Cn1cnc2c1c(=O)n(C)c(=O)n2C"), 4);
+
+    // Every atom in a Kekulé benzene has exactly one double bond
+    let benzene = parse_smiles("c1ccccc1").unwrap();
+    for atom_idx in 0..6 {
+        let doubles = benzene
+            .bonds_of(atom_idx)
+            .iter()
+            .filter(|&&b| benzene.bonds[b].bond_order == 2)
+            .count();
+        assert_eq!(doubles, 1);
+        assert!(benzene.atoms[atom_idx].aromatic);
+    }
+
+    // Kekulizing again keeps the same bond orders
+    let mut again = benzene.clone();
+    again.kekulize().unwrap();
+    assert!(again.bonds == benzene.bonds);
+
+    // Pyrrole written without its hydrogen has no Kekulé structure
+    match parse_smiles("c1ccnc1").map_err(|e| e.kind) {
+        Err(ErrorKind::Kekulize(KekulizeError { unmatched })) => assert_eq!(unmatched, vec![4]),
+        other => panic!("expected a kekulization error, got {:?}", other.err()),
+    }
+    assert!(parse_smiles("c1cccccc1").is_err());
+    // The error points at that atom
+    let error = parse_smiles("CC.c1ccnc1").err().unwrap();
+    assert_eq!(
+        (error.line, error.column, error.token.as_str()),
+        (1, 9, "c")
+    );
+    let error = parse_smiles("c1ccc[cH]1").err().unwrap();
+    assert_eq!((error.column, error.token.as_str()), (6, "[cH]"));
+}
+
+#[cfg(test)]
+use crate::core::aromaticity::AromaticityModel;
+
+#[test]
+fn test_aromaticity_models() {
+    let aromatic_atoms = |smiles: &str, model: AromaticityModel| {
+        let mut mol = parse_smiles(smiles).unwrap();
+        mol.perceive_aromaticity(model);
+        mol.atoms.iter().filter(|a| a.aromatic).count()
+    };
+
+    // Kekulé and aromatic input end up the same
+    let kekule = parse_smiles("C1=CC=CC=C1").unwrap();
+    assert!(kekule.atoms.iter().all(|a| a.aromatic));
+    assert!(kekule.bonds.iter().all(|b| b.arom));
+
+    for model in [
+        AromaticityModel::Daylight,
+        AromaticityModel::Mdl,
+        AromaticityModel::Simple,
+    ] {
+        assert_eq!(aromatic_atoms("c1ccc2ccccc2c1", model), 10);
+        assert_eq!(aromatic_atoms("c1ccncc1", model), 6);
+        assert_eq!(aromatic_atoms("C1=CCC=C1", model), 0);
+        assert_eq!(aromatic_atoms("O=C1C=CC(=O)C=C1", model), 0);
+    }
+
+    // Lone pairs only count outside the MDL model
+    assert_eq!(aromatic_atoms("c1cc[nH]c1", AromaticityModel::Daylight), 5);
+    assert_eq!(aromatic_atoms("c1cc[nH]c1", AromaticityModel::Simple), 5);
+    assert_eq!(aromatic_atoms("c1cc[nH]c1", AromaticityModel::Mdl), 0);
+
+    // Exocyclic C=O is Daylight only
+    assert_eq!(
+        aromatic_atoms("O=c1cccc[nH]1", AromaticityModel::Daylight),
+        6
+    );
+    assert_eq!(aromatic_atoms("O=c1cccc[nH]1", AromaticityModel::Simple), 0);
+
+    // Azulene is aromatic as a 10 electron envelope, outside 5/6 rings
+    let azulene = "C1=CC2=CC=CC=CC2=C1";
+    assert_eq!(aromatic_atoms(azulene, AromaticityModel::Daylight), 10);
+    assert_eq!(aromatic_atoms(azulene, AromaticityModel::Mdl), 10);
+    assert_eq!(aromatic_atoms(azulene, AromaticityModel::Simple), 0);
+
+    // Biphenyl: the bond between the rings stays non aromatic
+    let biphenyl = parse_smiles("c1ccccc1-c1ccccc1").unwrap();
+    let link = biphenyl.bond_between(5, 6).unwrap();
+    assert!(!biphenyl.bonds[link].arom);
+}
+
+#[cfg(test)]
+use crate::core::valence::{allowed_valences, implicit_h_count};
+
+#[test]
+fn test_valence_model() {
+    assert_eq!(allowed_valences(7, 0), &[3, 5]);
+    // Charged p-block atoms take their isoelectronic neighbour's valences
+    assert_eq!(allowed_valences(7, 1), &[4]);
+    assert_eq!(allowed_valences(8, -1), &[1]);
+    assert_eq!(allowed_valences(6, 1), &[3]);
+    assert!(allowed_valences(26, 2).is_empty());
+    assert!(allowed_valences(3, 1).is_empty());
+
+    assert_eq!(implicit_h_count(7, 1, 0), 4);
+    assert_eq!(implicit_h_count(6, -1, 1), 2);
+    assert_eq!(implicit_h_count(16, 0, 3), 1);
+    assert_eq!(implicit_h_count(16, 0, 7), 0);
+
+    // Explicit hydrogen atoms count towards H but are not implicit
+    let methanol = parse_smiles("[H]OC").unwrap();
+    assert_eq!(methanol.explicit_h_count(1), 1);
+    assert_eq!(methanol.implicit_h_count(1), 0);
+    assert_eq!(methanol.total_h_count(1), 1);
+    assert_eq!(methanol.total_h_count(2), 3);
+    assert_eq!(methanol.total_valence(1), 2);
+    assert_eq!(methanol.total_connections(2), 4);
+
+    let sulfone = parse_smiles("CS(=O)(=O)C").unwrap();
+    assert_eq!(sulfone.total_valence(1), 6);
+    assert_eq!(sulfone.default_h_count(1), 0);
+
+    // Kekulé bond orders give aromatic atoms their real valence
+    let pyridine = parse_smiles("c1ccncc1").unwrap();
+    assert_eq!(pyridine.total_valence(0), 4);
+    assert_eq!(pyridine.total_valence(3), 3);
+    let mut reassigned = pyridine.clone();
+    reassigned.assign_implicit_hydrogens();
+    for atom_idx in 0..6 {
+        assert_eq!(
+            reassigned.atoms[atom_idx].hydrogens,
+            pyridine.atoms[atom_idx].hydrogens
+        );
+    }
+
+    assert_eq!(parse_smiles("[NH4+]").unwrap().default_h_count(0), 4);
+    assert_eq!(parse_smiles("C[O-]").unwrap().atoms[1].hydrogens, 0);
+}
+
+#[test]
+fn test_editing() {
+    // Remove the chlorine from 2-chloropropanol
+    let mut mol = parse_smiles("CC(Cl)CO").unwrap();
+    let map = mol.remove_atom(2);
+    assert_eq!(map.atoms, vec![Some(0), Some(1), None, Some(2), Some(3)]);
+    assert_eq!(map.bond(1), None);
+    assert_eq!(mol.atoms.len(), 4);
+    assert_eq!(mol.bonds.len(), 3);
+    assert_eq!(mol.neighbors(1).collect::<Vec<_>>(), vec![0, 2]);
+    for (bond_idx, bond) in mol.bonds.iter().enumerate() {
+        assert!(mol.bonds_of(bond.source).contains(&bond_idx));
+        assert!(mol.bonds_of(bond.dest).contains(&bond_idx));
+    }
+    // Hydrogen counts are left for the caller to fix up
+    assert_eq!(mol.mol_to_smiles(false), "C[CH]CO");
+    mol.h_count_update(1, mol.default_h_count(1));
+    assert_eq!(mol.mol_to_smiles(false), "CCCO");
+
+    // Field edits keep indices
+    mol.set_bond_order(mol.bond_between(2, 3).unwrap(), 2);
+    mol.set_element(3, 7);
+    mol.set_charge(3, 1);
+    mol.set_isotope(0, 13);
+    mol.assign_implicit_hydrogens();
+    assert_eq!(mol.mol_to_smiles(false), "[13CH3]CC=[NH2+]");
+
+    let mut replacement = mol.atoms[1].clone();
+    replacement.outgoing_bond.clear();
+    replacement.element = 14;
+    mol.replace_atom(1, replacement);
+    assert_eq!(mol.degree(1), 2);
+
+    // Batch edits: opening a ring keeps the ring cache in step
+    let mut mol = parse_smiles("C1CCCCC1Br").unwrap();
+    assert_eq!(mol.ring_info().unwrap().num_rings(), 1);
+    let ring_bond = mol.bond_between(0, 5).unwrap();
+    let mut edit = mol.edit();
+    edit.remove_bond(ring_bond).remove_atom(6);
+    let added = edit.add_atom(mol_atom(17));
+    let map = edit.commit();
+    assert_eq!(map.atom(added), Some(6));
+    assert_eq!(map.atom(6), None);
+    assert_eq!(map.bond(ring_bond), None);
+    assert_eq!(mol.atoms.len(), 7);
+    assert_eq!(mol.ring_info().unwrap().num_rings(), 0);
+    assert!(mol.atoms.iter().all(|a| !a.ring));
+
+    // Bonds added to an atom removed in the same batch are dropped
+    let mut mol = parse_smiles("CCO").unwrap();
+    let mut edit = mol.edit();
+    edit.remove_atom(2);
+    let chlorine = edit.add_atom(mol_atom(17));
+    let dropped = edit.add_bond(Bond::new(2, chlorine, 1));
+    let kept = edit.add_bond(Bond::new(1, chlorine, 1));
+    let map = edit.commit();
+    assert_eq!(map.bond(dropped), None);
+    assert_eq!(map.bond(kept), Some(1));
+    assert_eq!(mol.neighbors(2).collect::<Vec<_>>(), vec![1]);
+
+    // Removing a neighbour drops the stereocentre's parity
+    let mut mol = parse_smiles("C[C@H](O)CC").unwrap();
+    mol.remove_atom(2);
+    assert!(mol.atoms[1].configuration.is_none());
+}
+
+#[cfg(test)]
+fn mol_atom(element: usize) -> crate::core::defs::Atom {
+    crate::core::defs::Atom::new(element)
+}
+
+#[cfg(test)]
+use crate::core::{fragments::FragmentCriterion, molecule::Molecule};
+
+#[test]
+fn test_fragments() {
+    // Sodium benzoate with a water
+    let mol = parse_smiles("[O-]C(=O)c1ccccc1.[Na+].O").unwrap();
+    assert_eq!(mol.num_components(), 3);
+    assert_eq!(
+        mol.connected_components(),
+        vec![0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 2]
+    );
+
+    let fragments = mol.split_fragments();
+    assert_eq!(fragments.len(), 3);
+    assert_eq!(
+        fragments[0].molecule.mol_to_smiles(false),
+        "[O-]C(=O)c1ccccc1"
+    );
+    assert_eq!(fragments[1].molecule.mol_to_smiles(false), "[Na+]");
+    assert_eq!(fragments[2].atom(10), Some(0));
+    assert_eq!(fragments[2].atom(0), None);
+    assert_eq!(fragments[0].bond(8), Some(8));
+    assert_eq!(fragments[1].bond(0), None);
+    assert_eq!(fragments[0].molecule.ring_info().unwrap().num_rings(), 1);
+
+    let largest = mol
+        .largest_fragment(FragmentCriterion::HeavyAtomCount)
+        .unwrap();
+    assert_eq!(largest.molecule.atoms.len(), 9);
+
+    // Sodium outweighs water, but a custom score can prefer anything
+    let salt = parse_smiles("O.[Na+]").unwrap();
+    let heaviest = salt
+        .largest_fragment(FragmentCriterion::MolecularWeight)
+        .unwrap();
+    assert_eq!(heaviest.molecule.atoms[0].element, 11);
+    let most_h = |m: &Molecule| m.atoms[0].hydrogens as f64;
+    let custom = salt
+        .largest_fragment(FragmentCriterion::Custom(&most_h))
+        .unwrap();
+    assert_eq!(custom.molecule.atoms[0].element, 8);
+
+    // Equal heavy atom counts go to the first fragment
+    let tie = salt
+        .largest_fragment(FragmentCriterion::HeavyAtomCount)
+        .unwrap();
+    assert_eq!(tie.atom(0), Some(0));
+
+    assert!(Molecule::new()
+        .largest_fragment(FragmentCriterion::HeavyAtomCount)
+        .is_none());
+    // Many fragments, each built from its own atoms
+    let waters = parse_smiles(&vec!["O"; 20000].join(".")).unwrap();
+    let fragments = waters.split_fragments();
+    assert_eq!(fragments.len(), 20000);
+    assert_eq!(fragments[19999].atoms, vec![19999]);
+    let ions = parse_smiles(&vec!["[O-]C(=O)C"; 5000].join(".")).unwrap();
+    let fragments = ions.split_fragments();
+    assert_eq!(fragments[4999].atom(19997), Some(1));
+    assert!(fragments
+        .iter()
+        .all(|f| f.molecule.mol_to_smiles(true) == "CC([O-])=O"));
+}
+
+// The same molecule with atom `i` moved to position `permutation[i]`
+#[cfg(test)]
+fn permute_atoms(mol: &Molecule, permutation: &[usize]) -> Molecule {
+    let mut permuted = mol.clone();
+    for (old_idx, atom) in mol.atoms.iter().enumerate() {
+        permuted.atoms[permutation[old_idx]] = atom.clone();
+    }
+    for bond in permuted.bonds.iter_mut() {
+        bond.source = permutation[bond.source];
+        bond.dest = permutation[bond.dest];
+    }
+    permuted
+}
+
+#[test]
+fn test_canonical_ranking() {
+    let mol = parse_smiles("CC(C)O").unwrap();
+    let classes = mol.symmetry_classes();
+    assert_eq!(classes[0], classes[2]);
+    assert_ne!(classes[0], classes[1]);
+    assert_ne!(classes[0], classes[3]);
+    assert_eq!(mol.atoms[2].symmetry_class, classes[2]);
+
+    let benzene = parse_smiles("c1ccccc1").unwrap();
+    assert!(benzene.symmetry_classes().iter().all(|&c| c == 0));
+    let mut ranks = benzene.canonical_ranks();
+    ranks.sort();
+    assert_eq!(ranks, (0..6).collect::<Vec<_>>());
+
+    // Different inputs for one structure give one string
+    for group in [
+        &["CCO", "OCC", "C(O)C"][..],
+        &["Oc1ccccc1C", "Cc1ccccc1O", "c1cc(C)c(O)cc1"],
+        &["C[C@H](N)C(=O)O", "N[C@@H](C)C(=O)O", "OC(=O)[C@@H](N)C"],
+        &["F/C=C/Cl", "Cl/C=C/F", "C(\\F)=C/Cl"],
+        &["C1CC2CCCCC2CC1.[Na+]", "[Na+].C1CCC2CCCCC2C1"],
+        // Refinement alone leaves every atom of these cages tied
+        &["C12C3C4C1C3C1C3C4C2C2C1C23", "C12C3C1C1C3C3C2C2C4C1C2C34"],
+    ] {
+        let expected = parse_smiles(group[0]).unwrap().mol_to_smiles(true);
+        for smiles in &group[1..] {
+            assert_eq!(
+                parse_smiles(smiles).unwrap().mol_to_smiles(true),
+                expected,
+                "{}",
+                smiles
+            );
+        }
+    }
+
+    // Stereoisomers stay apart
+    assert_ne!(
+        parse_smiles("C[C@H](N)C(=O)O").unwrap().mol_to_smiles(true),
+        parse_smiles("C[C@@H](N)C(=O)O")
+            .unwrap()
+            .mol_to_smiles(true)
+    );
+    assert_ne!(
+        parse_smiles("F/C=C/Cl").unwrap().mol_to_smiles(true),
+        parse_smiles("F/C=C\\Cl").unwrap().mol_to_smiles(true)
+    );
+
+    // Every rotation of the atom order
+    let mol = parse_smiles("CC(=O)N[C@@H](Cc1ccc(O)cc1)C(=O)O").unwrap();
+    let expected = mol.mol_to_smiles(true);
+    let n = mol.atoms.len();
+    for shift in 1..n {
+        let permutation: Vec<usize> = (0..n).map(|i| (i + shift) % n).collect();
+        let permuted = permute_atoms(&mol, &permutation);
+        assert_eq!(permuted.mol_to_smiles(true), expected, "shift {}", shift);
+    }
+    let cage = parse_smiles("C12C3C4C1C3C1C3C4C2C2C1C23").unwrap();
+    let expected = cage.mol_to_smiles(true);
+    let n = cage.atoms.len();
+    for shift in 1..n {
+        // Strided as well as rotated, 5 is coprime to the 12 atoms
+        let permutation: Vec<usize> = (0..n).map(|i| (i * 5 + shift) % n).collect();
+        let permuted = permute_atoms(&cage, &permutation);
+        assert_eq!(permuted.mol_to_smiles(true), expected, "shift {}", shift);
+    }
+}
+
+#[test]
+fn test_canonical_ranking_symmetric() {
+    // Identical fragments are each ranked once, whatever their number
+    let fragments = ["C", "c1ccccc1", "C1C2CC3CC1CC(C2)C3", "[Na+]"];
+    let forward: Vec<&str> = (0..120).map(|i| fragments[i % 4]).collect();
+    let backward: Vec<&str> = forward.iter().rev().copied().collect();
+    assert_eq!(
+        parse_smiles(&forward.join("."))
+            .unwrap()
+            .mol_to_smiles(true),
+        parse_smiles(&backward.join("."))
+            .unwrap()
+            .mol_to_smiles(true)
+    );
+
+    // Every branch point of the chain swaps its two methyls
+    let chain = parse_smiles(&"C(C)(C)".repeat(100)).unwrap();
+    let expected = chain.mol_to_smiles(true);
+    let n = chain.atoms.len();
+    let permutation: Vec<usize> = (0..n).map(|i| (i * 7 + 3) % n).collect();
+    assert_eq!(
+        permute_atoms(&chain, &permutation).mol_to_smiles(true),
+        expected
+    );
+
+    // Dodecahedrane, every atom tied after refinement and 120 automorphisms
+    let cage = parse_smiles("C12C3C4C5C6C7C4C4C3C3C2C2C8C3C4C7C8C6C2C15").unwrap();
+    let expected = cage.mol_to_smiles(true);
+    for shift in 1..20 {
+        let permutation: Vec<usize> = (0..20).map(|i| (i * 3 + shift) % 20).collect();
+        let permuted = permute_atoms(&cage, &permutation);
+        assert_eq!(permuted.mol_to_smiles(true), expected, "shift {}", shift);
+    }
+    let cages = parse_smiles(&["C12C3C4C5C6C7C4C4C3C3C2C2C8C3C4C7C8C6C2C15"; 30].join("."));
+    assert_eq!(
+        cages.unwrap().mol_to_smiles(true),
+        vec![expected.as_str(); 30].join(".")
+    );
+}
+
+#[cfg(test)]
+use crate::core::cip::CipLabel;
+
+#[test]
+fn test_cip_labels() {
+    let label =
+        |smiles: &str, atom_idx: usize| parse_smiles(smiles).unwrap().cip_labels().atoms[atom_idx];
+
+    // L-amino acids are S, except cysteine where CH2SH outranks COOH
+    assert_eq!(label("N[C@@H](C)C(=O)O", 1), Some(CipLabel::S));
+    assert_eq!(label("N[C@H](C)C(=O)O", 1), Some(CipLabel::R));
+    assert_eq!(label("C[C@H](N)C(=O)O", 1), Some(CipLabel::S));
+    assert_eq!(label("N[C@@H](CO)C(=O)O", 1), Some(CipLabel::S));
+    assert_eq!(label("N[C@@H](CS)C(=O)O", 1), Some(CipLabel::R));
+    assert_eq!(label("F[C@](Cl)(Br)I", 1), Some(CipLabel::S));
+    // Duplicated atoms decide between an aldehyde and a primary alcohol
+    assert_eq!(label("OC[C@@H](O)C=O", 2), Some(CipLabel::R));
+    // Ring bond numbers come before branches among the neighbours, on the
+    // atom opening the ring as on the one closing it
+    assert_eq!(label("O=C1C[C@@H](F)CCC1", 3), Some(CipLabel::S));
+    assert_eq!(label("F[C@@H]1CC(=O)CCC1", 1), Some(CipLabel::S));
+    assert_eq!(label("[C@@H]1(F)CC(=O)CCC1", 0), Some(CipLabel::S));
+    assert_eq!(label("F[C@H]1CCC(=O)C1", 1), Some(CipLabel::S));
+    assert_eq!(
+        label("F[C@@H]12CC(=O)CC1C2", 1),
+        label("F[C@@H]21CC(=O)CC2C1", 1)
+    );
+
+    // Equal ligands leave no stereocentre, an isotope label (rule 2) makes one
+    assert_eq!(label("C[C@H](O)C", 1), None);
+    assert!(label("C[C@H](O)C([2H])([2H])[2H]", 1).is_some());
+
+    let bond_label = |smiles: &str| {
+        let mol = parse_smiles(smiles).unwrap();
+        let labels = mol.cip_labels();
+        let bond_idx = mol.bonds.iter().position(|b| b.bond_order == 2).unwrap();
+        labels.bonds[bond_idx]
+    };
+    assert_eq!(bond_label("F/C=C/F"), Some(CipLabel::E));
+    assert_eq!(bond_label("F/C=C\\F"), Some(CipLabel::Z));
+    assert_eq!(bond_label("C(\\F)=C/F"), Some(CipLabel::E));
+    // Cl outranks F on the left, so the descriptor follows Cl and F
+    assert_eq!(bond_label("Cl/C(F)=C/F"), Some(CipLabel::E));
+    assert_eq!(bond_label("F/C(Cl)=C/F"), Some(CipLabel::Z));
+    assert_eq!(bond_label("C/C=N/O"), Some(CipLabel::E));
+    assert_eq!(bond_label("C=C/F"), None);
+
+    // Stereogenic but unspecified
+    let labels = parse_smiles("CC(O)CC.CC=CC.CC(C)O").unwrap().cip_labels();
+    assert_eq!(labels.unspecified_atoms, vec![1]);
+    assert_eq!(labels.unspecified_bonds.len(), 1);
+    assert!(labels.atoms.iter().all(|l| l.is_none()));
+    let ring = parse_smiles("C1CCCCC1=CC").unwrap().cip_labels();
+    assert!(ring.unspecified_bonds.is_empty());
+    assert_eq!(CipLabel::R.to_string(), "R");
+}
+
+#[cfg(test)]
+use crate::core::{configuration::Configuration, stereo_perception::BondStereo};
+
+#[test]
+fn test_stereo_from_coords() {
+    let canonical = |smiles: &str| parse_smiles(smiles).unwrap().mol_to_smiles(true);
+
+    // Tetrahedral C with F on top and Cl, Br, H anticlockwise below it
+    let place = |mol: &mut Molecule, coords: &[(f64, f64, f64)]| {
+        for (atom, &xyz) in mol.atoms.iter_mut().zip(coords) {
+            atom.coords_3d = Some(xyz);
+        }
+    };
+    let mut mol = parse_smiles("FC(Cl)Br").unwrap();
+    place(
+        &mut mol,
+        &[
+            (0.0, 0.0, 1.0),
+            (0.0, 0.0, 0.0),
+            (0.94, 0.0, -0.33),
+            (-0.47, 0.82, -0.33),
+        ],
+    );
+    mol.perceive_stereo_from_coords(&[]);
+    assert_eq!(mol.atoms[1].configuration, Some(Configuration::TH1));
+    assert_eq!(mol.mol_to_smiles(true), canonical("F[C@H](Cl)Br"));
+
+    // The mirror image
+    for atom in mol.atoms.iter_mut() {
+        atom.configuration = None;
+        atom.coords_3d = atom.coords_3d.map(|(x, y, z)| (x, -y, z));
+    }
+    mol.perceive_stereo_from_coords(&[]);
+    assert_eq!(mol.mol_to_smiles(true), canonical("F[C@@H](Cl)Br"));
+
+    // 2D drawing: Cl wedged towards the viewer gives S, hashed gives R
+    let drawing = [
+        (0.0, 1.0, 0.0),
+        (0.0, 0.0, 0.0),
+        (0.87, -0.5, 0.0),
+        (-0.87, -0.5, 0.0),
+    ];
+    let mut mol = parse_smiles("FC(Cl)Br").unwrap();
+    place(&mut mol, &drawing);
+    let cl_bond = mol.bond_between(1, 2).unwrap();
+    // Bond stereo is read from the narrow end, the centre
+    mol.bonds[cl_bond].source = 1;
+    mol.bonds[cl_bond].dest = 2;
+    let mut flags = vec![BondStereo::None; mol.bonds.len()];
+    flags[cl_bond] = BondStereo::Wedge;
+    let mut wedged = mol.clone();
+    wedged.perceive_stereo_from_coords(&flags);
+    assert_eq!(wedged.cip_labels().atoms[1], Some(CipLabel::S));
+    flags[cl_bond] = BondStereo::Hash;
+    let mut hashed = mol.clone();
+    hashed.perceive_stereo_from_coords(&flags);
+    assert_eq!(hashed.cip_labels().atoms[1], Some(CipLabel::R));
+    // No wedges, no stereo
+    mol.perceive_stereo_from_coords(&[]);
+    assert_eq!(mol.atoms[1].configuration, None);
+
+    // Double bonds, drawn flat
+    let mut mol = parse_smiles("FC=CF").unwrap();
+    place(
+        &mut mol,
+        &[
+            (-0.7, 0.9, 0.0),
+            (0.0, 0.0, 0.0),
+            (1.3, 0.0, 0.0),
+            (2.0, 0.9, 0.0),
+        ],
+    );
+    let mut cis = mol.clone();
+    cis.perceive_stereo_from_coords(&[]);
+    assert_eq!(cis.mol_to_smiles(true), canonical("F/C=C\\F"));
+    mol.atoms[3].coords_3d = Some((2.0, -0.9, 0.0));
+    let mut trans = mol.clone();
+    trans.perceive_stereo_from_coords(&[]);
+    assert_eq!(trans.mol_to_smiles(true), canonical("F/C=C/F"));
+    let crossed = vec![BondStereo::None, BondStereo::Either, BondStereo::None];
+    mol.perceive_stereo_from_coords(&crossed);
+    assert_eq!(mol.mol_to_smiles(true), canonical("FC=CF"));
+}
+
+#[cfg(test)]
+use crate::core::isomorphism::{MatchOptions, Vf2};
+
+#[test]
+fn test_isomorphism() {
+    let mol = |smiles: &str| parse_smiles(smiles).unwrap();
+
+    assert!(mol("OCC").is_isomorphic(&mol("CCO")));
+    assert!(!mol("CCO").is_isomorphic(&mol("COC")));
+    assert!(mol("C1=CC=CC=C1O").is_isomorphic(&mol("Oc1ccccc1")));
+    assert!(!mol("CCO").is_isomorphic(&mol("CCO.C")));
+
+    // The mapping carries elements and bonds across
+    let (a, b) = (mol("CC(=O)NC"), mol("CNC(C)=O"));
+    let mapping = a.isomorphism(&b, &MatchOptions::default()).unwrap();
+    for (atom_idx, &image) in mapping.iter().enumerate() {
+        assert_eq!(a.atoms[atom_idx].element, b.atoms[image].element);
+    }
+    for bond in &a.bonds {
+        let image = b
+            .get_bond(mapping[bond.source], mapping[bond.dest])
+            .unwrap();
+        assert_eq!(bond.bond_order, image.bond_order);
+    }
+
+    // Options relax the comparison
+    let loose = MatchOptions {
+        charge: false,
+        isotope: false,
+        stereo: false,
+        ..MatchOptions::default()
+    };
+    let (charged, neutral) = (mol("C[N+](C)(C)C"), mol("C[N](C)(C)C"));
+    assert!(!charged.is_isomorphic(&neutral));
+    assert!(charged.isomorphism(&neutral, &loose).is_some());
+    assert!(!mol("[13CH4]").is_isomorphic(&mol("C")));
+    assert!(mol("[13CH4]").isomorphism(&mol("C"), &loose).is_some());
+
+    // Stereo: same configuration written differently, then the enantiomer
+    let l_alanine = mol("N[C@@H](C)C(=O)O");
+    assert!(l_alanine.is_isomorphic(&mol("C[C@H](N)C(=O)O")));
+    assert!(l_alanine.is_isomorphic(&mol("OC(=O)[C@@H](N)C")));
+    assert!(!l_alanine.is_isomorphic(&mol("N[C@H](C)C(=O)O")));
+    assert!(!l_alanine.is_isomorphic(&mol("NC(C)C(=O)O")));
+    assert!(l_alanine
+        .isomorphism(&mol("N[C@H](C)C(=O)O"), &loose)
+        .is_some());
+    assert!(mol("F/C=C/F").is_isomorphic(&mol("C(\\F)=C/F")));
+    assert!(!mol("F/C=C/F").is_isomorphic(&mol("F/C=C\\F")));
+
+    // A Kekulé graph without perceived aromaticity
+    let mut kekule = mol("c1ccccc1");
+    for bond in kekule.bonds.iter_mut() {
+        bond.arom = false;
+    }
+    for atom in kekule.atoms.iter_mut() {
+        atom.aromatic = false;
+    }
+    let no_aromaticity = MatchOptions {
+        aromaticity: false,
+        ..MatchOptions::default()
+    };
+    assert!(!kekule.is_isomorphic(&mol("c1ccccc1")));
+    assert!(kekule
+        .isomorphism(&mol("c1ccccc1"), &no_aromaticity)
+        .is_some());
+
+    // Custom equivalence: any halogen matches any other
+    let (fluoro, chloro) = (mol("FCC"), mol("ClCC"));
+    let halogen = |z: usize| matches!(z, 9 | 17 | 35 | 53);
+    let atom_match = |a: usize, b: usize| {
+        let (x, y) = (fluoro.atoms[a].element, chloro.atoms[b].element);
+        x == y || (halogen(x) && halogen(y))
+    };
+    let bond_match = |_: usize, _: usize| true;
+    assert_eq!(
+        Vf2::new(&fluoro, &chloro, &atom_match, &bond_match).first(),
+        Some(vec![0, 1, 2])
+    );
+    let vf2 = Vf2::new(&fluoro, &chloro, &atom_match, &bond_match);
+    assert_eq!(vf2.first_from(2, 2), Some(vec![0, 1, 2]));
+    assert_eq!(vf2.first_from(0, 2), None);
+
+    // Automorphisms and the orbits they give
+    let options = MatchOptions::default();
+    assert_eq!(mol("c1ccccc1").automorphisms(&options).len(), 12);
+    let isobutane = mol("CC(C)C");
+    assert_eq!(isobutane.automorphisms(&options).len(), 6);
+    assert_eq!(isobutane.atom_orbits(&options), vec![0, 1, 0, 0]);
+    assert_eq!(
+        mol("OCC(O)CO").atom_orbits(&options),
+        vec![0, 1, 2, 3, 1, 0]
+    );
+    // Perfluorodecane has 2^10 * 3!^2 * 2 automorphisms, the orbits come
+    // without listing them: one per carbon and fluorine position, mirrored
+    let perfluorodecane = mol(&format!("F{}F", "C(F)(F)".repeat(10)));
+    let orbits = perfluorodecane.atom_orbits(&options);
+    assert_eq!(orbits.iter().max(), Some(&9));
+    assert_eq!(orbits[0], orbits[orbits.len() - 1]);
+    assert_eq!(orbits[2], orbits[3]);
+
+    // Searches that used to try every arrangement of the repeated parts:
+    // the invariants differ, then only the last component does, then only
+    // the stereocentre does
+    let methanes = ["C"; 12].join(".");
+    let (oxygen, nitrogen) = (
+        mol(&format!("{}.O", methanes)),
+        mol(&format!("{}.N", methanes)),
+    );
+    assert!(!oxygen.is_isomorphic(&nitrogen));
+    let cyclohexane = mol(&format!("{}.C1CCCCC1", methanes));
+    let cyclopropanes = mol(&format!("{}.C1CC1.C1CC1", methanes));
+    assert!(!cyclohexane.is_isomorphic(&cyclopropanes));
+    assert!(cyclohexane.is_isomorphic(&mol(&format!("C1CCCCC1.{}", methanes))));
+    let chain = "C(F)(F)".repeat(20);
+    let (left, right) = (
+        mol(&format!("Cl[C@H](Br){}F", chain)),
+        mol(&format!("Cl[C@@H](Br){}F", chain)),
+    );
+    assert!(!left.is_isomorphic(&right));
+    assert!(left.is_isomorphic(&mol(&format!("Br[C@@H](Cl){}F", chain))));
+}
+
+#[cfg(test)]
+use crate::core::properties::Property;
+#[test]
+fn test_properties() {
+    assert_eq!(Property::infer("42"), Property::Int(42));
+    assert_eq!(Property::infer("-1.5"), Property::Float(-1.5));
+    assert_eq!(Property::infer("true"), Property::Bool(true));
+    assert_eq!(Property::infer("007"), Property::String("007".into()));
+    assert_eq!(Property::infer("1.50"), Property::String("1.50".into()));
+    assert_eq!(Property::Int(3).as_float(), Some(3.0));
+
+    let mut properties = PropertyMap::new();
+    properties.set("b", 1);
+    properties.set("a", "x");
+    properties.set("b", false);
+    let names: Vec<&str> = properties.iter().map(|(name, _)| name).collect();
+    assert_eq!(names, vec!["b", "a"]);
+    assert_eq!(properties.get("b"), Some(&Property::Bool(false)));
+    assert_eq!(properties.remove("a"), Some(Property::String("x".into())));
+    assert_eq!(properties.len(), 1);
+
+    // Fragments keep the properties of the molecule and of their atoms
+    let mut salt = parse_smiles("[Na+].[O-]C sodium methoxide").unwrap();
+    salt.atoms[2].properties.set("label", 7);
+    let fragments = salt.split_fragments();
+    assert_eq!(fragments[1].molecule.name(), Some("sodium methoxide"));
+    assert_eq!(
+        fragments[1].molecule.atoms[1].properties.get("label"),
+        Some(&Property::Int(7))
+    );
+}
+
+#[cfg(test)]
+use crate::core::builder::{BondOrder, MoleculeBuilder};
+#[test]
+fn test_molecule_builder() {
+    let acetaldehyde = MoleculeBuilder::new()
+        .atom("C")
+        .atom("C")
+        .bond_to(0, BondOrder::Single)
+        .atom("O")
+        .bond_to(1, BondOrder::Double)
+        .build()
+        .unwrap();
+    assert_eq!(acetaldehyde.mol_to_smiles(false), "CC=O");
+    assert_eq!(acetaldehyde.atoms[0].outgoing_bond, vec![0]);
+    assert_eq!(acetaldehyde.atoms[1].outgoing_bond, vec![0, 1]);
+    assert_eq!(acetaldehyde.atoms[1].hydrogens, 1);
+
+    // Aromatic atoms are kekulized and perceived like parsed ones
+    let mut builder = MoleculeBuilder::new();
+    for symbol in ["c", "c", "c", "c", "c", "n"] {
+        let next = builder.next_index();
+        builder = builder.atom(symbol);
+        if next > 0 {
+            builder = builder.bond_to(next - 1, BondOrder::Aromatic);
+        }
+    }
+    let pyridine = builder.bond(5, 0, BondOrder::Aromatic).build().unwrap();
+    assert_eq!(
+        pyridine.mol_to_smiles(true),
+        parse_smiles("n1ccccc1").unwrap().mol_to_smiles(true)
+    );
+    assert!(pyridine.atoms.iter().all(|a| a.aromatic && a.ring));
+
+    // Atoms given in full keep their hydrogens and other fields
+    let ammonium = MoleculeBuilder::new()
+        .add_atom(
+            Atom::new(7)
+                .with_hydrogens(4)
+                .with_charge(1)
+                .with_isotope(15)
+                .with_atom_map(1),
+        )
+        .build()
+        .unwrap();
+    assert_eq!(ammonium.mol_to_smiles(false), "[15NH4+:1]");
+
+    let broken = MoleculeBuilder::new()
+        .atom("c")
+        .atom("c")
+        .bond_to(0, BondOrder::Aromatic)
+        .atom("c")
+        .bond_to(1, BondOrder::Aromatic)
+        .bond(2, 0, BondOrder::Aromatic);
+    assert!(broken.build().is_err());
+}
+
+#[cfg(test)]
+use crate::core::hybridization::Hybridization;
+#[test]
+fn test_hybridization() {
+    let hybridizations = |smiles: &str| parse_smiles(smiles).unwrap().hybridizations();
+    use Hybridization::*;
+    assert_eq!(hybridizations("CC=CC#N"), vec![SP3, SP2, SP2, SP, SP]);
+    assert_eq!(hybridizations("O=C=O"), vec![SP2, SP, SP2]);
+    // Lone pairs next to a π system are delocalised
+    assert_eq!(hybridizations("CC(=O)N"), vec![SP3, SP2, SP2, SP2]);
+    assert_eq!(hybridizations("CN(C)C"), vec![SP3, SP3, SP3, SP3]);
+    assert_eq!(hybridizations("c1cc[nH]c1")[3], SP2);
+    assert_eq!(hybridizations("c1ccoc1")[3], SP2);
+    assert_eq!(hybridizations("[CH3+]"), vec![SP2]);
+    assert_eq!(hybridizations("C[N+](=O)[O-]"), vec![SP3, SP2, SP2, SP2]);
+    assert_eq!(hybridizations("FS(F)(F)(F)(F)F")[1], SP3D2);
+    assert_eq!(hybridizations("ClP(Cl)(Cl)(Cl)Cl")[1], SP3D);
+    assert_eq!(hybridizations("[H][H]"), vec![S, S]);
+    assert_eq!(hybridizations("[Na+].[Fe]"), vec![Unspecified, Unspecified]);
+}
@@ -1,90 +1,33 @@
 #[cfg(test)]
 mod tests {
     use crate::core::{
-        defs::{Atom, Axialness, Bond},
+        builder::{BondOrder, MoleculeBuilder},
+        defs::Atom,
         molecule::Molecule,
     };
     use crate::fingerprints::ecfp::ecfp; // ← this is all you need
 
     // Helper to build a simple alkane: C–C
     fn build_ethane_like() -> Molecule {
-        let mut mol = Molecule::new();
-
-        // Two carbon atoms
-        let c1 = Atom {
-            element: 6,
-            outgoing_bond: Vec::new(),
-            isotope: 0,
-            hydrogens: 3,
-            aromatic: false,
-            f_charge: 0,
-            configuration: None,
-            ring: false,
-            symmetry_class: 0,
-            coords_3d: None,
-        };
-        mol.add_atom(c1);
-
-        let c2 = Atom {
-            element: 6,
-            outgoing_bond: Vec::new(),
-            isotope: 0,
-            hydrogens: 3,
-            aromatic: false,
-            f_charge: 0,
-            configuration: None,
-            ring: false,
-            symmetry_class: 0,
-            coords_3d: None,
-        };
-        mol.add_atom(c2);
-
-        // Single bond between them
-        mol.add_bond(Bond {
-            source: 0,
-            dest: 1,
-            arom: false,
-            ring: false,
-            bond_order: 1,
-            axialness: Axialness::UNKNOWN,
-        });
-
-        mol
+        MoleculeBuilder::new()
+            .atom("C")
+            .atom("C")
+            .bond_to(0, BondOrder::Single)
+            .build()
+            .unwrap()
     }
 
     // Helper to build a simple benzene‑like ring
     fn build_benzene_like() -> Molecule {
-        let mut mol = Molecule::new();
-
-        // 6 aromatic carbons
-        for _ in 0..6 {
-            mol.add_atom(Atom {
-                element: 6,
-                outgoing_bond: Vec::new(),
-                isotope: 0,
-                hydrogens: 1,
-                aromatic: true,
-                f_charge: 0,
-                configuration: None,
-                ring: true,
-                symmetry_class: 0,
-                coords_3d: None,
-            });
-        }
-
-        // Ring bonds: 1–2, 2–3, 3–4, 4–5, 5–6, 6–1
+        // 6 aromatic carbons, ring bonds 1–2, 2–3, 3–4, 4–5, 5–6, 6–1
+        let mut builder = MoleculeBuilder::new();
         for i in 0..6 {
-            mol.add_bond(Bond {
-                source: i,
-                dest: (i + 1) % 6,
-                arom: true,
-                ring: true,
-                bond_order: 1, // stored as aromatic 1; kekulization is separate
-                axialness: Axialness::UNKNOWN,
-            });
+            builder = builder.add_atom(Atom::new(6).with_aromatic(true).with_hydrogens(1));
+            if i > 0 {
+                builder = builder.bond_to(i - 1, BondOrder::Aromatic);
+            }
         }
-
-        mol
+        builder.bond(5, 0, BondOrder::Aromatic).build().unwrap()
     }
 
     #[test]
@@ -9,7 +9,7 @@ fn test_smiles_parsing() {
             assert_eq!(mol.atoms.len(), 19)
         }
         Err(e) => {
-            assert!(false, "Failed to parse SMILES string: {:?}", e);
+            panic!("Failed to parse SMILES string: {:?}", e);
         }
     }
 }
@@ -54,6 +54,50 @@ fn test_components() {
     assert!(SmartsPattern::new("[O]").match_mol(&parse_smiles("OO").unwrap()));
 }
 
+#[cfg(test)]
+use crate::core::{
+    configuration::Configuration,
+    defs::{Atom, Axialness, Bond},
+};
+#[test]
+fn test_smiles_parser_graph() {
+    // Implicit hydrogens on every organic subset atom, not only chain ends
+    let isobutane = parse_smiles("CC(C)C").unwrap();
+    let hydrogens: Vec<usize> = isobutane.atoms.iter().map(|a| a.hydrogens).collect();
+    assert_eq!(hydrogens, vec![3, 1, 3, 3]);
+    assert_eq!(parse_smiles("c1ccccc1").unwrap().atoms[2].hydrogens, 1);
+    // A bare H in brackets means one hydrogen
+    assert_eq!(parse_smiles("[CH]").unwrap().atoms[0].hydrogens, 1);
+
+    // Branches close back to the atom they opened on, nested or not
+    let nested = parse_smiles("CC(C(F)Cl)Br").unwrap();
+    assert!(nested.bonds.iter().any(|b| b.source == 1 && b.dest == 5));
+    assert!(nested.bonds.iter().any(|b| b.source == 2 && b.dest == 4));
+
+    let symbols = |smiles: &str| -> Vec<usize> {
+        let mol = parse_smiles(smiles).unwrap();
+        mol.atoms.iter().map(|a| a.element).collect()
+    };
+    assert_eq!(symbols("[Ga][U][W]Cl"), vec![31, 92, 74, 17]);
+    assert_eq!(symbols("[se]1cccc1"), vec![34, 6, 6, 6, 6]);
+    // Only the organic subset may be written without brackets
+    assert!(parse_smiles("CNa").is_err());
+}
+
+#[test]
+fn test_smiles_parser_stereo() {
+    let bonds = parse_smiles("F/C=C\\F").unwrap().bonds;
+    assert!(bonds[0].axialness == Axialness::UP);
+    assert!(bonds[2].axialness == Axialness::DOWN);
+
+    // A leading [C@H] lists its hydrogen first, F[C@@H] lists it second:
+    // both spellings are the same configuration
+    let leading = parse_smiles("[C@H](F)(Cl)Br").unwrap();
+    let inner = parse_smiles("F[C@@H](Cl)Br").unwrap();
+    assert!(leading.atoms[0].configuration == Some(Configuration::TH2));
+    assert!(inner.atoms[1].configuration == Some(Configuration::TH2));
+}
+
 #[test]
 fn test_stereochemistry() {
     // Backbone match without chirality constraint
@@ -82,3 +126,525 @@ fn test_stereochemistry() {
     assert!(!with_stereo.match_mol(&parse_smiles("C[C@@H](O)CC").unwrap()));
     assert!(!with_stereo.match_mol(&parse_smiles("CC(O)CC").unwrap()));
 }
+
+#[test]
+fn test_ring_primitives() {
+    let naphthalene = parse_smiles("c1ccc2ccccc2c1").unwrap();
+    let methylcyclopentane = parse_smiles("CC1CCCC1").unwrap();
+
+    // R<n>: number of SSSR rings
+    assert!(SmartsPattern::new("[cR2]").match_mol(&naphthalene));
+    assert!(!SmartsPattern::new("[CR2]").match_mol(&methylcyclopentane));
+    assert!(SmartsPattern::new("[CR0]").match_mol(&methylcyclopentane));
+    assert!(SmartsPattern::new("[CR]").match_mol(&methylcyclopentane));
+
+    // r<n>: smallest ring size
+    assert!(SmartsPattern::new("[Cr5]").match_mol(&methylcyclopentane));
+    assert!(!SmartsPattern::new("[Cr6]").match_mol(&methylcyclopentane));
+    assert!(SmartsPattern::new("[cr6]").match_mol(&naphthalene));
+
+    // x<n>: ring bonds on the atom
+    assert!(SmartsPattern::new("[cx3]").match_mol(&naphthalene));
+    assert!(!SmartsPattern::new("[Cx3]").match_mol(&methylcyclopentane));
+    assert!(SmartsPattern::new("[Cx2]").match_mol(&methylcyclopentane));
+
+    // Atoms added after parsing are not covered by the cached rings
+    let mut cyclopropane = parse_smiles("C1CC1").unwrap();
+    assert!(!SmartsPattern::new("[CR0]").match_mol(&cyclopropane));
+    let methyl = cyclopropane.add_atom(Atom::new(6).with_hydrogens(3));
+    cyclopropane.add_bond(Bond::new(0, methyl, 1));
+    assert!(cyclopropane.ring_info().is_none());
+    assert!(SmartsPattern::new("[CR0]").match_mol(&cyclopropane));
+    cyclopropane.perceive_rings();
+    cyclopropane.atoms.push(Atom::new(8));
+    assert!(cyclopropane.ring_info().is_none());
+}
+
+#[test]
+fn test_valence_primitives() {
+    let acetic_acid = parse_smiles("CC(=O)O").unwrap();
+    // X counts implicit hydrogens, D does not
+    assert!(SmartsPattern::new("[CX4]").match_mol(&acetic_acid));
+    assert!(SmartsPattern::new("[CX3]").match_mol(&acetic_acid));
+    assert!(!SmartsPattern::new("[OX3]").match_mol(&acetic_acid));
+
+    // v is total valence, h the implicit hydrogens
+    assert!(SmartsPattern::new("[Ov2]").match_mol(&acetic_acid));
+    assert!(SmartsPattern::new("[Oh1]").match_mol(&acetic_acid));
+    assert!(SmartsPattern::new("[Ch3]").match_mol(&acetic_acid));
+    assert!(SmartsPattern::new("[Oh]").match_mol(&acetic_acid));
+    assert!(!SmartsPattern::new("[Oh2]").match_mol(&acetic_acid));
+
+    let sulfone = parse_smiles("CS(=O)(=O)C").unwrap();
+    assert!(SmartsPattern::new("[Sv6]").match_mol(&sulfone));
+    assert!(!SmartsPattern::new("[Sv2]").match_mol(&sulfone));
+
+    // H counts hydrogen atoms in the graph as well
+    let methanol = parse_smiles("[H]OC").unwrap();
+    assert!(SmartsPattern::new("[OH1]").match_mol(&methanol));
+    assert!(SmartsPattern::new("[Oh0]").match_mol(&methanol));
+}
+
+#[cfg(test)]
+use crate::{
+    core::kekulize::KekulizeError,
+    parsers::{daylight::reaction::parse_reaction_smiles, error::ErrorKind},
+};
+#[test]
+fn test_reaction_smiles() {
+    let rxn = parse_reaction_smiles("[CH3:1][OH:2].[Na+]>CO>[CH3:1][O-:2].[Na+]").unwrap();
+    assert_eq!(rxn.reactants.len(), 2);
+    assert_eq!(rxn.agents.len(), 1);
+    assert_eq!(rxn.products.len(), 2);
+    let maps: Vec<usize> = rxn.products[0].atoms.iter().map(|a| a.atom_map).collect();
+    assert_eq!(maps, vec![1, 2]);
+    assert_eq!(rxn.products[0].atoms[1].f_charge, -1);
+    assert_eq!(rxn.agents[0].atoms[0].hydrogens, 3);
+
+    let no_agents = parse_reaction_smiles("CC=O>>CCO").unwrap();
+    assert!(no_agents.agents.is_empty());
+    assert_eq!(no_agents.molecules().count(), 2);
+    assert_eq!(parse_reaction_smiles(">>").unwrap().molecules().count(), 0);
+
+    let error = parse_reaction_smiles("CC>O").err().unwrap();
+    assert_eq!(error.kind, ErrorKind::Expected("'>'"));
+    assert_eq!(error.column, 5);
+    let error = parse_reaction_smiles("C>>C>C").err().unwrap();
+    assert_eq!(error.reason(), "more than three reaction roles");
+    assert_eq!(error.column, 5);
+    // Positions count from the start of the reaction
+    let error = parse_reaction_smiles("C>>CQ").err().unwrap();
+    assert_eq!((error.column, error.token.as_str()), (5, "Q"));
+}
+
+#[test]
+fn test_atom_maps() {
+    let mol = parse_smiles("[CH3:1]C[OH:12]").unwrap();
+    assert_eq!(mol.atoms[0].atom_map, 1);
+    assert_eq!(mol.atoms[1].atom_map, 0);
+    assert_eq!(mol.atom_with_map(12), Some(2));
+    assert_eq!(mol.atom_with_map(3), None);
+    assert!(parse_smiles("[CH3:]").is_err());
+
+    // Maps label query atoms without restricting what they match
+    let pattern = SmartsPattern::new("[C:1][OH1:2]");
+    let maps: Vec<usize> = pattern
+        .nodes
+        .iter()
+        .filter(|node| node.nbrs.is_some())
+        .map(|node| node.atom_map)
+        .collect();
+    assert_eq!(maps, vec![1, 2]);
+    assert!(pattern.match_mol(&parse_smiles("CCO").unwrap()));
+    assert!(pattern.match_mol(&mol));
+    assert!(!pattern.match_mol(&parse_smiles("COC").unwrap()));
+}
+
+#[cfg(test)]
+use crate::{
+    core::properties::{Property, SGROUPS, STEREO_GROUP},
+    parsers::sdf::sdf::{parse_molfile, read_sdf, SdfReader},
+};
+#[test]
+fn test_sdf_properties() {
+    let record = "\
+ethanol
+  molrus
+
+  3  2  0  0  0  0  0  0  0  0999 V2000
+    0.0000    0.0000    0.0000 C   0  0  0  0  0  0  0  0  0  0  0  0
+    1.5000    0.0000    0.0000 C   0  0  0  0  0  0  0  0  0  0  0  0
+    2.0000    1.4000    0.0000 O   0  0  0  0  0  0  0  0  0  0  0  0
+  1  2  1  0  0  0  0
+  2  3  1  0  0  0  0
+M  END
+> <ID>
+007
+
+>  <pIC50>
+6.5
+
+> <NOTE>
+two
+lines
+$$$$
+methanol
+  molrus
+
+  2  1  0  0  0  0  0  0  0  0999 V2000
+    0.0000    0.0000    0.0000 C   0  0  0  0  0  0  0  0  0  0  0  0
+    1.4000    0.0000    0.0000 O   0  0  0  0  0  0  0  0  0  0  0  0
+  1  2  1  0  0  0  0
+M  END
+> <count>
+12
+
+$$$$
+";
+    let path = std::env::temp_dir().join("molrus_test_sdf_properties.sdf");
+    std::fs::write(&path, record).unwrap();
+    let molecules: Vec<_> = read_sdf(path.to_str().unwrap())
+        .unwrap()
+        .into_iter()
+        .map(Result::unwrap)
+        .collect();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(molecules.len(), 2);
+    let ethanol = &molecules[0];
+    assert_eq!(ethanol.name(), Some("ethanol"));
+    assert_eq!(ethanol.atoms.len(), 3);
+    assert_eq!(
+        ethanol.properties.get("ID"),
+        Some(&Property::String("007".to_string()))
+    );
+    // SD data items are untyped text
+    assert_eq!(
+        ethanol.properties.get("pIC50"),
+        Some(&Property::from("6.5"))
+    );
+    assert_eq!(
+        ethanol.properties.get("NOTE").and_then(|p| p.as_str()),
+        Some("two\nlines")
+    );
+    assert_eq!(molecules[1].name(), Some("methanol"));
+    assert_eq!(
+        molecules[1]
+            .properties
+            .get("count")
+            .map(|p| Property::infer(&p.to_string())),
+        Some(Property::Int(12))
+    );
+}
+
+#[test]
+fn test_hybridization_primitive() {
+    assert!(SmartsPattern::new("[C^2]=O").match_mol(&parse_smiles("CC=O").unwrap()));
+    assert!(!SmartsPattern::new("[C^2]").match_mol(&parse_smiles("CC").unwrap()));
+    assert!(SmartsPattern::new("[N^2]").match_mol(&parse_smiles("CC(=O)N").unwrap()));
+    assert!(SmartsPattern::new("[N^1]").match_mol(&parse_smiles("CC#N").unwrap()));
+
+    assert!(!SmartsPattern::new("[c^3]").match_mol(&parse_smiles("c1ccccc1").unwrap()));
+}
+
+#[test]
+fn test_parse_errors() {
+    let error = parse_smiles("C1CC").err().unwrap();
+    assert_eq!(error.kind, ErrorKind::UnclosedRing(1));
+    assert_eq!(
+        error.to_string(),
+        "line 1, column 2: unclosed ring bond 1 at \"1\""
+    );
+    assert_eq!(
+        parse_smiles("C1CC3CC1").err().unwrap().reason(),
+        "unclosed ring bond 3"
+    );
+
+    let error = parse_smiles("CC[Xx]").err().unwrap();
+    assert_eq!(error.reason(), "unknown element Xx");
+    assert_eq!(error.column, 4);
+
+    assert_eq!(
+        parse_smiles("CC(C").err().unwrap().kind,
+        ErrorKind::UnclosedBranch
+    );
+    let error = parse_smiles("CC)C").err().unwrap();
+    assert_eq!((error.kind, error.column), (ErrorKind::UnmatchedBranch, 3));
+    assert_eq!(
+        parse_smiles("C[C").err().unwrap().kind,
+        ErrorKind::UnexpectedEnd
+    );
+
+    let error = SmartsPattern::parse("[C;H1").err().unwrap();
+    assert_eq!(error.line, 1);
+    assert!(SmartsPattern::parse("C1CC").is_err());
+    assert!(SmartsPattern::parse("C)C").is_err());
+    assert!(SmartsPattern::parse("C(=O)[OX2H1]").is_ok());
+}
+
+#[test]
+fn test_ring_closures() {
+    // Two rings opened on the same atom
+    let bicycle = parse_smiles("C12CC1CC2").unwrap();
+    assert_eq!(bicycle.bonds.len(), 6);
+    assert_eq!(bicycle.degree(0), 3);
+    assert!(bicycle.get_bond(0, 2).is_some() && bicycle.get_bond(0, 4).is_some());
+
+    // Two-digit labels, and labels reused once closed
+    let decalin = parse_smiles("C%10CCC2CCCCC2C%10").unwrap();
+    assert_eq!((decalin.atoms.len(), decalin.bonds.len()), (10, 11));
+    let spiro = parse_smiles("C1CC11CCCC1").unwrap();
+    assert_eq!(spiro.degree(2), 4);
+    let long_label = parse_smiles("C%(123)CCCCC%(123)").unwrap();
+    assert_eq!(long_label.bonds.len(), 6);
+
+    // Bond symbols on either end of a ring bond
+    for smiles in ["C=1CCCCC1", "C1CCCCC=1", "C=1CCCCC=1"] {
+        let mol = parse_smiles(smiles).unwrap();
+        assert_eq!(mol.get_bond(0, 5).unwrap().bond_order, 2, "{}", smiles);
+        assert_eq!(mol.atoms[0].hydrogens, 1, "{}", smiles);
+    }
+    // The chain bond keeps its own symbol
+    let mol = parse_smiles("C1CCCCC#1C").unwrap();
+    assert_eq!(mol.get_bond(0, 5).unwrap().bond_order, 3);
+    assert_eq!(mol.get_bond(5, 6).unwrap().bond_order, 1);
+    assert_eq!(parse_smiles("c1ccccc1").unwrap().bonds.len(), 6);
+
+    let error = parse_smiles("C=1CCCCC#1").err().unwrap();
+    assert_eq!(error.reason(), "ring bond 1 has conflicting bond symbols");
+    assert_eq!((error.column, error.token.as_str()), (9, "#1"));
+    assert!(parse_smiles("C11").is_err());
+    assert!(parse_smiles("C1C1").is_err());
+    assert_eq!(
+        parse_smiles("C%1CC%1").err().unwrap().kind,
+        ErrorKind::Expected("ring bond number")
+    );
+    assert_eq!(
+        parse_smiles("C%10CC").err().unwrap().kind,
+        ErrorKind::UnclosedRing(10)
+    );
+    assert!(parse_smiles("C%(12CC%(12)").is_err());
+}
+
+#[test]
+fn test_molfile_v2000() {
+    // Charge codes, a mass difference and a valence of 0 in the atom block,
+    // short lines without the trailing columns
+    let salt = parse_molfile(
+        "\
+salt
+  molrus
+
+  4  1  0  0  0  0  0  0  0  0999 V2000
+    0.0000    0.0000    0.0000 N   0  3
+    1.0000    0.0000    0.0000 C   1  0
+    3.0000    0.0000    0.0000 O   0  5
+    5.0000    0.0000    0.0000 Na  0  0  0  0  0 15
+  1  2  1
+M  END
+",
+    )
+    .unwrap();
+    let charges: Vec<i8> = salt.atoms.iter().map(|a| a.f_charge).collect();
+    assert_eq!(charges, vec![1, 0, -1, 0]);
+    let hydrogens: Vec<usize> = salt.atoms.iter().map(|a| a.hydrogens).collect();
+    assert_eq!(hydrogens, vec![3, 3, 1, 0]);
+    assert_eq!(salt.atoms[1].isotope, 13);
+    assert_eq!(salt.degree(0), 1);
+    assert_eq!(salt.bonds_of(1), &[0]);
+
+    // The properties block replaces atom block charges and isotopes
+    let radical = parse_molfile(
+        "
+  molrus
+
+  3  2  0  0  0  0  0  0  0  0999 V2000
+    0.0000    0.0000    0.0000 C   0  3  0  0  0  0  0  0  0  0  0  0
+    1.5000    0.0000    0.0000 C   0  0  0  0  0  0  0  0  0  0  0  0
+    2.0000    1.4000    0.0000 O   0  0  0  0  0  0  0  0  0  0  0  0
+  1  2  1  0  0  0  0
+  2  3  1  0  0  0  0
+M  CHG  1   3  -1
+M  RAD  1   1   2
+M  ISO  1   2  14
+M  END
+",
+    )
+    .unwrap();
+    assert_eq!(radical.name(), None);
+    let charges: Vec<i8> = radical.atoms.iter().map(|a| a.f_charge).collect();
+    assert_eq!(charges, vec![0, 0, -1]);
+    let hydrogens: Vec<usize> = radical.atoms.iter().map(|a| a.hydrogens).collect();
+    assert_eq!(hydrogens, vec![2, 2, 0]);
+    assert_eq!(radical.atoms[1].isotope, 14);
+
+    // Aromatic bonds are kekulized
+    let mut pyridine =
+        String::from("pyridine\n  molrus\n\n  6  6  0  0  0  0  0  0  0  0999 V2000\n");
+    for (i, symbol) in ["N", "C", "C", "C", "C", "C"].iter().enumerate() {
+        pyridine.push_str(&format!(
+            "{:10.4}{:10.4}{:10.4} {:<3} 0  0\n",
+            i as f64, 0.0, 0.0, symbol
+        ));
+    }
+    for i in 1..=6 {
+        pyridine.push_str(&format!("{:3}{:3}  4  0\n", i, i % 6 + 1));
+    }
+    pyridine.push_str("M  END\n");
+    // Five aromatic carbons have no Kekulé structure, the error points at
+    // the atom line of one left without a double bond
+    let mut carbocycle =
+        String::from("cyclopentadiene\n  molrus\n\n  5  5  0  0  0  0  0  0  0  0999 V2000\n");
+    for i in 0..5 {
+        carbocycle.push_str(&format!(
+            "{:10.4}{:10.4}{:10.4} C   0  0\n",
+            i as f64, 0.0, 0.0
+        ));
+    }
+    for i in 1..=5 {
+        carbocycle.push_str(&format!("{:3}{:3}  4  0\n", i, i % 5 + 1));
+    }
+    carbocycle.push_str("M  END\n");
+    let error = parse_molfile(&carbocycle).err().unwrap();
+    let ErrorKind::Kekulize(KekulizeError { unmatched }) = &error.kind else {
+        panic!("expected a kekulization error, got {:?}", error);
+    };
+    assert_eq!(error.line, 5 + unmatched[0]);
+    assert_eq!((error.column, error.token.as_str()), (32, "C"));
+    let pyridine = parse_molfile(&pyridine).unwrap();
+    assert!(pyridine.atoms.iter().all(|a| a.aromatic));
+    assert_eq!(
+        pyridine.bonds.iter().filter(|b| b.bond_order == 2).count(),
+        3
+    );
+    assert_eq!(pyridine.atoms[0].hydrogens, 0);
+    assert_eq!(pyridine.atoms[1].hydrogens, 1);
+}
+
+#[test]
+fn test_sdf_record_errors() {
+    let file = "\
+first
+  molrus
+
+  1  0  0  0  0  0  0  0  0  0999 V2000
+    0.0000    0.0000    0.0000 C   0  0
+M  END
+$$$$
+broken
+  molrus
+
+  2  1  0  0  0  0  0  0  0  0999 V2000
+    0.0000    0.0000    0.0000 C   0  0
+    1.0000    0.0000    0.0000 Xq  0  0
+  1  2  1  0
+M  END
+> <ID>
+2
+
+$$$$
+truncated
+  molrus
+
+  2  1  0  0  0  0  0  0  0  0999 V2000
+    0.0000    0.0000    0.0000 C   0  0
+$$$$
+last
+  molrus
+
+  1  0  0  0  0  0  0  0  0  0999 V2000
+    0.0000    0.0000    0.0000 O   0  0
+M  END
+$$$$
+
+";
+    let records: Vec<_> = SdfReader::new(file.as_bytes()).collect();
+    assert_eq!(records.len(), 4);
+    assert_eq!(records[0].as_ref().unwrap().name(), Some("first"));
+    let error = records[1].as_ref().err().unwrap();
+    assert_eq!(error.kind, ErrorKind::UnknownElement("Xq".to_string()));
+    assert_eq!((error.line, error.column), (13, 32));
+    let error = records[2].as_ref().err().unwrap();
+    assert_eq!(
+        (error.kind.clone(), error.line),
+        (ErrorKind::InvalidNumber, 25)
+    );
+    assert_eq!(records[3].as_ref().unwrap().name(), Some("last"));
+
+    let error = parse_molfile("bad\n\n\n  1  0  0  0  0  0  0  0  0  0999 V2000\n  1.0 C\n")
+        .err()
+        .unwrap();
+    assert_eq!(error.line, 5);
+    assert!(parse_molfile("short\n\n").is_err());
+    assert!(parse_molfile("").is_err());
+}
+
+#[test]
+fn test_molfile_v3000() {
+    let molfile = "\
+methylammonium
+  molrus
+
+  0  0  0     0  0            999 V3000
+M  V30 BEGIN CTAB
+M  V30 COUNTS 4 3 1 0 0
+M  V30 BEGIN ATOM
+M  V30 10 C 0.0 0.0 0.0 0
+M  V30 20 N 1.5 0.0 0.0 3 CHG=1 -
+M  V30 MASS=15
+M  V30 30 C 3.0 0.0 0.0 0 RAD=2
+M  V30 40 O -1.5 0.0 0.0 0 CHG=-1 VAL=-1
+M  V30 END ATOM
+M  V30 BEGIN BOND
+M  V30 1 1 10 20
+M  V30 2 1 20 30
+M  V30 3 1 10 40
+M  V30 END BOND
+M  V30 BEGIN SGROUP
+M  V30 1 SUP 0 ATOMS=(1 30) LABEL=\"Me group\"
+M  V30 END SGROUP
+M  V30 BEGIN COLLECTION
+M  V30 MDLV30/STERAC1 ATOMS=(2 10 -
+M  V30 20)
+M  V30 END COLLECTION
+M  V30 END CTAB
+M  END
+> <ID>
+7
+
+$$$$
+";
+    let mol = parse_molfile(molfile).unwrap();
+    assert_eq!(mol.name(), Some("methylammonium"));
+    assert_eq!((mol.atoms.len(), mol.bonds.len()), (4, 3));
+    let charges: Vec<i8> = mol.atoms.iter().map(|a| a.f_charge).collect();
+    assert_eq!(charges, vec![0, 1, 0, -1]);
+    let hydrogens: Vec<usize> = mol.atoms.iter().map(|a| a.hydrogens).collect();
+    assert_eq!(hydrogens, vec![2, 2, 2, 0]);
+    assert_eq!((mol.atoms[1].isotope, mol.atoms[1].atom_map), (15, 3));
+    assert_eq!(mol.atoms[3].coords_3d, Some((-1.5, 0.0, 0.0)));
+    assert_eq!(mol.degree(0), 2);
+    let group = |atom_idx: usize| mol.atoms[atom_idx].properties.get(STEREO_GROUP).cloned();
+    assert_eq!(group(0), Some(Property::from("and1")));
+    assert_eq!(group(2), None);
+    assert_eq!(mol.properties.get("ID"), Some(&Property::from("7")));
+    // SGroups are kept with their atoms numbered by index
+    assert_eq!(
+        mol.properties.get(SGROUPS),
+        Some(&Property::from("1 SUP 0 ATOMS=(1 3) LABEL=\"Me group\""))
+    );
+
+    let atom_list = molfile.replace("30 C 3.0", "30 [N,O] 3.0");
+    assert_eq!(
+        parse_molfile(&atom_list).err().unwrap().reason(),
+        "atom lists are not supported"
+    );
+    let wrong_count = molfile.replace("COUNTS 4 3", "COUNTS 5 3");
+    assert_eq!(parse_molfile(&wrong_count).err().unwrap().line, 6);
+    let unknown_atom = molfile.replace("3 1 10 40", "3 1 10 50");
+    assert!(parse_molfile(&unknown_atom).is_err());
+    let unknown_sgroup_atom = molfile.replace("ATOMS=(1 30)", "ATOMS=(1 50)");
+    assert_eq!(
+        parse_molfile(&unknown_sgroup_atom).err().unwrap().reason(),
+        "no atom 50 in the atom block"
+    );
+    // Kekulization errors point at the symbol of an atom left without a
+    // double bond
+    let mut carbocycle = String::from("cyclopentadiene\n  molrus\n\n");
+    carbocycle.push_str("  0  0  0     0  0            999 V3000\nM  V30 BEGIN CTAB\n");
+    carbocycle.push_str("M  V30 COUNTS 5 5 0 0 0\nM  V30 BEGIN ATOM\n");
+    for i in 1..=5 {
+        carbocycle.push_str(&format!("M  V30 {} C {}.0 0.0 0.0 0\n", i, i));
+    }
+    carbocycle.push_str("M  V30 END ATOM\nM  V30 BEGIN BOND\n");
+    for i in 1..=5 {
+        carbocycle.push_str(&format!("M  V30 {} 4 {} {}\n", i, i, i % 5 + 1));
+    }
+    carbocycle.push_str("M  V30 END BOND\nM  V30 END CTAB\nM  END\n");
+    let error = parse_molfile(&carbocycle).err().unwrap();
+    let ErrorKind::Kekulize(KekulizeError { unmatched }) = &error.kind else {
+        panic!("expected a kekulization error, got {:?}", error);
+    };
+    assert_eq!(error.line, 8 + unmatched[0]);
+    assert_eq!((error.column, error.token.as_str()), (10, "C"));
+}
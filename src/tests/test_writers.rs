@@ -0,0 +1,461 @@
+#[cfg(test)]
+use crate::{core::molecule::Molecule, parsers::daylight::smiles::parse_smiles};
+
+#[cfg(test)]
+type GraphSummary = (
+    Vec<(usize, i8, usize, usize, bool)>,
+    Vec<(usize, usize, i8)>,
+);
+
+// Order independent summary of atoms and bonds, used to compare round trips
+#[cfg(test)]
+fn graph_summary(mol: &Molecule) -> GraphSummary {
+    let mut atoms: Vec<_> = mol
+        .atoms
+        .iter()
+        .map(|a| (a.element, a.f_charge, a.isotope, a.hydrogens, a.aromatic))
+        .collect();
+    atoms.sort();
+    let mut bonds: Vec<_> = mol
+        .bonds
+        .iter()
+        .map(|b| {
+            let (x, y) = (mol.atoms[b.source].element, mol.atoms[b.dest].element);
+            (x.min(y), x.max(y), b.bond_order)
+        })
+        .collect();
+    bonds.sort();
+    (atoms, bonds)
+}
+
+#[test]
+fn test_smiles_writer_symbols() {
+    let mol = parse_smiles("ClCC(=O)[O-]").unwrap();
+    assert_eq!(mol.mol_to_smiles(false), "ClCC(=O)[O-]");
+
+    let mol = parse_smiles("[NH4+].[Cl-]").unwrap();
+    assert_eq!(mol.mol_to_smiles(false), "[NH4+].[Cl-]");
+
+    let mol = parse_smiles("[13CH4]").unwrap();
+    assert_eq!(mol.mol_to_smiles(false), "[13CH4]");
+
+    // Hydrogen counts that differ from the default valence need brackets
+    let mol = parse_smiles("[CH3]C[CH2]").unwrap();
+    assert_eq!(mol.mol_to_smiles(false), "CC[CH2]");
+    let mol = parse_smiles("[Fe+2]").unwrap();
+    assert_eq!(mol.mol_to_smiles(false), "[Fe+2]");
+}
+
+#[test]
+fn test_smiles_writer_chirality() {
+    let mol = parse_smiles("C[C@H](O)CC").unwrap();
+    assert_eq!(mol.mol_to_smiles(false), "C[C@H](O)CC");
+
+    // Starting from the stereocentre moves the hydrogen to the front
+    let mol = parse_smiles("F[C@H](Cl)Br").unwrap();
+    assert_eq!(
+        mol.mol_to_smiles(true),
+        parse_smiles("[C@@H](F)(Cl)Br").unwrap().mol_to_smiles(true)
+    );
+
+    let mol = parse_smiles("F/C=C\\F").unwrap();
+    assert_eq!(mol.mol_to_smiles(false), "F/C=C\\F");
+}
+
+#[test]
+fn test_smiles_round_trip() {
+    for smiles in [
+        "CC(=O)O",
+        "CC(C(C)C)(CC)N",
+        "C[C@@H](N)C(=O)O",
+        "OCC(O)CBr",
+        "C#N.[Na+]",
+    ] {
+        let mol = parse_smiles(smiles).unwrap();
+        for canonical in [false, true] {
+            let written = mol.mol_to_smiles(canonical);
+            let reparsed = parse_smiles(&written).unwrap();
+            assert_eq!(graph_summary(&mol), graph_summary(&reparsed), "{}", written);
+            assert_eq!(reparsed.mol_to_smiles(canonical), written);
+        }
+    }
+}
+
+#[test]
+fn test_smiles_writer_kekule() {
+    let mol = parse_smiles("Cc1ccccc1").unwrap();
+    assert_eq!(mol.mol_to_smiles(false), "Cc1ccccc1");
+    let kekule = mol.mol_to_kekule_smiles(false).unwrap();
+    assert_eq!(kekule, "CC1=CC=CC=C1");
+
+    // Reading it back perceives the same aromatic molecule
+    let reread = parse_smiles(&kekule).unwrap();
+    assert_eq!(graph_summary(&reread), graph_summary(&mol));
+    assert_eq!(reread.mol_to_smiles(false), "Cc1ccccc1");
+
+    // A double ring closure bond goes on the opening digit
+    let pyrrole = parse_smiles("c1cc[nH]c1").unwrap();
+    assert_eq!(pyrrole.mol_to_kekule_smiles(false).unwrap(), "C=1C=CNC1");
+    let reread = parse_smiles("C=1C=CNC1").unwrap();
+    assert_eq!(graph_summary(&reread).1, graph_summary(&pyrrole).1);
+    assert_eq!(reread.atoms[3].hydrogens, 1);
+}
+
+#[test]
+fn test_smiles_records() {
+    let mol = parse_smiles("OCC ethanol, absolute").unwrap();
+    assert_eq!(mol.name(), Some("ethanol, absolute"));
+    assert_eq!(mol.mol_to_smiles_record(true), "CCO ethanol, absolute");
+    assert_eq!(
+        parse_smiles("CCO").unwrap().mol_to_smiles_record(false),
+        "CCO"
+    );
+
+    let rxn = parse_reaction_smiles("CC=O>>CCO\treduction").unwrap();
+    assert_eq!(rxn.rxn_to_smiles_record(false), "CC=O>>CCO reduction");
+}
+
+#[test]
+fn test_atom_map_and_reaction_writer() {
+    let mapped = parse_smiles("[CH3:1][OH:2]").unwrap();
+    assert_eq!(mapped.mol_to_smiles(false), "[CH3:1][OH:2]");
+    assert_eq!(
+        parse_smiles("C[CH3:7]").unwrap().mol_to_smiles(false),
+        "C[CH3:7]"
+    );
+
+    let rxn = parse_reaction_smiles("[CH3:1][OH:2].[Na+]>CO>[Na+].[CH3:1][O-:2]").unwrap();
+    assert_eq!(
+        rxn.rxn_to_smiles(false),
+        "[CH3:1][OH:2].[Na+]>CO>[Na+].[CH3:1][O-:2]"
+    );
+    let reordered = parse_reaction_smiles("[Na+].[OH:2][CH3:1]>OC>[O-:2][CH3:1].[Na+]").unwrap();
+    assert_eq!(rxn.rxn_to_smiles(true), reordered.rxn_to_smiles(true));
+    assert_eq!(
+        parse_reaction_smiles("CC=O>>CCO")
+            .unwrap()
+            .rxn_to_smiles(false),
+        "CC=O>>CCO"
+    );
+}
+
+#[cfg(test)]
+use crate::parsers::{
+    binary::read_binary, daylight::reaction::parse_reaction_smiles, error::ErrorKind,
+};
+
+// A molecule with stereo, coordinates, aromaticity and perceived rings
+#[cfg(test)]
+fn decorated_molecule() -> Molecule {
+    let mut mol = parse_smiles("[13CH3][C@@H](F/C=C/Cl)c1cc[nH]c1.[O-2]").unwrap();
+    for (atom_idx, atom) in mol.atoms.iter_mut().enumerate() {
+        atom.coords_3d = Some((atom_idx as f64 * 1.5, -0.25, 1e-9));
+    }
+    mol.set_name("decorated");
+    mol.properties.set("IC50", 0.25);
+    mol.properties.set("count", 3);
+    mol.properties.set("active", true);
+    mol.atoms[1].properties.set("label", "centre");
+    mol.bonds[0].properties.set("length", 1.54);
+    mol
+}
+
+#[test]
+fn test_binary_round_trip() {
+    let mol = decorated_molecule();
+    let bytes = mol.to_binary();
+    let decoded = read_binary(&bytes).unwrap();
+    assert_eq!(decoded.to_binary(), bytes);
+    assert_eq!(decoded.mol_to_smiles(false), mol.mol_to_smiles(false));
+    assert_eq!(decoded.atoms[3].coords_3d, mol.atoms[3].coords_3d);
+    assert_eq!(decoded.atoms[1].configuration, mol.atoms[1].configuration);
+    assert_eq!(decoded.properties, mol.properties);
+    assert_eq!(decoded.atoms[1].properties, mol.atoms[1].properties);
+    assert_eq!(decoded.bonds[0].properties, mol.bonds[0].properties);
+    assert!(decoded.ring_info().is_some());
+
+    assert_eq!(
+        read_binary(&Molecule::new().to_binary())
+            .unwrap()
+            .atoms
+            .len(),
+        0
+    );
+    assert_eq!(
+        read_binary(&bytes[..bytes.len() - 1]).err().unwrap().kind,
+        ErrorKind::UnexpectedEnd
+    );
+    let error = read_binary(b"XRB\x01\x00\x00\x00").err().unwrap();
+    assert_eq!(error.reason(), "not molrus binary data");
+    assert_eq!(error.column, 1);
+    let mut trailing = bytes.clone();
+    trailing.push(0);
+    assert!(read_binary(&trailing).is_err());
+    let mut newer = bytes.clone();
+    newer[3] = 2;
+    let error = read_binary(&newer).err().unwrap();
+    assert_eq!(error.reason(), "unsupported format version 2");
+    assert_eq!(error.column, 4);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde_round_trip() {
+    let mol = decorated_molecule();
+    let json = serde_json::to_string(&mol).unwrap();
+    let decoded: Molecule = serde_json::from_str(&json).unwrap();
+    // Everything but the ring cache, which is not serialized
+    let (bytes, decoded_bytes) = (mol.to_binary(), decoded.to_binary());
+    assert_eq!(decoded_bytes[..bytes.len() - 1], bytes[..bytes.len() - 1]);
+
+    let sodium = parse_smiles("[Na+]").unwrap();
+    assert_eq!(
+        serde_json::to_string(&sodium).unwrap(),
+        concat!(
+            r#"{"atoms":[{"outgoing_bond":[],"element":11,"isotope":0,"hydrogens":0,"#,
+            r#""aromatic":false,"f_charge":1,"configuration":null,"ring":false,"#,
+            r#""symmetry_class":0,"coords_3d":null,"atom_map":0,"properties":[]}],"#,
+            r#""bonds":[],"properties":[]}"#
+        )
+    );
+}
+
+#[cfg(test)]
+use crate::{
+    core::{
+        cip::CipLabel,
+        properties::{Property, SGROUPS, STEREO_GROUP},
+    },
+    parsers::sdf::sdf::{parse_molfile, read_sdf, SdfReader},
+    writer::molfile::{write_sdf, MolfileError},
+};
+
+#[test]
+fn test_molfile_v3000_writer() {
+    // Charges, isotopes and hydrogen counts the default valences do not give
+    let mol = parse_smiles("[13CH3][N+](C)(C)C.[O-]C(=O)c1cc[nH]c1.[CH3].[Na]").unwrap();
+    let molfile = mol.mol_to_molfile_v3000().unwrap();
+    assert!(molfile.ends_with("M  V30 END CTAB\nM  END\n"));
+    let read = parse_molfile(&molfile).unwrap();
+    assert_eq!(read.mol_to_smiles(true), mol.mol_to_smiles(true));
+
+    // A stereocentre drawn flat keeps its configuration through a wedge
+    let mut chiral = parse_smiles("F[C@H](Cl)Br").unwrap();
+    let coords = [(0.0, 1.0), (0.0, 0.0), (0.87, -0.5), (-0.87, -0.5)];
+    for (atom, (x, y)) in chiral.atoms.iter_mut().zip(coords) {
+        atom.coords_3d = Some((x, y, 0.0));
+    }
+    chiral.atoms[1].properties.set(STEREO_GROUP, "or1");
+    chiral.set_name("halothane fragment");
+    let molfile = chiral.mol_to_molfile_v3000().unwrap();
+    assert!(molfile.contains(" CFG=1") || molfile.contains(" CFG=3"));
+    assert!(molfile.contains("MDLV30/STEREL1 ATOMS=(1 2)"));
+    let read = parse_molfile(&molfile).unwrap();
+    assert_eq!(read.name(), Some("halothane fragment"));
+    assert_eq!(read.cip_labels().atoms[1], chiral.cip_labels().atoms[1]);
+    assert!(matches!(
+        read.cip_labels().atoms[1],
+        Some(CipLabel::R | CipLabel::S)
+    ));
+    assert_eq!(
+        read.atoms[1].properties.get(STEREO_GROUP),
+        chiral.atoms[1].properties.get(STEREO_GROUP)
+    );
+
+    // Past the V2000 limit of 999 atoms, with long lines continued
+    let mut chain = parse_smiles(&"C".repeat(1200)).unwrap();
+    for atom_idx in 0..chain.atoms.len() {
+        chain.atoms[atom_idx].properties.set(STEREO_GROUP, "abs");
+    }
+    let molfile = chain.mol_to_molfile_v3000().unwrap();
+    assert!(molfile.lines().all(|line| line.len() <= 80));
+    let read = parse_molfile(&molfile).unwrap();
+    assert_eq!((read.atoms.len(), read.bonds.len()), (1200, 1199));
+    assert_eq!(read.get_bond(998, 999).map(|b| b.bond_order), Some(1));
+    assert!(read
+        .atoms
+        .iter()
+        .all(|a| a.properties.get(STEREO_GROUP).is_some()));
+    // SGroups are written back with the atoms and bonds renumbered
+    let acetic_acid = "\
+acetic acid
+  molrus            2D
+
+  0  0  0     0  0            999 V3000
+M  V30 BEGIN CTAB
+M  V30 COUNTS 4 3 2 0 0
+M  V30 BEGIN ATOM
+M  V30 5 C 0.0 0.0 0.0 0
+M  V30 6 C 1.5 0.0 0.0 0
+M  V30 7 O 2.25 1.3 0.0 0
+M  V30 8 O 2.25 -1.3 0.0 0
+M  V30 END ATOM
+M  V30 BEGIN BOND
+M  V30 11 1 5 6
+M  V30 12 2 6 7
+M  V30 13 1 6 8
+M  V30 END BOND
+M  V30 BEGIN SGROUP
+M  V30 1 SUP 0 ATOMS=(3 6 7 8) XBONDS=(1 11) CSTATE=(4 11 -1.5 0.0 0.0) -
+M  V30 LABEL=\"carboxylic acid group\" SAP=(3 6 5 1)
+M  V30 2 DAT 0 ATOMS=(1 5) FIELDNAME=note FIELDDATA=\"methyl\"
+M  V30 END SGROUP
+M  V30 END CTAB
+M  END
+";
+    let mol = parse_molfile(acetic_acid).unwrap();
+    let sgroups = "\
+1 SUP 0 ATOMS=(3 2 3 4) XBONDS=(1 1) CSTATE=(4 1 -1.5 0.0 0.0) \
+LABEL=\"carboxylic acid group\" SAP=(3 2 1 1)
+2 DAT 0 ATOMS=(1 1) FIELDNAME=note FIELDDATA=\"methyl\"";
+    assert_eq!(mol.properties.get(SGROUPS), Some(&Property::from(sgroups)));
+    let molfile = mol.mol_to_molfile().unwrap();
+    assert!(molfile.contains("M  V30 COUNTS 4 3 2 0 0\n"));
+    let read = parse_molfile(&molfile).unwrap();
+    assert_eq!(read.properties.get(SGROUPS), Some(&Property::from(sgroups)));
+    assert!(!mol.mol_to_sdf_record().unwrap().contains(SGROUPS));
+}
+
+#[test]
+fn test_molfile_v2000_writer() {
+    let mut mol = parse_smiles("C[NH3+]").unwrap();
+    mol.atoms[0].coords_3d = Some((-1.5, 0.25, 0.0));
+    mol.atoms[1].coords_3d = Some((0.0, 0.0, 0.0));
+    mol.atoms[1].atom_map = 7;
+    mol.set_name("methylammonium");
+    assert_eq!(
+        mol.mol_to_molfile().unwrap(),
+        "\
+methylammonium
+  molrus            2D
+
+  2  1  0  0  0  0  0  0  0  0999 V2000
+   -1.5000    0.2500    0.0000 C   0  0  0  0  0  0  0  0  0  0  0  0
+    0.0000    0.0000    0.0000 N   0  3  0  0  0  0  0  0  0  7  0  0
+  1  2  1  0
+M  CHG  1   2   1
+M  END
+"
+    );
+
+    // Past the V2000 limits the molfile falls back to V3000
+    mol.atoms[0].properties.set(STEREO_GROUP, "abs");
+    assert!(mol.mol_to_molfile().unwrap().contains(" V3000\n"));
+    let chain = parse_smiles(&"C".repeat(1000)).unwrap();
+    assert!(chain
+        .mol_to_molfile()
+        .unwrap()
+        .contains("M  V30 COUNTS 1000 999 0 0 0"));
+
+    // Aromatic bonds go out in their Kekulé form, type 4 would read back
+    // as aromatic, so a quadruple bond has no type to be written as
+    let pyridine = parse_smiles("c1ccncc1").unwrap();
+    let read = parse_molfile(&pyridine.mol_to_molfile().unwrap()).unwrap();
+    assert_eq!(read.mol_to_smiles(true), pyridine.mol_to_smiles(true));
+    let dichromium = parse_smiles("[Cr]$[Cr]").unwrap();
+    assert_eq!(
+        dichromium.mol_to_molfile(),
+        Err(MolfileError::BondOrder { bond: 0, order: 4 })
+    );
+    let path = std::env::temp_dir().join("molrus_test_quadruple_bond.sdf");
+    let error = write_sdf(&[dichromium], path.to_str().unwrap()).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+    assert!(!path.exists());
+    // Without coordinates tetrahedral stereo goes in the parity column,
+    // other stereo has nowhere to go
+    for smiles in ["C[C@H](N)O", "F[C@]1(Cl)CC[C@H](F)CC1"] {
+        let mol = parse_smiles(smiles).unwrap();
+        for molfile in [mol.mol_to_molfile(), mol.mol_to_molfile_v3000()] {
+            let read = parse_molfile(&molfile.unwrap()).unwrap();
+            assert_eq!(read.mol_to_smiles(true), mol.mol_to_smiles(true));
+        }
+    }
+    assert_eq!(
+        parse_smiles("C/C=C/C").unwrap().mol_to_molfile(),
+        Err(MolfileError::DoubleBondStereo { bond: 1 })
+    );
+    assert_eq!(
+        parse_smiles("C[S@](=O)CC").unwrap().mol_to_molfile_v3000(),
+        Err(MolfileError::Stereocentre { atom: 1 })
+    );
+}
+
+#[test]
+fn test_sdf_round_trip() {
+    let mut first = parse_smiles("[13CH3][C@@H](N)C(=O)[O-].[NH4+].[CH3] alanine").unwrap();
+    let coords = [
+        (0.0, 1.5),
+        (0.0, 0.0),
+        (1.3, -0.75),
+        (-1.3, -0.75),
+        (-2.6, 0.0),
+    ];
+    for (atom_idx, atom) in first.atoms.iter_mut().enumerate() {
+        let (x, y) = coords
+            .get(atom_idx)
+            .copied()
+            .unwrap_or((atom_idx as f64, 3.0));
+        atom.coords_3d = Some((x, y, 0.0));
+    }
+    first.atoms[2].atom_map = 1;
+    first.properties.set("ID", "007");
+    first.properties.set("count", 12);
+    first.properties.set("pIC50", 6.5);
+    first.properties.set("active", true);
+    first.properties.set("note", "two\nlines");
+    let second = parse_smiles("c1ccc2[nH]ccc2c1.[Na]").unwrap();
+
+    let path = std::env::temp_dir().join("molrus_test_sdf_round_trip.sdf");
+    write_sdf(&[first.clone(), second.clone()], path.to_str().unwrap()).unwrap();
+    let molecules: Vec<Molecule> = read_sdf(path.to_str().unwrap())
+        .unwrap()
+        .into_iter()
+        .map(Result::unwrap)
+        .collect();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(molecules.len(), 2);
+    for (read, written) in molecules.iter().zip([&first, &second]) {
+        assert_eq!(read.mol_to_smiles(true), written.mol_to_smiles(true));
+        // Every value comes back as its text
+        let text = |m: &Molecule| {
+            (m.properties.iter())
+                .map(|(name, value)| (name.to_string(), Property::from(value.to_string())))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(text(read), text(written));
+        let coords = |m: &Molecule| m.atoms.iter().map(|a| a.coords_3d).collect::<Vec<_>>();
+        assert!(written.atoms[0].coords_3d.is_none() || coords(read) == coords(written));
+    }
+    assert_eq!(molecules[0].name(), Some("alanine"));
+    assert_eq!(
+        molecules[0].cip_labels().atoms[1],
+        first.cip_labels().atoms[1]
+    );
+    assert_eq!(molecules[1].atoms.last().unwrap().hydrogens, 0);
+    let count = molecules[0].properties.get("count");
+    assert_eq!(count, Some(&Property::from("12")));
+
+    // Strings that look like numbers stay strings, values and names that
+    // would end the data item early are refused
+    let mut mol = parse_smiles("C").unwrap();
+    mol.properties.set("ID", "42");
+    let record = mol.mol_to_sdf_record().unwrap();
+    let read = SdfReader::new(record.as_bytes()).next().unwrap().unwrap();
+    assert_eq!(read.properties.get("ID"), Some(&Property::from("42")));
+    for (name, value) in [
+        ("note", "first\n\nsecond"),
+        ("note", "first\n"),
+        ("note", "$$$$"),
+        ("a<b>", "1"),
+    ] {
+        let mut mol = mol.clone();
+        mol.properties.set(name, value);
+        assert_eq!(
+            mol.mol_to_sdf_record(),
+            Err(MolfileError::DataItem {
+                name: name.to_string()
+            })
+        );
+    }
+}
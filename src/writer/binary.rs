@@ -0,0 +1,117 @@
+// Compact binary encoding of a molecule, read back by `parsers::binary`.
+//
+// Layout, integers as LEB128 varints unless noted:
+//   magic "MRB", format version (u8)
+//   atom count, then per atom: element, isotope, hydrogens, charge (i8),
+//     flags (u8: aromatic, ring, has configuration, has coordinates),
+//     symmetry class, atom map, configuration (u8) if any, x y z (f64 LE) if any,
+//     outgoing bond count and indices, properties
+//   bond count, then per bond: source, dest, order (i8),
+//     flags (u8: aromatic, ring, axialness in bits 2-3), properties
+//   molecule properties
+//   rings perceived (u8)
+//
+// Properties are a count, then per property: name, a tag (u8: string, int,
+// float, bool) and the value. Strings are a byte length and UTF-8, ints and
+// floats 8 bytes LE, bools one byte.
+use crate::core::{
+    defs::Axialness,
+    molecule::Molecule,
+    properties::{Property, PropertyMap},
+};
+
+pub const BINARY_MAGIC: &[u8; 3] = b"MRB";
+pub const BINARY_VERSION: u8 = 1;
+
+impl Molecule {
+    /// Lossless binary form of the molecule, see `parsers::binary::read_binary`.
+    pub fn to_binary(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(16 + 12 * self.atoms.len() + 5 * self.bonds.len());
+        out.extend_from_slice(BINARY_MAGIC);
+        out.push(BINARY_VERSION);
+
+        write_varint(&mut out, self.atoms.len());
+        for atom in &self.atoms {
+            write_varint(&mut out, atom.element);
+            write_varint(&mut out, atom.isotope);
+            write_varint(&mut out, atom.hydrogens);
+            out.push(atom.f_charge as u8);
+            let flags = u8::from(atom.aromatic)
+                | u8::from(atom.ring) << 1
+                | u8::from(atom.configuration.is_some()) << 2
+                | u8::from(atom.coords_3d.is_some()) << 3;
+            out.push(flags);
+            write_varint(&mut out, atom.symmetry_class);
+            write_varint(&mut out, atom.atom_map);
+            if let Some(configuration) = &atom.configuration {
+                out.push(configuration.clone() as u8);
+            }
+            if let Some((x, y, z)) = atom.coords_3d {
+                for value in [x, y, z] {
+                    out.extend_from_slice(&value.to_le_bytes());
+                }
+            }
+            write_varint(&mut out, atom.outgoing_bond.len());
+            for &bond_idx in &atom.outgoing_bond {
+                write_varint(&mut out, bond_idx);
+            }
+            write_properties(&mut out, &atom.properties);
+        }
+
+        write_varint(&mut out, self.bonds.len());
+        for bond in &self.bonds {
+            write_varint(&mut out, bond.source);
+            write_varint(&mut out, bond.dest);
+            out.push(bond.bond_order as u8);
+            let axialness = match bond.axialness {
+                Axialness::UNKNOWN => 0,
+                Axialness::UP => 1,
+                Axialness::DOWN => 2,
+            };
+            out.push(u8::from(bond.arom) | u8::from(bond.ring) << 1 | axialness << 2);
+            write_properties(&mut out, &bond.properties);
+        }
+        write_properties(&mut out, &self.properties);
+
+        out.push(u8::from(self.ring_info().is_some()));
+        out
+    }
+}
+
+fn write_varint(out: &mut Vec<u8>, mut value: usize) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn write_string(out: &mut Vec<u8>, value: &str) {
+    write_varint(out, value.len());
+    out.extend_from_slice(value.as_bytes());
+}
+
+fn write_properties(out: &mut Vec<u8>, properties: &PropertyMap) {
+    write_varint(out, properties.len());
+    for (name, value) in properties.iter() {
+        write_string(out, name);
+        match value {
+            Property::String(value) => {
+                out.push(0);
+                write_string(out, value);
+            }
+            Property::Int(value) => {
+                out.push(1);
+                out.extend_from_slice(&value.to_le_bytes());
+            }
+            Property::Float(value) => {
+                out.push(2);
+                out.extend_from_slice(&value.to_le_bytes());
+            }
+            Property::Bool(value) => {
+                out.push(3);
+                out.push(u8::from(*value));
+            }
+        }
+    }
+}
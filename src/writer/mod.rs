@@ -1 +1,3 @@
+pub mod binary;
+pub mod molfile;
 pub mod smiles;
@@ -0,0 +1,411 @@
+// MDL molfiles and SD files, read back by `parsers::sdf`.
+//
+// Molfiles are V2000 unless the molecule does not fit: more than 999 atoms
+// or bonds, values too wide for their columns, or enhanced stereo groups and
+// SGroups, which are only written in V3000.
+//
+// Bonds are written in their Kekulé form, orders without an MDL bond type
+// such as quadruple bonds are an error rather than a guess; hydrogen counts the default
+// valences would not give back are kept with a valence mark. Stereocentres
+// are drawn with wedges when the coordinates are flat, stereogenic double
+// bonds without marks are crossed. Atoms without coordinates are written at
+// the origin; with every atom there the stereo is read back from the parity
+// column, and stereo that has no parity, like that of double bonds, is an
+// error.
+use std::{collections::BTreeMap, fmt, fs, io};
+
+use crate::core::{
+    mendeleev::symbol,
+    molecule::Molecule,
+    properties::{NAME, SGROUPS, STEREO_GROUP},
+    stereo_perception::BondStereo,
+    valence::implicit_h_count,
+};
+
+// V3000 lines are at most 80 characters, "M  V30 " and the '-' included
+const V30_WIDTH: usize = 72;
+
+// Entries on one "M  CHG" or "M  ISO" line
+const ENTRIES_PER_LINE: usize = 8;
+
+/// A molecule the molfile format can't hold.
+#[derive(Clone, Debug, PartialEq)]
+pub enum MolfileError {
+    /// A bond order with no MDL bond type, like a quadruple bond. Type 4 is
+    /// aromatic, not quadruple.
+    BondOrder { bond: usize, order: i8 },
+    /// A stereocentre with no MDL parity, in a molecule without coordinates.
+    Stereocentre { atom: usize },
+    /// A stereogenic double bond in a molecule without coordinates.
+    DoubleBondStereo { bond: usize },
+    /// An SD data item that would not read back the same: a blank or
+    /// "$$$$" line in its value, or '<', '>' or a line break in its name.
+    DataItem { name: String },
+}
+
+impl fmt::Display for MolfileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MolfileError::BondOrder { bond, order } => {
+                write!(f, "no MDL bond type for bond {} of order {}", bond, order)
+            }
+            MolfileError::Stereocentre { atom } => {
+                write!(f, "no coordinates or parity for stereocentre {}", atom)
+            }
+            MolfileError::DoubleBondStereo { bond } => {
+                write!(f, "no coordinates for stereogenic double bond {}", bond)
+            }
+            MolfileError::DataItem { name } => {
+                write!(f, "SD data item {:?} would not read back", name)
+            }
+        }
+    }
+}
+
+impl std::error::Error for MolfileError {}
+
+/// Writes `molecules` to an SD file at `file_path`, see `mol_to_sdf_record`.
+/// A molecule the format can't hold is an `InvalidData` error and nothing
+/// is written.
+pub fn write_sdf(molecules: &[Molecule], file_path: &str) -> io::Result<()> {
+    let records = molecules
+        .iter()
+        .map(|m| m.mol_to_sdf_record())
+        .collect::<Result<String, _>>()
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    fs::write(file_path, records)
+}
+
+impl Molecule {
+    /// Molfile of the molecule, V2000 when it fits and V3000 otherwise, up
+    /// to and including "M  END".
+    pub fn mol_to_molfile(&self) -> Result<String, MolfileError> {
+        self.check_stereo()?;
+        if self.fits_v2000() {
+            self.molfile_v2000()
+        } else {
+            self.mol_to_molfile_v3000()
+        }
+    }
+
+    /// SD file record: the molfile, then every property but the name and
+    /// the SGroups as a data item, then "$$$$". Values are written as text and read back as
+    /// strings.
+    pub fn mol_to_sdf_record(&self) -> Result<String, MolfileError> {
+        let mut out = self.mol_to_molfile()?;
+        for (name, value) in self.properties.iter() {
+            if name == NAME || name == SGROUPS {
+                continue;
+            }
+            let value = value.to_string();
+            let unreadable_name = name.contains(['<', '>', '\n', '\r']);
+            let unreadable_value = !value.is_empty()
+                && value
+                    .split('\n')
+                    .any(|line| line.trim().is_empty() || line.trim() == "$$$$");
+            if unreadable_name || unreadable_value {
+                return Err(MolfileError::DataItem {
+                    name: name.to_string(),
+                });
+            }
+            out.push_str(&format!("> <{}>\n{}\n\n", name, value));
+        }
+        out.push_str("$$$$\n");
+        Ok(out)
+    }
+
+    fn fits_v2000(&self) -> bool {
+        self.atoms.len() <= 999
+            && self.bonds.len() <= 999
+            && self.stereo_groups().is_empty()
+            && self.properties.get(SGROUPS).is_none()
+            && self.atoms.iter().all(|a| {
+                let (x, y, z) = a.coords_3d.unwrap_or_default();
+                // Coordinates get ten characters, four of them decimals
+                [x, y, z].iter().all(|c| (-9999.0..99999.0).contains(c))
+                    && (-15..=15).contains(&a.f_charge)
+                    && a.isotope <= 999
+                    && a.atom_map <= 999
+            })
+    }
+
+    fn molfile_v2000(&self) -> Result<String, MolfileError> {
+        let bond_types = self.mdl_bond_types()?;
+        let mut out = self.molfile_header();
+        out.push_str(&format!(
+            "{:3}{:3}  0  0  0  0  0  0  0  0999 V2000\n",
+            self.atoms.len(),
+            self.bonds.len()
+        ));
+
+        for (atom_idx, atom) in self.atoms.iter().enumerate() {
+            let (x, y, z) = atom.coords_3d.unwrap_or_default();
+            // Charges -3 to 3 also go in the atom block for older readers
+            let charge_code = match atom.f_charge {
+                charge @ (-3..=-1 | 1..=3) => 4 - charge,
+                _ => 0,
+            };
+            let valence = match self.valence_mark(atom_idx) {
+                Some(0) => 15,
+                Some(valence) => valence,
+                None => 0,
+            };
+            out.push_str(&format!(
+                "{:10.4}{:10.4}{:10.4} {:<3} 0{:3}{:3}  0  0{:3}  0  0  0{:3}  0  0\n",
+                x,
+                y,
+                z,
+                symbol(atom.element),
+                charge_code,
+                self.mdl_parity(atom_idx).unwrap_or(0),
+                valence,
+                atom.atom_map
+            ));
+        }
+
+        for (bond_idx, (atom1, atom2, stereo)) in self.written_bonds().into_iter().enumerate() {
+            let stereo = match stereo {
+                BondStereo::None => 0,
+                BondStereo::Wedge => 1,
+                BondStereo::Hash => 6,
+                BondStereo::Either if bond_types[bond_idx] == 2 => 3,
+                BondStereo::Either => 4,
+            };
+            out.push_str(&format!(
+                "{:3}{:3}{:3}{:3}\n",
+                atom1 + 1,
+                atom2 + 1,
+                bond_types[bond_idx],
+                stereo
+            ));
+        }
+
+        let charges: Vec<(usize, i64)> = (self.atoms.iter().enumerate())
+            .filter(|(_, a)| a.f_charge != 0)
+            .map(|(atom_idx, a)| (atom_idx, a.f_charge as i64))
+            .collect();
+        push_property_lines(&mut out, "CHG", &charges);
+        let isotopes: Vec<(usize, i64)> = (self.atoms.iter().enumerate())
+            .filter(|(_, a)| a.isotope != 0)
+            .map(|(atom_idx, a)| (atom_idx, a.isotope as i64))
+            .collect();
+        push_property_lines(&mut out, "ISO", &isotopes);
+        out.push_str("M  END\n");
+        Ok(out)
+    }
+
+    /// V3000 molfile of the molecule, up to and including "M  END".
+    pub fn mol_to_molfile_v3000(&self) -> Result<String, MolfileError> {
+        self.check_stereo()?;
+        let bond_types = self.mdl_bond_types()?;
+        let mut out = self.molfile_header();
+        out.push_str("  0  0  0     0  0            999 V3000\n");
+        push_v30(&mut out, "BEGIN CTAB");
+        let sgroups: Vec<&str> = (self.properties.get(SGROUPS))
+            .and_then(|p| p.as_str())
+            .map_or(Vec::new(), |s| s.lines().collect());
+        push_v30(
+            &mut out,
+            &format!(
+                "COUNTS {} {} {} 0 0",
+                self.atoms.len(),
+                self.bonds.len(),
+                sgroups.len()
+            ),
+        );
+
+        push_v30(&mut out, "BEGIN ATOM");
+        for (atom_idx, atom) in self.atoms.iter().enumerate() {
+            let (x, y, z) = atom.coords_3d.unwrap_or_default();
+            let mut line = format!(
+                "{} {} {:.4} {:.4} {:.4} {}",
+                atom_idx + 1,
+                symbol(atom.element),
+                x,
+                y,
+                z,
+                atom.atom_map
+            );
+            if atom.f_charge != 0 {
+                line.push_str(&format!(" CHG={}", atom.f_charge));
+            }
+            if atom.isotope != 0 {
+                line.push_str(&format!(" MASS={}", atom.isotope));
+            }
+            if let Some(parity) = self.mdl_parity(atom_idx) {
+                line.push_str(&format!(" CFG={}", parity));
+            }
+            match self.valence_mark(atom_idx) {
+                Some(0) => line.push_str(" VAL=-1"),
+                Some(valence) => line.push_str(&format!(" VAL={}", valence)),
+                None => {}
+            }
+            push_v30(&mut out, &line);
+        }
+        push_v30(&mut out, "END ATOM");
+
+        push_v30(&mut out, "BEGIN BOND");
+        for (bond_idx, (atom1, atom2, stereo)) in self.written_bonds().into_iter().enumerate() {
+            let mut line = format!(
+                "{} {} {} {}",
+                bond_idx + 1,
+                bond_types[bond_idx],
+                atom1 + 1,
+                atom2 + 1
+            );
+            match stereo {
+                BondStereo::Wedge => line.push_str(" CFG=1"),
+                BondStereo::Either => line.push_str(" CFG=2"),
+                BondStereo::Hash => line.push_str(" CFG=3"),
+                BondStereo::None => {}
+            }
+            push_v30(&mut out, &line);
+        }
+        push_v30(&mut out, "END BOND");
+
+        if !sgroups.is_empty() {
+            push_v30(&mut out, "BEGIN SGROUP");
+            for sgroup in sgroups {
+                push_v30(&mut out, sgroup);
+            }
+            push_v30(&mut out, "END SGROUP");
+        }
+
+        let groups = self.stereo_groups();
+        if !groups.is_empty() {
+            push_v30(&mut out, "BEGIN COLLECTION");
+            for (name, atoms) in groups {
+                let atoms: Vec<String> = atoms.iter().map(|a| (a + 1).to_string()).collect();
+                let line = format!(
+                    "MDLV30/{} ATOMS=({} {})",
+                    name,
+                    atoms.len(),
+                    atoms.join(" ")
+                );
+                push_v30(&mut out, &line);
+            }
+            push_v30(&mut out, "END COLLECTION");
+        }
+        push_v30(&mut out, "END CTAB");
+        out.push_str("M  END\n");
+        Ok(out)
+    }
+
+    // Title, program and comment lines
+    fn molfile_header(&self) -> String {
+        let title = self.name().and_then(|n| n.lines().next()).unwrap_or("");
+        let flat = self
+            .atoms
+            .iter()
+            .all(|a| a.coords_3d.is_none_or(|c| c.2 == 0.0));
+        let dimension = if flat { "2D" } else { "3D" };
+        format!("{}\n  molrus{:>14}\n\n", title, dimension)
+    }
+
+    // Without coordinates only tetrahedral stereo with a parity is kept
+    fn check_stereo(&self) -> Result<(), MolfileError> {
+        let no_geometry =
+            (self.atoms.iter()).all(|a| a.coords_3d.unwrap_or_default() == (0.0, 0.0, 0.0));
+        if !no_geometry {
+            return Ok(());
+        }
+        let labels = self.cip_labels();
+        if let Some(atom) = (0..self.atoms.len()).find(|&atom_idx| {
+            labels.atoms[atom_idx].is_some() && self.mdl_parity(atom_idx).is_none()
+        }) {
+            return Err(MolfileError::Stereocentre { atom });
+        }
+        match labels.bonds.iter().position(Option::is_some) {
+            Some(bond) => Err(MolfileError::DoubleBondStereo { bond }),
+            None => Ok(()),
+        }
+    }
+
+    // MDL type of each bond: its order, aromatic bonds in their Kekulé form
+    fn mdl_bond_types(&self) -> Result<Vec<i8>, MolfileError> {
+        (self.bonds.iter().enumerate())
+            .map(|(bond_idx, bond)| match bond.bond_order {
+                order @ 1..=3 => Ok(order),
+                order => Err(MolfileError::BondOrder {
+                    bond: bond_idx,
+                    order,
+                }),
+            })
+            .collect()
+    }
+
+    // Total valence to write for an atom whose hydrogens the reader would
+    // not work out from the default valences, 0 for none at all
+    fn valence_mark(&self, atom_idx: usize) -> Option<i32> {
+        let atom = &self.atoms[atom_idx];
+        let valence = self.explicit_valence(atom_idx);
+        if atom.hydrogens == implicit_h_count(atom.element, atom.f_charge, valence) {
+            return None;
+        }
+        Some(valence + atom.hydrogens as i32).filter(|&v| v <= 14)
+    }
+
+    // Atoms of each bond in the order written, with its stereo flag
+    fn written_bonds(&self) -> Vec<(usize, usize, BondStereo)> {
+        let wedges = self.wedge_bonds();
+        let mut crossed = vec![false; self.bonds.len()];
+        if self.atoms.iter().all(|a| a.coords_3d.is_some()) {
+            for bond_idx in self.cip_labels().unspecified_bonds {
+                crossed[bond_idx] = true;
+            }
+        }
+        self.bonds
+            .iter()
+            .enumerate()
+            .map(|(bond_idx, bond)| match wedges[bond_idx] {
+                // A wedge starts at its stereocentre
+                Some((centre, stereo)) => (centre, bond.other(centre), stereo),
+                None if crossed[bond_idx] => (bond.source, bond.dest, BondStereo::Either),
+                None => (bond.source, bond.dest, BondStereo::None),
+            })
+            .collect()
+    }
+
+    // Atoms of each enhanced stereo group by V3000 collection name
+    fn stereo_groups(&self) -> BTreeMap<String, Vec<usize>> {
+        let mut groups: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+        for (atom_idx, atom) in self.atoms.iter().enumerate() {
+            let Some(group) = atom.properties.get(STEREO_GROUP).and_then(|p| p.as_str()) else {
+                continue;
+            };
+            let name = match group {
+                "abs" => "STEABS".to_string(),
+                _ if group.starts_with("and") => format!("STERAC{}", &group[3..]),
+                _ if group.starts_with("or") => format!("STEREL{}", &group[2..]),
+                _ => continue,
+            };
+            groups.entry(name).or_default().push(atom_idx);
+        }
+        groups
+    }
+}
+
+// "M  CHG"-style lines of atom and value pairs
+fn push_property_lines(out: &mut String, tag: &str, entries: &[(usize, i64)]) {
+    for chunk in entries.chunks(ENTRIES_PER_LINE) {
+        out.push_str(&format!("M  {}{:3}", tag, chunk.len()));
+        for (atom_idx, value) in chunk {
+            out.push_str(&format!(" {:3} {:3}", atom_idx + 1, value));
+        }
+        out.push('\n');
+    }
+}
+
+// Appends an "M  V30" line, continued over several when it is too long
+fn push_v30(out: &mut String, line: &str) {
+    let chars: Vec<char> = line.chars().collect();
+    let mut chunks = chars.chunks(V30_WIDTH).peekable();
+    while let Some(chunk) = chunks.next() {
+        out.push_str("M  V30 ");
+        out.extend(chunk);
+        if chunks.peek().is_some() {
+            out.push('-');
+        }
+        out.push('\n');
+    }
+}
@@ -1,5 +1,33 @@
-use crate::core::molecule::Molecule;
-use std::collections::{HashMap, HashSet};
+use crate::core::{
+    configuration::Configuration,
+    defs::Axialness,
+    kekulize::KekulizeError,
+    mendeleev::symbol,
+    molecule::Molecule,
+    properties::NAME,
+    reaction::Reaction,
+    valence::{aromatic_implicit_h_count, implicit_h_count},
+};
+
+// Stand-in for the implicit hydrogen when ordering neighbours around a stereocentre
+const IMPLICIT_H: usize = usize::MAX;
+
+// Per component DFS bookkeeping, filled by `plan_atom` and consumed by `write_atom`
+struct SmilesPlan {
+    visited: Vec<bool>,
+    // Tree children of each atom, in output order
+    children: Vec<Vec<usize>>,
+    // Ring closure bonds touching each atom, in output order
+    closures: Vec<Vec<usize>>,
+    // Label currently assigned to an open ring closure bond
+    open_labels: Vec<Option<usize>>,
+    labels_in_use: Vec<bool>,
+    // Directional bonds tied together through double bonds share a group,
+    // keyed by one of its bonds; a group is flipped as a whole when needed
+    // to make its first written mark a '/'
+    direction_group: Vec<usize>,
+    flip_direction: Vec<Option<bool>>,
+}
 
 impl Molecule {
     pub fn mol_to_smiles(&self, canonical: bool) -> String {
@@ -8,170 +36,431 @@ impl Molecule {
         }
 
         let n = self.atoms.len();
-        let mut idx = 0..n;
-
-        // If canonical, replace default order 0..n with canonical order
-        let mut canonical_indices = (0..n).collect();
-        if canonical {
-            canonical_indices = self.compute_canonical_order();
-        }
-
-        let mut visited = HashSet::new();
-        let mut result = String::new();
-        let mut ring_closures = HashMap::new(); // canonical_atom_idx -> Vec<closure_label>
-        let mut closure_labels: Vec<u8> = (1..=9).collect();
-        let mut next_label_idx = 0;
-
-        let start = canonical_indices[0];
-        self.visit_atom_with_indices(
-            start,
-            &canonical_indices,
-            &mut idx,
-            &mut visited,
-            &mut result,
-            &mut ring_closures,
-            &mut closure_labels,
-            &mut next_label_idx,
-        );
-
-        result
-    }
-
-    /// Simple canonical order: sort by (element, degree, aromaticity, charge)
-    /// Replace this with InChI‑based labels for full standard canonical SMILES.
-    fn compute_canonical_order(&self) -> Vec<usize> {
-        let mut ids: Vec<usize> = (0..self.atoms.len()).collect();
-        ids.sort_by(|&i, &j| {
-            let a = &self.atoms[i];
-            let b = &self.atoms[j];
-
-            a.element
-                .cmp(&b.element)
-                .then(a.hydrogens.cmp(&b.hydrogens))
-                .then(a.f_charge.cmp(&b.f_charge))
-                .then(a.aromatic.cmp(&b.aromatic))
-                .then_with(|| self.atom_degree(i).cmp(&self.atom_degree(j)))
-                .then(i.cmp(&j))
-        });
-        ids
+
+        // Atoms are visited in rank order, canonical ranks make the output unique
+        let rank = if canonical {
+            self.canonical_ranks()
+        } else {
+            (0..n).collect()
+        };
+        let mut order: Vec<usize> = (0..n).collect();
+        order.sort_by_key(|&atom_idx| rank[atom_idx]);
+
+        let mut plan = SmilesPlan {
+            visited: vec![false; n],
+            children: vec![Vec::new(); n],
+            closures: vec![Vec::new(); n],
+            open_labels: vec![None; self.bonds.len()],
+            labels_in_use: Vec::new(),
+            direction_group: self.direction_groups(),
+            flip_direction: vec![None; self.bonds.len()],
+        };
+
+        // One DFS per connected component, joined with '.'
+        let mut components = Vec::new();
+        for &start in &order {
+            if plan.visited[start] {
+                continue;
+            }
+            self.plan_atom(start, None, &rank, &mut plan);
+            let mut output = String::new();
+            self.write_atom(start, None, &mut plan, &mut output);
+            components.push(output);
+        }
+
+        components.join(".")
     }
 
-    /// Degree = number of bonds, not counting order.
-    fn atom_degree(&self, atom_idx: usize) -> usize {
-        self.bonds
-            .iter()
-            .filter(|b| b.source == atom_idx || b.dest == atom_idx)
-            .count()
+    /// A SMILES record: the SMILES, then the molecule's name after a space
+    /// when it has one.
+    pub fn mol_to_smiles_record(&self, canonical: bool) -> String {
+        let smiles = self.mol_to_smiles(canonical);
+        match self.name() {
+            Some(name) => format!("{} {}", smiles, name),
+            None => smiles,
+        }
     }
 
-    fn visit_atom_with_indices(
-        &self,
-        atom_idx: usize,
-        canonical_indices: &[usize],
-        idx: &mut std::ops::Range<usize>,
-        visited: &mut HashSet<usize>,
-        output: &mut String,
-        ring_closures: &mut HashMap<usize, Vec<u8>>,
-        closure_labels: &mut Vec<u8>,
-        next_label_idx: &mut usize,
-    ) {
-        let atom_idx = canonical_indices[idx.clone().next().unwrap_or(atom_idx)];
-        idx.next();
+    /// SMILES in Kekulé form: uppercase atoms with explicit double bonds.
+    pub fn mol_to_kekule_smiles(&self, canonical: bool) -> Result<String, KekulizeError> {
+        let mut kekule = self.clone();
+        kekule.kekulize()?;
+        for atom in kekule.atoms.iter_mut() {
+            atom.aromatic = false;
+        }
+        for bond in kekule.bonds.iter_mut() {
+            bond.arom = false;
+        }
+        Ok(kekule.mol_to_smiles(canonical))
+    }
 
-        if visited.contains(&atom_idx) {
-            let label =
-                self.allocate_ring_label(atom_idx, ring_closures, closure_labels, next_label_idx);
-            output.push_str(&label.to_string());
-            return;
+    /// The atom as a SMILES token, with its configuration as stored.
+    pub fn atom_to_smiles_str(&self, atom_idx: usize) -> String {
+        self.atom_token(atom_idx, self.atoms[atom_idx].configuration.as_ref())
+    }
+
+    fn atom_token(&self, atom_idx: usize, configuration: Option<&Configuration>) -> String {
+        let atom = &self.atoms[atom_idx];
+        let chirality = configuration.map_or(String::new(), configuration_to_smiles);
+
+        let organic = is_organic(atom.element) && (!atom.aromatic || can_be_aromatic(atom.element));
+
+        // When do we need brackets?
+        let needs_brackets = !organic
+            || atom.isotope != 0
+            || atom.f_charge != 0
+            || !chirality.is_empty()
+            || atom.atom_map != 0
+            || atom.hydrogens != self.smiles_implicit_h(atom_idx);
+
+        let mut sym = symbol(atom.element).to_string();
+        if atom.aromatic {
+            // SMILES aromatic atoms: lowercase
+            sym = sym.to_lowercase();
         }
 
-        visited.insert(atom_idx);
-        let atom_str = self.atom_to_smiles_str(atom_idx);
-        output.push_str(&atom_str);
+        if !needs_brackets {
+            return sym;
+        }
 
-        let mut neighbors: Vec<usize> = self
-            .bonds
+        let mut s = String::from("[");
+        if atom.isotope != 0 {
+            s.push_str(&atom.isotope.to_string());
+        }
+        s.push_str(&sym);
+        s.push_str(&chirality);
+        match atom.hydrogens {
+            0 => {}
+            1 => s.push('H'),
+            h => s.push_str(&format!("H{}", h)),
+        }
+        match atom.f_charge {
+            0 => {}
+            1 => s.push('+'),
+            -1 => s.push('-'),
+            c if c > 0 => s.push_str(&format!("+{}", c)),
+            c => s.push_str(&c.to_string()), // already has minus sign
+        }
+        if atom.atom_map != 0 {
+            s.push_str(&format!(":{}", atom.atom_map));
+        }
+        s.push(']');
+        s
+    }
+
+    /// Hydrogens a SMILES reader would infer for this atom written without brackets.
+    fn smiles_implicit_h(&self, atom_idx: usize) -> usize {
+        let atom = &self.atoms[atom_idx];
+        let valence: i32 = self
+            .bonds_of(atom_idx)
             .iter()
-            .filter(|b| b.source == atom_idx || b.dest == atom_idx)
-            .map(|b| {
-                if b.source == atom_idx {
-                    b.dest
-                } else {
-                    b.source
-                }
+            .map(|&b| match &self.bonds[b] {
+                // Readers take aromatic bonds as single whatever the Kekulé form
+                bond if bond.arom => 1,
+                bond => bond.bond_order as i32,
             })
+            .sum();
+        // Unbracketed atoms are read as uncharged
+        if atom.aromatic {
+            aromatic_implicit_h_count(atom.element, 0, valence)
+        } else {
+            implicit_h_count(atom.element, 0, valence)
+        }
+    }
+
+    // First pass: build the DFS spanning tree and find the ring closure bonds
+    fn plan_atom(
+        &self,
+        atom_idx: usize,
+        parent_bond: Option<usize>,
+        rank: &[usize],
+        plan: &mut SmilesPlan,
+    ) {
+        plan.visited[atom_idx] = true;
+
+        let mut bonds: Vec<usize> = self
+            .bonds_of(atom_idx)
+            .iter()
+            .copied()
+            .filter(|&i| Some(i) != parent_bond)
             .collect();
+        bonds.sort_by_key(|&i| rank[self.bonds[i].other(atom_idx)]);
 
-        neighbors.sort_by(|&i, &j| {
-            canonical_indices
-                .iter()
-                .position(|&x| x == i)
-                .cmp(&canonical_indices.iter().position(|&x| x == j))
-                .then(i.cmp(&j))
-        });
+        for bond_idx in bonds {
+            let neighbor = self.bonds[bond_idx].other(atom_idx);
+            if !plan.visited[neighbor] {
+                plan.children[atom_idx].push(bond_idx);
+                self.plan_atom(neighbor, Some(bond_idx), rank, plan);
+            } else if !plan.closures[neighbor].contains(&bond_idx) {
+                // Back edge to an atom already on the tree closes a ring
+                plan.closures[neighbor].push(bond_idx);
+                plan.closures[atom_idx].push(bond_idx);
+            }
+        }
+    }
 
-        if neighbors.is_empty() {
-            return;
+    // Second pass: emit atoms, ring closure digits and branches
+    fn write_atom(
+        &self,
+        atom_idx: usize,
+        from: Option<usize>,
+        plan: &mut SmilesPlan,
+        output: &mut String,
+    ) {
+        let atom = &self.atoms[atom_idx];
+
+        // Neighbour order as it will appear in the output
+        let mut written_order = Vec::new();
+        written_order.extend(from);
+        if atom.hydrogens > 0 {
+            written_order.push(IMPLICIT_H);
+        }
+        for &bond_idx in &plan.closures[atom_idx] {
+            written_order.push(self.bonds[bond_idx].other(atom_idx));
+        }
+        for &bond_idx in &plan.children[atom_idx] {
+            written_order.push(self.bonds[bond_idx].other(atom_idx));
         }
 
-        for i in 0..neighbors.len() {
-            let neighbor = neighbors[i];
-            let bond = self.get_bond(atom_idx, neighbor).unwrap();
-            let mut open_branch = false;
+        let configuration = atom
+            .configuration
+            .as_ref()
+            .map(|c| self.written_configuration(atom_idx, c, &written_order));
+        output.push_str(&self.atom_token(atom_idx, configuration.as_ref()));
 
-            if i > 0 {
-                open_branch = true;
-                output.push('(');
+        for bond_idx in plan.closures[atom_idx].clone() {
+            let label = match plan.open_labels[bond_idx].take() {
+                Some(label) => {
+                    plan.labels_in_use[label] = false;
+                    label
+                }
+                None => {
+                    self.write_bond(bond_idx, atom_idx, plan, output);
+                    let label = allocate_ring_label(&mut plan.labels_in_use);
+                    plan.open_labels[bond_idx] = Some(label);
+                    label
+                }
+            };
+            if label < 10 {
+                output.push_str(&label.to_string());
+            } else if label < 100 {
+                output.push_str(&format!("%{}", label));
+            } else {
+                output.push_str(&format!("%({})", label));
             }
+        }
 
-            let bond_str = self.bond_order_to_smiles(bond.bond_order);
-            if !bond_str.is_empty() {
-                output.push_str(&bond_str);
+        let children = plan.children[atom_idx].clone();
+        for (i, &bond_idx) in children.iter().enumerate() {
+            let branch = i + 1 < children.len();
+            if branch {
+                output.push('(');
             }
-
-            self.visit_atom_with_indices(
-                neighbor,
-                canonical_indices,
-                idx,
-                visited,
+            self.write_bond(bond_idx, atom_idx, plan, output);
+            self.write_atom(
+                self.bonds[bond_idx].other(atom_idx),
+                Some(atom_idx),
+                plan,
                 output,
-                ring_closures,
-                closure_labels,
-                next_label_idx,
             );
-
-            if open_branch {
+            if branch {
                 output.push(')');
             }
         }
     }
 
-    fn allocate_ring_label(
+    /// Re-expresses a tetrahedral configuration for a new neighbour order.
+    /// The stored parity follows `outgoing_bond` order with an implicit
+    /// hydrogen counted straight after the first neighbour.
+    fn written_configuration(
         &self,
         atom_idx: usize,
-        ring_closures: &mut HashMap<usize, Vec<u8>>,
-        closure_labels: &mut Vec<u8>,
-        next_label_idx: &mut usize,
-    ) -> u8 {
-        if let Some(&label) = closure_labels.get(*next_label_idx) {
-            *next_label_idx += 1;
-            ring_closures
-                .entry(atom_idx)
-                .or_insert_with(Vec::new)
-                .push(label);
-            label
+        configuration: &Configuration,
+        written_order: &[usize],
+    ) -> Configuration {
+        let atom = &self.atoms[atom_idx];
+        let mut stored_order: Vec<usize> = atom
+            .outgoing_bond
+            .iter()
+            .map(|&b| self.bonds[b].other(atom_idx))
+            .collect();
+        if atom.hydrogens > 0 {
+            stored_order.insert(stored_order.len().min(1), IMPLICIT_H);
+        }
+
+        match configuration {
+            Configuration::TH1 | Configuration::TH2 => {}
+            // Only tetrahedral centres are re-ordered for now
+            _ => return configuration.clone(),
+        }
+        if stored_order.len() != written_order.len() {
+            return configuration.clone();
+        }
+
+        let mut permutation: Vec<usize> = Vec::with_capacity(written_order.len());
+        for atom in written_order {
+            match stored_order.iter().position(|a| a == atom) {
+                Some(position) => permutation.push(position),
+                None => return configuration.clone(),
+            }
+        }
+        if permutation_is_odd(&permutation) {
+            match configuration {
+                Configuration::TH1 => Configuration::TH2,
+                _ => Configuration::TH1,
+            }
         } else {
-            *closure_labels.first().unwrap_or(&1)
+            configuration.clone()
+        }
+    }
+
+    fn write_bond(&self, bond_idx: usize, from: usize, plan: &mut SmilesPlan, output: &mut String) {
+        let written = self.bond_to_smiles(bond_idx, from);
+        if written != "/" && written != "\\" {
+            output.push_str(&written);
+            return;
+        }
+        // F/C=C/F and F\C=C\F are the same molecule, always write the first
+        let group = plan.direction_group[bond_idx];
+        let flip = *plan.flip_direction[group].get_or_insert(written == "\\");
+        output.push_str(match (written.as_str(), flip) {
+            ("/", false) | ("\\", true) => "/",
+            _ => "\\",
+        });
+    }
+
+    // Union of the directional bonds around each double bond
+    fn direction_groups(&self) -> Vec<usize> {
+        let mut group: Vec<usize> = (0..self.bonds.len()).collect();
+        fn root(group: &mut [usize], mut bond_idx: usize) -> usize {
+            while group[bond_idx] != bond_idx {
+                group[bond_idx] = group[group[bond_idx]];
+                bond_idx = group[bond_idx];
+            }
+            bond_idx
+        }
+        for bond in &self.bonds {
+            if bond.bond_order != 2 || bond.arom {
+                continue;
+            }
+            let marked: Vec<usize> = [bond.source, bond.dest]
+                .iter()
+                .flat_map(|&end| self.bonds_of(end).iter().copied())
+                .filter(|&b| self.bonds[b].axialness != Axialness::UNKNOWN)
+                .collect();
+            for pair in marked.windows(2) {
+                let (a, b) = (root(&mut group, pair[0]), root(&mut group, pair[1]));
+                group[a] = b;
+            }
         }
+        (0..self.bonds.len()).map(|b| root(&mut group, b)).collect()
     }
 
-    fn bond_order_to_smiles(&self, order: i8) -> String {
-        match order {
-            1 => String::new(), // often elided between organic atoms; kekulize can add where needed
+    fn bond_to_smiles(&self, bond_idx: usize, from: usize) -> String {
+        let bond = &self.bonds[bond_idx];
+        let both_aromatic = self.atoms[bond.source].aromatic && self.atoms[bond.dest].aromatic;
+
+        if bond.arom {
+            return if both_aromatic {
+                String::new()
+            } else {
+                ":".to_string()
+            };
+        }
+        match bond.bond_order {
+            1 => {
+                // Directional bonds flip when written from the other end
+                let forward = bond.source == from;
+                match (&bond.axialness, forward) {
+                    (Axialness::UP, true) | (Axialness::DOWN, false) => "/".to_string(),
+                    (Axialness::DOWN, true) | (Axialness::UP, false) => "\\".to_string(),
+                    // A single bond between aromatic atoms must be explicit
+                    _ if both_aromatic => "-".to_string(),
+                    _ => String::new(),
+                }
+            }
             2 => "=".to_string(),
             3 => "#".to_string(),
             4 => "$".to_string(),
-            _ => "".to_string(),
+            _ => String::new(),
+        }
+    }
+}
+
+impl Reaction {
+    /// Reaction SMILES `reactants>agents>products`. Canonical output also
+    /// sorts the molecules on each side.
+    pub fn rxn_to_smiles(&self, canonical: bool) -> String {
+        let side = |molecules: &[Molecule]| {
+            let mut parts: Vec<String> = molecules
+                .iter()
+                .map(|molecule| molecule.mol_to_smiles(canonical))
+                .filter(|smiles| !smiles.is_empty())
+                .collect();
+            if canonical {
+                parts.sort_unstable();
+            }
+            parts.join(".")
+        };
+        [
+            side(&self.reactants),
+            side(&self.agents),
+            side(&self.products),
+        ]
+        .join(">")
+    }
+
+    /// A reaction SMILES record, followed by the reaction's name if it has one.
+    pub fn rxn_to_smiles_record(&self, canonical: bool) -> String {
+        let smiles = self.rxn_to_smiles(canonical);
+        match self.properties.get(NAME).and_then(|p| p.as_str()) {
+            Some(name) => format!("{} {}", smiles, name),
+            None => smiles,
+        }
+    }
+}
+
+fn allocate_ring_label(labels_in_use: &mut Vec<bool>) -> usize {
+    // Lowest free label, 0 is skipped to keep the output readable
+    let label = (1..labels_in_use.len())
+        .find(|&l| !labels_in_use[l])
+        .unwrap_or(labels_in_use.len().max(1));
+    if label >= labels_in_use.len() {
+        labels_in_use.resize(label + 1, false);
+    }
+    labels_in_use[label] = true;
+    label
+}
+
+fn permutation_is_odd(permutation: &[usize]) -> bool {
+    let mut inversions = 0;
+    for i in 0..permutation.len() {
+        for j in i + 1..permutation.len() {
+            if permutation[i] > permutation[j] {
+                inversions += 1;
+            }
         }
     }
+    inversions % 2 == 1
+}
+
+fn configuration_to_smiles(configuration: &Configuration) -> String {
+    match configuration {
+        Configuration::TH1 => "@".to_string(),
+        Configuration::TH2 => "@@".to_string(),
+        // Double bond stereo lives on the bonds, not in the atom token
+        Configuration::DB1 | Configuration::DB2 | Configuration::Unknown => String::new(),
+        // The remaining variant names match their SMILES spelling (AL1, TB12, OH30...)
+        other => format!("@{:?}", other),
+    }
+}
+
+// organic subset atomic numbers: 5,6,7,8,15,16,9,17,35,53 and the * wildcard
+const fn is_organic(z: usize) -> bool {
+    matches!(z, 0 | 5 | 6 | 7 | 8 | 15 | 16 | 9 | 17 | 35 | 53)
+}
+
+// Elements with a lowercase form outside brackets
+const fn can_be_aromatic(z: usize) -> bool {
+    matches!(z, 0 | 5 | 6 | 7 | 8 | 15 | 16)
 }